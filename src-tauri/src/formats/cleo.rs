@@ -0,0 +1,396 @@
+use crate::game::descriptors::find_game_descriptor;
+use crate::game::types::{
+    ApiResponse, CleoPluginUsage, CleoScriptInfo, GameDescriptor, MissingCleoPlugin,
+    PlannedFileCopy,
+};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// 指令参数的种类
+#[derive(Clone, Copy)]
+enum Param {
+    /// 整数（常量或变量）
+    Int,
+    /// 浮点数（常量或变量）
+    Float,
+    /// 字符串（常量、字符串变量或保存字符串地址的变量）
+    Str,
+    /// 保存结果的变量
+    Out,
+}
+
+use Param::{Float, Int, Out, Str};
+
+/// 由 CLEO 插件或特定 CLEO 版本提供的指令
+struct KnownOpcode {
+    opcode: u16,
+    plugin: Option<&'static str>, // 为空时为 CLEO 5 的核心指令
+    params: &'static [Param],
+}
+
+const fn plugin(opcode: u16, name: &'static str, params: &'static [Param]) -> KnownOpcode {
+    KnownOpcode {
+        opcode,
+        plugin: Some(name),
+        params,
+    }
+}
+
+/// 随 G2M 提供的 CLEO 插件（G2M/Module 下的 cleo/*.cleo 和 CLEO_PLUGINS/*.cleo）的指令
+/// 以及 CLEO 5 新增的核心指令；参数格式来自 CLEO SDK 的示例插件
+const KNOWN_OPCODES: &[KnownOpcode] = &[
+    plugin(0x0AF0, "IniFiles", &[Str, Str, Str, Out]),
+    plugin(0x0AF1, "IniFiles", &[Int, Str, Str, Str]),
+    plugin(0x0AF2, "IniFiles", &[Str, Str, Str, Out]),
+    plugin(0x0AF3, "IniFiles", &[Float, Str, Str, Str]),
+    plugin(0x0AF4, "IniFiles", &[Str, Str, Str, Out]),
+    plugin(0x0AF5, "IniFiles", &[Str, Str, Str, Str]),
+    plugin(0x0B00, "FileSystemOperations", &[Str]),
+    plugin(0x0B01, "FileSystemOperations", &[Str, Int]),
+    plugin(0x0B02, "FileSystemOperations", &[Str, Str]),
+    plugin(0x0B03, "FileSystemOperations", &[Str, Str]),
+    plugin(0x0B04, "FileSystemOperations", &[Str, Str]),
+    plugin(0x0B05, "FileSystemOperations", &[Str, Str]),
+    plugin(0x0B10, "IntOperations", &[Int, Int, Out]),
+    plugin(0x0B11, "IntOperations", &[Int, Int, Out]),
+    plugin(0x0B12, "IntOperations", &[Int, Int, Out]),
+    plugin(0x0B13, "IntOperations", &[Int, Out]),
+    plugin(0x0B14, "IntOperations", &[Int, Int, Out]),
+    plugin(0x0B15, "IntOperations", &[Int, Int, Out]),
+    plugin(0x0B16, "IntOperations", &[Int, Int, Out]),
+    plugin(0x0B17, "IntOperations", &[Out, Int]),
+    plugin(0x0B18, "IntOperations", &[Out, Int]),
+    plugin(0x0B19, "IntOperations", &[Out, Int]),
+    plugin(0x0B1A, "IntOperations", &[Out]),
+    plugin(0x0B1B, "IntOperations", &[Out, Int]),
+    plugin(0x0B1C, "IntOperations", &[Out, Int]),
+    plugin(0x0B1D, "IntOperations", &[Out, Int]),
+    plugin(0x0B20, "ClipboardControl", &[Int, Int]),
+    plugin(0x0B21, "ClipboardControl", &[Int, Int]),
+    KnownOpcode {
+        opcode: 0x2000,
+        plugin: None,
+        params: &[Str, Out],
+    },
+    KnownOpcode {
+        opcode: 0x2001,
+        plugin: None,
+        params: &[Int, Int, Out],
+    },
+];
+
+/// 给浮点变量赋值、加减浮点数的原版指令，三个游戏中格式相同，用于判断浮点常量的长度
+const FLOAT_OPCODES: &[u16] = &[0x0005, 0x0007, 0x0009, 0x000B, 0x000D, 0x000F];
+
+/// CLEO 脚本的扩展名：.cs（SA 的 CLEO 3/4 另有 .cs3/.cs4）和自定义任务 .cm
+const SCRIPT_EXTENSIONS: &[&str] = &["cs", "cs3", "cs4", "cm"];
+
+/// 是否为 CLEO 脚本（按扩展名判断）
+pub fn is_cleo_script(name: &str) -> bool {
+    Path::new(name).extension().is_some_and(|e| {
+        SCRIPT_EXTENSIONS
+            .iter()
+            .any(|ext| e.eq_ignore_ascii_case(ext))
+    })
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn is_text(bytes: &[u8]) -> bool {
+    bytes.iter().all(|&b| b == 0 || b >= 0x20)
+}
+
+/// 可能出现在指令之后的下一条指令（原版指令、CLEO 及常见插件的范围）
+fn is_plausible_opcode(opcode: u16) -> bool {
+    let opcode = opcode & 0x7FFF;
+    opcode < 0x1000 || (0x2000..0x2100).contains(&opcode)
+}
+
+/// 解析一个参数，返回参数的字节数和是否使用了 SA 特有的数据类型
+/// float_width 为浮点常量的字节数：III 为 2（定点数），VC/SA 为 4
+fn read_param(
+    data: &[u8],
+    offset: usize,
+    param: Param,
+    float_width: usize,
+) -> Option<(usize, bool)> {
+    let kind = *data.get(offset)?;
+    let size = match kind {
+        0x01 => 5,
+        0x02 | 0x05 => 3,
+        // 局部变量的编号超出范围时不是有效的参数
+        0x03 if read_u16(data, offset + 1)? < 1024 => 3,
+        0x04 => 2,
+        0x06 => 1 + float_width,
+        // SA 的数组元素：偏移、索引变量、数组长度、元素类型
+        0x07 | 0x08 | 0x0C | 0x0D => 7,
+        // SA 的字符串变量
+        0x0A | 0x0B | 0x10 | 0x11 => 3,
+        // SA 的 8 字节和 16 字节字符串常量
+        0x09 => 9,
+        0x0F => 17,
+        // SA 的变长字符串常量：长度 + 内容
+        0x0E => 2 + *data.get(offset + 1)? as usize,
+        // III/VC 没有类型字节的 8 字节字符串
+        0x20..=0x7E => 8,
+        _ => return None,
+    };
+    let bytes = data.get(offset..offset + size)?;
+    let accepted = match param {
+        Int => matches!(kind, 0x01..=0x05 | 0x07 | 0x08),
+        Float => matches!(kind, 0x02 | 0x03 | 0x06..=0x08),
+        Out => matches!(kind, 0x02 | 0x03 | 0x07 | 0x08 | 0x0A..=0x0D | 0x10 | 0x11),
+        Str => match kind {
+            0x09 | 0x0F => is_text(&bytes[1..]),
+            0x0E => is_text(&bytes[2..]),
+            0x20..=0x7E => is_text(bytes),
+            _ => matches!(kind, 0x02 | 0x03 | 0x0A..=0x0D | 0x10 | 0x11),
+        },
+    };
+    accepted.then_some((size, matches!(kind, 0x07..=0x11)))
+}
+
+/// 在 offset 处按已知格式解析指令，成功时返回指令之后的位置和是否使用了 SA 特有的数据类型
+fn match_opcode(
+    data: &[u8],
+    offset: usize,
+    known: &KnownOpcode,
+    float_width: usize,
+) -> Option<(usize, bool)> {
+    let mut position = offset + 2;
+    let mut sa_only = false;
+    for &param in known.params {
+        let (size, sa) = read_param(data, position, param, float_width)?;
+        position += size;
+        sa_only |= sa;
+    }
+    // 后面紧跟下一条指令（或脚本结束）才认为匹配
+    match read_u16(data, position) {
+        Some(next) if !is_plausible_opcode(next) => None,
+        _ => Some((position, sa_only)),
+    }
+}
+
+/// 统计浮点赋值指令的浮点常量更像 2 字节（III）还是 4 字节（VC/SA）
+fn float_width_votes(data: &[u8]) -> (usize, usize) {
+    let (mut short, mut long) = (0, 0);
+    for offset in 0..data.len().saturating_sub(8) {
+        let Some(opcode) = read_u16(data, offset) else {
+            break;
+        };
+        if !FLOAT_OPCODES.contains(&opcode)
+            || !matches!(data[offset + 2], 0x02 | 0x03)
+            || data[offset + 5] != 0x06
+        {
+            continue;
+        }
+        let value = offset + 6;
+        let short_fits = read_u16(data, value + 2).is_none_or(is_plausible_opcode);
+        let long_fits = data
+            .get(value..value + 4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .is_some_and(|f| f == 0.0 || (f.is_finite() && (1e-4..1e7).contains(&f.abs())))
+            && read_u16(data, value + 4).is_none_or(is_plausible_opcode);
+        match (short_fits, long_fits) {
+            (true, false) => short += 1,
+            (false, true) => long += 1,
+            _ => {}
+        }
+    }
+    (short, long)
+}
+
+/// 分析编译后的 CLEO 脚本：推测适用的游戏、需要的 CLEO 版本和插件
+/// 脚本没有文件头，这里在字节码中查找已知的插件指令并按参数格式校验，结果只是推测
+pub fn analyze_script(data: &[u8]) -> CleoScriptInfo {
+    let (short, long) = float_width_votes(data);
+    let float_width = if short > long { 2 } else { 4 };
+
+    let mut plugins: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    let mut sa_only = false;
+    let mut cleo5 = false;
+    let mut offset = 0;
+    while offset + 2 <= data.len() {
+        let opcode = read_u16(data, offset).unwrap_or_default() & 0x7FFF;
+        let matched = KNOWN_OPCODES
+            .iter()
+            .filter(|k| k.opcode == opcode)
+            .find_map(|k| match_opcode(data, offset, k, float_width).map(|m| (k, m)));
+        let Some((known, (next, sa))) = matched else {
+            offset += 1;
+            continue;
+        };
+        sa_only |= sa;
+        match known.plugin {
+            Some(name) => {
+                let opcodes = plugins.entry(name).or_default();
+                let opcode = format!("{:04X}", known.opcode);
+                if !opcodes.contains(&opcode) {
+                    opcodes.push(opcode);
+                }
+            }
+            None => cleo5 = true,
+        }
+        offset = next;
+    }
+
+    let games: &[&str] = if sa_only || cleo5 {
+        &["gtasa"]
+    } else if short > long {
+        &["gta3"]
+    } else if long > short {
+        &["gtavc", "gtasa"]
+    } else {
+        &["gta3", "gtavc", "gtasa"]
+    };
+    let cleo_version = match games {
+        ["gtasa"] if cleo5 => Some("5"),
+        ["gtasa"] => Some("4"),
+        _ if !games.contains(&"gtasa") => Some("2"),
+        _ => None,
+    };
+    CleoScriptInfo {
+        games: games.iter().map(|g| g.to_string()).collect(),
+        cleo_version: cleo_version.map(str::to_string),
+        plugins: plugins
+            .into_iter()
+            .map(|(plugin, mut opcodes)| {
+                opcodes.sort();
+                CleoPluginUsage {
+                    plugin: plugin.to_string(),
+                    opcodes,
+                }
+            })
+            .collect(),
+        text_file: None,
+    }
+}
+
+/// 插件文件名的版本前缀（CLEO 2 和 CLEO 5 的插件文件名带前缀）对应的游戏
+const PLUGIN_PREFIXES: [(&str, &str); 3] = [("iii.", "gta3"), ("vc.", "gtavc"), ("sa.", "gtasa")];
+
+/// 插件文件名对应的插件名称：去掉 .cleo 扩展名（不区分大小写）和 "III."、"VC."、"SA." 前缀
+/// 带前缀的插件只适用于对应的游戏，engine 已知且不符时返回 None
+fn plugin_name(file_name: &str, engine: Option<&str>) -> Option<String> {
+    let split = file_name.len().checked_sub(".cleo".len())?;
+    if !file_name.is_char_boundary(split) || !file_name[split..].eq_ignore_ascii_case(".cleo") {
+        return None;
+    }
+    let stem = file_name[..split].to_ascii_lowercase();
+    for (prefix, game) in PLUGIN_PREFIXES {
+        if let Some(name) = stem.strip_prefix(prefix) {
+            return engine
+                .is_none_or(|engine| engine == game)
+                .then(|| name.to_string());
+        }
+    }
+    Some(stem)
+}
+
+/// 游戏目录中已安装的 CLEO 插件：cleo/*.cleo、cleo/cleo_plugins/*.cleo（CLEO 5）
+/// 以及 CLEO Redux 的 plugins/CLEO/CLEO_PLUGINS/*.cleo，插件目录名不区分大小写
+fn installed_plugins(game_dir: &Path, descriptor: Option<&GameDescriptor>) -> Vec<String> {
+    let engine = descriptor.map(|d| d.engine.as_str());
+    let mut bases = vec![
+        game_dir.join("CLEO"),
+        game_dir.join("cleo"),
+        game_dir.join("plugins").join("CLEO"),
+    ];
+    if let Some(cleo_dir) = descriptor.map(|d| game_dir.join(&d.cleo_dir)) {
+        if !bases.contains(&cleo_dir) {
+            bases.push(cleo_dir);
+        }
+    }
+    let mut dirs = Vec::new();
+    for base in bases {
+        let Ok(entries) = fs::read_dir(&base) else {
+            continue;
+        };
+        dirs.extend(entries.flatten().map(|e| e.path()).filter(|path| {
+            path.is_dir()
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.eq_ignore_ascii_case("cleo_plugins"))
+        }));
+        dirs.push(base);
+    }
+    let mut plugins = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if let Some(name) = plugin_name(&entry.file_name().to_string_lossy(), engine) {
+                plugins.push(name);
+            }
+        }
+    }
+    plugins
+}
+
+/// 检查即将安装的 CLEO 脚本需要的插件是否已安装（或随 MOD 一起安装）
+/// 插件目录和插件适用的游戏按游戏描述（自定义游戏按其基于的引擎）确定
+/// 只报告插件缺失，不阻止安装；无法读取的脚本不报告
+pub fn check_cleo_plugins(
+    game_dir: &Path,
+    game_type: Option<&str>,
+    copies: &[PlannedFileCopy],
+) -> Vec<MissingCleoPlugin> {
+    let scripts: Vec<&PlannedFileCopy> = copies
+        .iter()
+        .filter(|c| is_cleo_script(&c.target))
+        .collect();
+    if scripts.is_empty() {
+        return Vec::new();
+    }
+    let descriptor = game_type.and_then(find_game_descriptor);
+    let engine = descriptor.map(|d| d.engine.as_str());
+    let mut available = installed_plugins(game_dir, descriptor);
+    available.extend(copies.iter().filter_map(|c| {
+        let name = Path::new(&c.target)
+            .file_name()?
+            .to_string_lossy()
+            .to_string();
+        plugin_name(&name, engine)
+    }));
+
+    let mut missing = Vec::new();
+    for copy in scripts {
+        let Ok(data) = fs::read(&copy.source) else {
+            continue;
+        };
+        for usage in analyze_script(&data).plugins {
+            if available.contains(&usage.plugin.to_ascii_lowercase()) {
+                continue;
+            }
+            missing.push(MissingCleoPlugin {
+                file: copy.target.clone(),
+                plugin: usage.plugin,
+                opcodes: usage.opcodes,
+            });
+        }
+    }
+    missing
+}
+
+/// 分析 CLEO 脚本（.cs/.cm）适用的游戏、需要的 CLEO 版本和插件
+#[tauri::command]
+pub async fn analyze_cleo_script(path: String) -> Result<ApiResponse<CleoScriptInfo>, String> {
+    let script = Path::new(&path);
+    let data = match fs::read(script) {
+        Ok(data) => data,
+        Err(e) => return Ok(ApiResponse::error(format!("无法读取文件 {}: {}", path, e))),
+    };
+    let mut info = analyze_script(&data);
+    // CLEO 脚本的文本通常放在同名的 FXT 文件中
+    info.text_file = ["fxt", "FXT"]
+        .iter()
+        .map(|ext| script.with_extension(ext))
+        .find(|p| p.is_file())
+        .map(|p| p.to_string_lossy().to_string());
+    Ok(ApiResponse::success(info))
+}
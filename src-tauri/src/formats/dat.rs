@@ -0,0 +1,562 @@
+use crate::game::descriptors::{find_game_descriptor, game_descriptors};
+use crate::game::types::{ApiResponse, DatEditResult, DatEntry, DatFileEntry, DatFileInfo};
+use crate::mod_core::utils::check_relative_path;
+use crate::mod_core::variables::detect_game_type;
+use std::fs;
+use std::path::Path;
+
+/// MOD 写入的行在上方带有该标记，多个 MOD 需要同一行时每个 MOD 一行标记
+/// 游戏把 # 开头的行视为注释，行尾不能追加注释
+const OWNER_MARKER: &str = "# g2m:mod=";
+
+/// .dat 文件中的一行
+enum DatLine {
+    /// 注释、空行等，原样写回
+    Raw(Vec<u8>),
+    /// 关键字 + 参数，如 IDE DATA\MAPS\foo.ide
+    Entry {
+        raw: Vec<u8>,
+        keyword: String,
+        path: String,
+        owners: Vec<u32>,
+    },
+}
+
+/// gta.dat、default.dat 等按行组织的配置文件
+/// 未修改的行按原始字节写回，注释、顺序和换行符保持不变
+pub struct DatFile {
+    lines: Vec<DatLine>,
+    crlf: bool,
+    trailing_newline: bool,
+}
+
+/// 解析条目行，返回（大写关键字, 参数）
+fn parse_entry(line: &[u8]) -> Option<(String, String)> {
+    let text = String::from_utf8_lossy(line);
+    let text = text.trim();
+    if text.is_empty() || text.starts_with('#') {
+        return None;
+    }
+    let (keyword, rest) = text.split_once(char::is_whitespace)?;
+    let path = rest.trim();
+    if path.is_empty() || !is_valid_keyword(keyword) {
+        return None;
+    }
+    Some((keyword.to_uppercase(), path.to_string()))
+}
+
+fn parse_owner(line: &[u8]) -> Option<u32> {
+    String::from_utf8_lossy(line)
+        .trim()
+        .strip_prefix(OWNER_MARKER)?
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn is_valid_keyword(keyword: &str) -> bool {
+    !keyword.is_empty()
+        && keyword
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 比较参数时不区分大小写和分隔符
+fn same_path(a: &str, b: &str) -> bool {
+    a.trim()
+        .replace('/', "\\")
+        .eq_ignore_ascii_case(&b.trim().replace('/', "\\"))
+}
+
+impl DatLine {
+    fn matches(&self, keyword: &str, path: &str) -> bool {
+        matches!(self, DatLine::Entry { keyword: k, path: p, .. }
+            if k.eq_ignore_ascii_case(keyword) && same_path(p, path))
+    }
+}
+
+impl DatFile {
+    pub fn parse(content: &[u8]) -> Self {
+        let crlf = content.windows(2).any(|w| w == b"\r\n");
+        let trailing_newline = content.is_empty() || content.ends_with(b"\n");
+        let body = content.strip_suffix(b"\n").unwrap_or(content);
+
+        let mut lines = Vec::new();
+        let mut pending_markers: Vec<(Vec<u8>, u32)> = Vec::new();
+        if !content.is_empty() {
+            for line in body.split(|&b| b == b'\n') {
+                let line = line.strip_suffix(b"\r").unwrap_or(line).to_vec();
+                if let Some(owner) = parse_owner(&line) {
+                    pending_markers.push((line, owner));
+                    continue;
+                }
+                match parse_entry(&line) {
+                    Some((keyword, path)) => {
+                        let mut owners: Vec<u32> = Vec::new();
+                        for (_, owner) in pending_markers.drain(..) {
+                            if !owners.contains(&owner) {
+                                owners.push(owner);
+                            }
+                        }
+                        lines.push(DatLine::Entry {
+                            raw: line,
+                            keyword,
+                            path,
+                            owners,
+                        });
+                    }
+                    None => {
+                        // 标记后面不是条目时按普通注释保留
+                        lines.extend(
+                            pending_markers
+                                .drain(..)
+                                .map(|(marker, _)| DatLine::Raw(marker)),
+                        );
+                        lines.push(DatLine::Raw(line));
+                    }
+                }
+            }
+        }
+        lines.extend(
+            pending_markers
+                .into_iter()
+                .map(|(marker, _)| DatLine::Raw(marker)),
+        );
+
+        Self {
+            lines,
+            crlf,
+            trailing_newline,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        fs::read(path)
+            .map(|content| Self::parse(&content))
+            .map_err(|e| format!("读取 {} 失败: {}", path.display(), e))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let newline: &[u8] = if self.crlf { b"\r\n" } else { b"\n" };
+        let mut bytes = Vec::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            if index > 0 {
+                bytes.extend_from_slice(newline);
+            }
+            match line {
+                DatLine::Raw(raw) => bytes.extend_from_slice(raw),
+                DatLine::Entry { raw, owners, .. } => {
+                    for owner in owners {
+                        bytes.extend_from_slice(format!("{}{}", OWNER_MARKER, owner).as_bytes());
+                        bytes.extend_from_slice(newline);
+                    }
+                    bytes.extend_from_slice(raw);
+                }
+            }
+        }
+        if self.trailing_newline && !self.lines.is_empty() {
+            bytes.extend_from_slice(newline);
+        }
+        bytes
+    }
+
+    /// 先写入临时文件再替换，避免写入中断时损坏原文件
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let temp = path.with_extension("dat.g2m-tmp");
+        fs::write(&temp, self.to_bytes())
+            .and_then(|_| fs::rename(&temp, path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&temp);
+                format!("写入 {} 失败: {}", path.display(), e)
+            })
+    }
+
+    /// 所有条目及其行号（从 1 开始，包括 MOD 标记所占的行）
+    pub fn entries(&self) -> Vec<DatFileEntry> {
+        let mut entries = Vec::new();
+        let mut line_number = 0;
+        for line in &self.lines {
+            line_number += 1;
+            if let DatLine::Entry {
+                keyword,
+                path,
+                owners,
+                ..
+            } = line
+            {
+                line_number += owners.len();
+                entries.push(DatFileEntry {
+                    line: line_number,
+                    keyword: keyword.clone(),
+                    path: path.clone(),
+                    owner_mod_id: owners.first().copied(),
+                    owner_mod_ids: owners.clone(),
+                });
+            }
+        }
+        entries
+    }
+
+    pub fn contains(&self, keyword: &str, path: &str) -> bool {
+        self.lines.iter().any(|line| line.matches(keyword, path))
+    }
+
+    /// 添加条目，返回是否修改了文件
+    /// 已存在由 MOD 写入的相同条目时只为该行追加 owner，手动添加或原版的行不做修改
+    /// 新行插入到同类关键字的最后一行之后，没有同类条目时插入到最后一个条目之后
+    pub fn add_entry(&mut self, keyword: &str, path: &str, owner: Option<u32>) -> bool {
+        if self.contains(keyword, path) {
+            let Some(owner) = owner else {
+                return false;
+            };
+            let mut changed = false;
+            for line in &mut self.lines {
+                if !line.matches(keyword, path) {
+                    continue;
+                }
+                if let DatLine::Entry { owners, .. } = line {
+                    if !owners.is_empty() && !owners.contains(&owner) {
+                        owners.push(owner);
+                        changed = true;
+                    }
+                }
+            }
+            return changed;
+        }
+        let is_entry = |line: &DatLine| matches!(line, DatLine::Entry { .. });
+        let position = self
+            .lines
+            .iter()
+            .rposition(|line| {
+                matches!(line, DatLine::Entry { keyword: k, .. } if k.eq_ignore_ascii_case(keyword))
+            })
+            .or_else(|| self.lines.iter().rposition(is_entry))
+            .map_or(self.lines.len(), |index| index + 1);
+
+        let keyword = keyword.to_uppercase();
+        let path = path.trim().to_string();
+        self.lines.insert(
+            position,
+            DatLine::Entry {
+                raw: format!("{} {}", keyword, path).into_bytes(),
+                keyword,
+                path,
+                owners: owner.into_iter().collect(),
+            },
+        );
+        true
+    }
+
+    /// 删除所有相同的条目，返回是否删除
+    pub fn remove_entry(&mut self, keyword: &str, path: &str) -> bool {
+        let before = self.lines.len();
+        self.lines.retain(|line| !line.matches(keyword, path));
+        self.lines.len() != before
+    }
+
+    /// 释放指定 MOD 对条目的占用，返回修改的行数
+    /// 只有最后一个 MOD 释放时才删除该行，其他 MOD 仍需要的行保留
+    pub fn remove_owned(&mut self, mod_id: u32) -> usize {
+        let mut changed = 0;
+        self.lines.retain_mut(|line| match line {
+            DatLine::Entry { owners, .. } if owners.contains(&mod_id) => {
+                owners.retain(|&owner| owner != mod_id);
+                changed += 1;
+                !owners.is_empty()
+            }
+            _ => true,
+        });
+        changed
+    }
+}
+
+/// 检查 .dat 文件路径：须为游戏目录内的 .dat 文件
+fn check_dat_file(file: &str) -> Result<(), String> {
+    check_relative_path(file).map_err(|reason| format!("\"{}\" {}", file, reason))?;
+    if !file.trim().to_lowercase().ends_with(".dat") {
+        return Err(format!("\"{}\" 不是 .dat 文件", file));
+    }
+    Ok(())
+}
+
+/// 检查 .dat 条目的格式（不检查文件是否存在）
+pub fn validate_dat_entry(entry: &DatEntry) -> Result<(), String> {
+    if let Some(file) = entry.file.as_deref().filter(|f| !f.trim().is_empty()) {
+        check_dat_file(file)?;
+    }
+    if !is_valid_keyword(entry.keyword.trim()) {
+        return Err(format!(
+            "关键字 \"{}\" 无效，只能包含字母、数字和下划线",
+            entry.keyword
+        ));
+    }
+    let path = entry.path.trim();
+    if path.contains(['#', '\n', '\r']) {
+        return Err(format!("参数 \"{}\" 不能包含 # 或换行", entry.path));
+    }
+    check_relative_path(path).map_err(|reason| format!("参数 \"{}\" {}", entry.path, reason))
+}
+
+/// 查找游戏的主配置文件（游戏描述中的 data/gta.dat、data/gta_vc.dat、data/gta3.dat 等）
+/// 优先使用当前游戏类型的文件，无法识别时按 SA、VC、III 的顺序使用找到的第一个
+pub fn find_main_dat_file(game_dir: &Path) -> Option<&'static str> {
+    let current = detect_game_type(game_dir)
+        .as_deref()
+        .and_then(find_game_descriptor);
+    current
+        .into_iter()
+        .chain(game_descriptors().iter().rev())
+        .filter_map(|d| d.data_file.as_deref())
+        .find(|f| game_dir.join(f).is_file())
+}
+
+/// 确定 .dat 文件：指定的文件须在游戏目录中存在，未指定时使用游戏的主配置文件
+fn resolve_dat_file(game_dir: &Path, file: Option<&str>) -> Result<String, String> {
+    match file.map(str::trim).filter(|f| !f.is_empty()) {
+        Some(file) => {
+            check_dat_file(file)?;
+            let normalized = file.replace('\\', "/");
+            if !game_dir.join(&normalized).is_file() {
+                return Err(format!("游戏目录中没有 {}", normalized));
+            }
+            Ok(normalized)
+        }
+        None => find_main_dat_file(game_dir)
+            .map(str::to_string)
+            .ok_or_else(|| "游戏目录中没有游戏的主配置文件（如 data/gta.dat）".to_string()),
+    }
+}
+
+/// 校验条目并补全所在文件，关键字统一为大写
+pub fn resolve_dat_entries(game_dir: &Path, entries: &[DatEntry]) -> Result<Vec<DatEntry>, String> {
+    entries
+        .iter()
+        .map(|entry| {
+            validate_dat_entry(entry)?;
+            Ok(DatEntry {
+                file: Some(resolve_dat_file(game_dir, entry.file.as_deref())?),
+                keyword: entry.keyword.trim().to_uppercase(),
+                path: entry.path.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// 按文件分组修改条目（条目的 file 须已解析），每个文件只读写一次
+fn edit_dat_files(
+    game_dir: &Path,
+    entries: &[DatEntry],
+    mut edit: impl FnMut(&mut DatFile, &DatEntry) -> bool,
+) -> Result<DatEditResult, String> {
+    let mut files: Vec<&str> = Vec::new();
+    for file in entries.iter().filter_map(|e| e.file.as_deref()) {
+        if !files.contains(&file) {
+            files.push(file);
+        }
+    }
+
+    let mut result = DatEditResult {
+        changed: Vec::new(),
+        unchanged: Vec::new(),
+    };
+    for file in files {
+        let path = game_dir.join(file);
+        let mut dat = DatFile::load(&path)?;
+        let mut changed = false;
+        for entry in entries.iter().filter(|e| e.file.as_deref() == Some(file)) {
+            if edit(&mut dat, entry) {
+                changed = true;
+                result.changed.push(entry.clone());
+            } else {
+                result.unchanged.push(entry.clone());
+            }
+        }
+        if changed {
+            dat.save(&path)?;
+        }
+    }
+    Ok(result)
+}
+
+/// 写入 MOD 声明的 .dat 行并标记所属 MOD，已存在的行不重复添加
+/// 返回补全了所在文件的条目（记录到 mods.json，卸载和禁用时据此找到要修改的文件）
+pub fn apply_mod_dat_entries(
+    game_dir: &Path,
+    mod_id: u32,
+    entries: &[DatEntry],
+) -> Result<Vec<DatEntry>, String> {
+    let resolved = resolve_dat_entries(game_dir, entries)?;
+    if let Err(e) = edit_dat_files(game_dir, &resolved, |dat, entry| {
+        dat.add_entry(&entry.keyword, &entry.path, Some(mod_id))
+    }) {
+        let _ = remove_mod_dat_entries(game_dir, mod_id, &resolved);
+        return Err(e);
+    }
+    Ok(resolved)
+}
+
+/// 删除 MOD 写入的所有 .dat 行（只删除带有该 MOD 标记的行）
+pub fn remove_mod_dat_entries(
+    game_dir: &Path,
+    mod_id: u32,
+    entries: &[DatEntry],
+) -> Result<(), String> {
+    let mut files: Vec<&str> = entries.iter().filter_map(|e| e.file.as_deref()).collect();
+    files.sort_unstable();
+    files.dedup();
+
+    for file in files {
+        let path = game_dir.join(file);
+        if !path.is_file() {
+            continue;
+        }
+        let mut dat = DatFile::load(&path)?;
+        if dat.remove_owned(mod_id) > 0 {
+            dat.save(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// 列出 .dat 文件中的条目，未指定文件时读取游戏的主配置文件（Tauri 命令）
+#[tauri::command]
+pub async fn list_dat_entries(
+    game_dir: String,
+    file: Option<String>,
+) -> Result<ApiResponse<DatFileInfo>, String> {
+    let game_path = Path::new(&game_dir);
+    let file = match resolve_dat_file(game_path, file.as_deref()) {
+        Ok(file) => file,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+
+    match DatFile::load(&game_path.join(&file)) {
+        Ok(dat) => Ok(ApiResponse::success(DatFileInfo {
+            entries: dat.entries(),
+            file,
+        })),
+        Err(e) => Ok(ApiResponse::error(e)),
+    }
+}
+
+/// 向 .dat 文件添加条目，已存在的条目不重复添加（Tauri 命令）
+#[tauri::command]
+pub async fn add_dat_entries(
+    game_dir: String,
+    entries: Vec<DatEntry>,
+) -> Result<ApiResponse<DatEditResult>, String> {
+    let game_path = Path::new(&game_dir);
+    let result = resolve_dat_entries(game_path, &entries).and_then(|resolved| {
+        edit_dat_files(game_path, &resolved, |dat, entry| {
+            dat.add_entry(&entry.keyword, &entry.path, None)
+        })
+    });
+    match result {
+        Ok(result) => Ok(ApiResponse::success(result)),
+        Err(e) => Ok(ApiResponse::error(format!("添加 .dat 条目失败: {}", e))),
+    }
+}
+
+/// 从 .dat 文件删除条目，不存在的条目忽略（Tauri 命令）
+#[tauri::command]
+pub async fn remove_dat_entries(
+    game_dir: String,
+    entries: Vec<DatEntry>,
+) -> Result<ApiResponse<DatEditResult>, String> {
+    let game_path = Path::new(&game_dir);
+    let result = resolve_dat_entries(game_path, &entries).and_then(|resolved| {
+        edit_dat_files(game_path, &resolved, |dat, entry| {
+            dat.remove_entry(&entry.keyword, &entry.path)
+        })
+    });
+    match result {
+        Ok(result) => Ok(ApiResponse::success(result)),
+        Err(e) => Ok(ApiResponse::error(format!("删除 .dat 条目失败: {}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GTA_DAT: &[u8] = b"# load IDE files\r\nIDE DATA\\MAPS\\generic.ide\r\n\r\nIPL DATA\\MAPS\\paths.ipl\r\nSPLASH loadsc0\r\n";
+
+    /// 在独立的临时游戏目录中写入 data/gta.dat，返回游戏目录
+    fn game_dir(name: &str, content: &[u8]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("g2m-dat-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("data")).unwrap();
+        fs::write(dir.join("data/gta.dat"), content).unwrap();
+        dir
+    }
+
+    fn entry(keyword: &str, path: &str) -> DatEntry {
+        DatEntry {
+            file: Some("data/gta.dat".to_string()),
+            keyword: keyword.to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn revert_restores_original_bytes() {
+        let dir = game_dir("revert", GTA_DAT);
+        let entries = [
+            entry("ide", "DATA\\MAPS\\mod.ide"),
+            entry("IPL", "DATA\\MAPS\\mod.ipl"),
+        ];
+        let resolved = apply_mod_dat_entries(&dir, 3, &entries).unwrap();
+        assert_eq!(resolved[0].keyword, "IDE");
+
+        let applied = fs::read(dir.join("data/gta.dat")).unwrap();
+        let applied = String::from_utf8_lossy(&applied);
+        assert!(
+            applied.contains(
+                "IDE DATA\\MAPS\\generic.ide\r\n# g2m:mod=3\r\nIDE DATA\\MAPS\\mod.ide\r\n"
+            ),
+            "{}",
+            applied
+        );
+        assert!(applied.contains("# g2m:mod=3\r\nIPL DATA\\MAPS\\mod.ipl\r\nSPLASH"));
+
+        remove_mod_dat_entries(&dir, 3, &resolved).unwrap();
+        assert_eq!(fs::read(dir.join("data/gta.dat")).unwrap(), GTA_DAT);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn revert_keeps_line_the_user_already_had() {
+        let dir = game_dir("user-line", GTA_DAT);
+        // 路径分隔符和大小写不同也视为同一行
+        let entries = [entry("IDE", "data/maps/GENERIC.ide")];
+        let resolved = apply_mod_dat_entries(&dir, 4, &entries).unwrap();
+        assert_eq!(fs::read(dir.join("data/gta.dat")).unwrap(), GTA_DAT);
+
+        remove_mod_dat_entries(&dir, 4, &resolved).unwrap();
+        let dat = DatFile::load(&dir.join("data/gta.dat")).unwrap();
+        assert!(dat.contains("IDE", "DATA\\MAPS\\generic.ide"));
+        assert_eq!(fs::read(dir.join("data/gta.dat")).unwrap(), GTA_DAT);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn shared_line_is_removed_by_last_owner() {
+        let dir = game_dir("shared", GTA_DAT);
+        let entries = [entry("IDE", "DATA\\MAPS\\shared.ide")];
+        let resolved = apply_mod_dat_entries(&dir, 5, &entries).unwrap();
+        apply_mod_dat_entries(&dir, 6, &entries).unwrap();
+
+        remove_mod_dat_entries(&dir, 5, &resolved).unwrap();
+        let dat = DatFile::load(&dir.join("data/gta.dat")).unwrap();
+        let shared = dat
+            .entries()
+            .into_iter()
+            .find(|e| e.path == "DATA\\MAPS\\shared.ide")
+            .unwrap();
+        assert_eq!(shared.owner_mod_ids, vec![6]);
+
+        remove_mod_dat_entries(&dir, 6, &resolved).unwrap();
+        assert_eq!(fs::read(dir.join("data/gta.dat")).unwrap(), GTA_DAT);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+/// CLEO 的 FXT 文本文件没有分表，所有条目视为 MAIN 表
+pub const FXT_TABLE: &str = "MAIN";
+
+/// FXT 文件中的一行
+enum FxtLine {
+    /// 注释、空行等，原样写回
+    Raw(Vec<u8>),
+    /// 键名 + 空格 + 文本
+    Entry {
+        raw: Vec<u8>,
+        key: String,
+        text: String,
+    },
+}
+
+/// CLEO 的 FXT 文本文件：每行为“键名 文本”，# 开头的行为注释
+/// 文本按字节对应到 U+0000–U+00FF，写回时保持原始字节；未修改的行原样写回
+pub struct FxtFile {
+    lines: Vec<FxtLine>,
+    crlf: bool,
+    trailing_newline: bool,
+}
+
+fn decode(bytes: &[u8]) -> String {
+    bytes.iter().map(|&b| b as char).collect()
+}
+
+fn encode(text: &str) -> Result<Vec<u8>, String> {
+    text.chars()
+        .map(|c| {
+            u8::try_from(c as u32)
+                .map_err(|_| format!("文本 \"{}\" 包含无法保存到 FXT 的字符 {}", text, c))
+        })
+        .collect()
+}
+
+fn parse_entry(line: &[u8]) -> Option<(String, String)> {
+    let line = line.trim_ascii_start();
+    if line.is_empty() || line.starts_with(b"#") || line.starts_with(b"//") {
+        return None;
+    }
+    let split = line.iter().position(|b| b.is_ascii_whitespace())?;
+    let key = std::str::from_utf8(&line[..split]).ok()?;
+    let text = line[split..].trim_ascii();
+    Some((key.to_string(), decode(text)))
+}
+
+/// 检查键名：只能包含字母、数字和下划线，最长 7 个字符
+fn check_key(key: &str) -> Result<(), String> {
+    if key.is_empty()
+        || key.len() > 7
+        || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(format!(
+            "FXT 键名 \"{}\" 无效，只能使用最长 7 个字符的字母、数字和下划线",
+            key
+        ));
+    }
+    Ok(())
+}
+
+impl FxtFile {
+    pub fn parse(content: &[u8]) -> Self {
+        let crlf = content.windows(2).any(|w| w == b"\r\n");
+        let trailing_newline = content.is_empty() || content.ends_with(b"\n");
+        let body = content.strip_suffix(b"\n").unwrap_or(content);
+        let mut lines = Vec::new();
+        if !content.is_empty() {
+            for line in body.split(|&b| b == b'\n') {
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                lines.push(match parse_entry(line) {
+                    Some((key, text)) => FxtLine::Entry {
+                        raw: line.to_vec(),
+                        key,
+                        text,
+                    },
+                    None => FxtLine::Raw(line.to_vec()),
+                });
+            }
+        }
+        Self {
+            lines,
+            crlf,
+            trailing_newline,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let newline: &[u8] = if self.crlf { b"\r\n" } else { b"\n" };
+        let mut bytes = Vec::new();
+        for (index, line) in self.lines.iter().enumerate() {
+            if index > 0 {
+                bytes.extend_from_slice(newline);
+            }
+            match line {
+                FxtLine::Raw(raw) | FxtLine::Entry { raw, .. } => bytes.extend_from_slice(raw),
+            }
+        }
+        if self.trailing_newline && !self.lines.is_empty() {
+            bytes.extend_from_slice(newline);
+        }
+        bytes
+    }
+
+    /// 所有条目（键名重复时以最后一行为准）
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut entries: Vec<(String, String)> = Vec::new();
+        for line in &self.lines {
+            if let FxtLine::Entry { key, text, .. } = line {
+                entries.retain(|(k, _)| !k.eq_ignore_ascii_case(key));
+                entries.push((key.clone(), text.clone()));
+            }
+        }
+        entries
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().rev().find_map(|line| match line {
+            FxtLine::Entry { key: k, text, .. } if k.eq_ignore_ascii_case(key) => {
+                Some(text.as_str())
+            }
+            _ => None,
+        })
+    }
+
+    /// 设置条目：已有的行原位修改，否则追加到文件末尾
+    pub fn set(&mut self, key: &str, text: &str) -> Result<(), String> {
+        check_key(key)?;
+        let mut raw = format!("{} ", key).into_bytes();
+        raw.extend(encode(text)?);
+        let line = FxtLine::Entry {
+            raw,
+            key: key.to_string(),
+            text: text.to_string(),
+        };
+        let existing = self.lines.iter().rposition(
+            |l| matches!(l, FxtLine::Entry { key: k, .. } if k.eq_ignore_ascii_case(key)),
+        );
+        match existing {
+            Some(index) => self.lines[index] = line,
+            None => self.lines.push(line),
+        }
+        Ok(())
+    }
+
+    /// 删除键名相同的所有行，返回是否删除
+    pub fn remove(&mut self, key: &str) -> bool {
+        let before = self.lines.len();
+        self.lines.retain(
+            |line| !matches!(line, FxtLine::Entry { key: k, .. } if k.eq_ignore_ascii_case(key)),
+        );
+        self.lines.len() != before
+    }
+}
@@ -0,0 +1,534 @@
+use std::collections::BTreeMap;
+
+/// 主表的名称；其他表在 TKEY 之前还有 8 字节的表名
+pub const MAIN_TABLE: &str = "MAIN";
+/// 表名和键名占 8 字节（含结尾的 0）
+const NAME_LENGTH: usize = 8;
+
+/// GXT 文件的格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GxtFormat {
+    /// GTA III：只有一个表（TKEY + TDAT），UTF-16 文本
+    Gta3,
+    /// GTA VC：TABL 分表，键名为字符串，UTF-16 文本
+    Vc,
+    /// GTA SA：TABL 分表，键名为 CRC32，文本为 8 位或 16 位
+    Sa { wide: bool },
+}
+
+impl GxtFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gta3 => "gta3",
+            Self::Vc => "gtavc",
+            Self::Sa { wide: false } => "gtasa",
+            Self::Sa { wide: true } => "gtasa16",
+        }
+    }
+}
+
+/// GXT 中的一个表，键按游戏二分查找的顺序排列
+#[derive(Debug, Clone)]
+pub struct GxtTable {
+    pub name: String,
+    pub entries: BTreeMap<String, String>,
+}
+
+/// GXT 文本表文件
+/// SA 的键只保存 CRC32，以 8 位大写十六进制表示（如 "0A1B2C3D"）
+/// 8 位文本按字节对应到 U+0000–U+00FF，写回时保持原始字节
+#[derive(Debug, Clone)]
+pub struct GxtFile {
+    pub format: GxtFormat,
+    pub tables: Vec<GxtTable>,
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "GXT 文件数据不完整".to_string())
+}
+
+fn read_name(data: &[u8], offset: usize) -> Result<String, String> {
+    let bytes = data
+        .get(offset..offset + NAME_LENGTH)
+        .ok_or_else(|| "GXT 文件数据不完整".to_string())?;
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    Ok(String::from_utf8_lossy(&bytes[..end]).to_string())
+}
+
+fn expect_tag(data: &[u8], offset: usize, tag: &[u8; 4]) -> Result<usize, String> {
+    if data.get(offset..offset + 4) != Some(tag.as_slice()) {
+        return Err(format!(
+            "GXT 文件在偏移 {} 处缺少 {}",
+            offset,
+            String::from_utf8_lossy(tag)
+        ));
+    }
+    Ok(read_u32(data, offset + 4)? as usize)
+}
+
+/// SA 键名的哈希：键名转大写后的 CRC32（不取反）
+pub fn sa_key_hash(key: &str) -> u32 {
+    let mut crc = u32::MAX;
+    for byte in key.bytes() {
+        crc ^= byte.to_ascii_uppercase() as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+fn is_hash_key(key: &str) -> bool {
+    key.len() == 8 && key.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn decode_text(data: &[u8], offset: usize, wide: bool) -> Result<String, String> {
+    let rest = data
+        .get(offset..)
+        .ok_or_else(|| "GXT 文本偏移超出 TDAT 范围".to_string())?;
+    if wide {
+        let units: Vec<u16> = rest
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .take_while(|&u| u != 0)
+            .collect();
+        Ok(String::from_utf16_lossy(&units))
+    } else {
+        Ok(rest
+            .iter()
+            .take_while(|&&b| b != 0)
+            .map(|&b| b as char)
+            .collect())
+    }
+}
+
+fn encode_text(text: &str, wide: bool, out: &mut Vec<u8>) -> Result<(), String> {
+    if wide {
+        for unit in text.encode_utf16() {
+            out.extend_from_slice(&unit.to_le_bytes());
+        }
+        out.extend_from_slice(&[0, 0]);
+    } else {
+        for c in text.chars() {
+            let byte = u8::try_from(c as u32)
+                .map_err(|_| format!("文本 \"{}\" 包含无法用 8 位 GXT 保存的字符 {}", text, c))?;
+            out.push(byte);
+        }
+        out.push(0);
+    }
+    Ok(())
+}
+
+fn write_name(name: &str, out: &mut Vec<u8>) {
+    let mut bytes = [0u8; NAME_LENGTH];
+    bytes[..name.len()].copy_from_slice(name.as_bytes());
+    out.extend_from_slice(&bytes);
+}
+
+/// 检查表名或键名：ASCII，最长 7 个字符
+fn check_name(name: &str, label: &str) -> Result<(), String> {
+    if name.is_empty() || !name.is_ascii() || name.len() >= NAME_LENGTH || name.contains('\0') {
+        return Err(format!(
+            "{} \"{}\" 无效，只能使用最长 {} 个字符的英文名称",
+            label,
+            name,
+            NAME_LENGTH - 1
+        ));
+    }
+    Ok(())
+}
+
+impl GxtFile {
+    pub fn parse(data: &[u8]) -> Result<Self, String> {
+        if data.starts_with(b"TKEY") {
+            let entries = Self::parse_table(data, 0, GxtFormat::Gta3)?;
+            return Ok(Self {
+                format: GxtFormat::Gta3,
+                tables: vec![GxtTable {
+                    name: MAIN_TABLE.to_string(),
+                    entries,
+                }],
+            });
+        }
+
+        let (format, tabl) = if data.starts_with(b"TABL") {
+            (GxtFormat::Vc, 0)
+        } else if data.get(4..8) == Some(b"TABL".as_slice()) {
+            let version = u16::from_le_bytes([data[0], data[1]]);
+            let bits = u16::from_le_bytes([data[2], data[3]]);
+            if version != 4 || (bits != 8 && bits != 16) {
+                return Err(format!("不支持的 GXT 版本 {}（{} 位）", version, bits));
+            }
+            (GxtFormat::Sa { wide: bits == 16 }, 4)
+        } else {
+            return Err("不是 GXT 文件".to_string());
+        };
+
+        let size = expect_tag(data, tabl, b"TABL")?;
+        let mut tables = Vec::new();
+        for index in 0..size / 12 {
+            let entry = tabl + 8 + index * 12;
+            let name = read_name(data, entry)?;
+            let mut offset = read_u32(data, entry + NAME_LENGTH)? as usize;
+            if name != MAIN_TABLE {
+                offset += NAME_LENGTH;
+            }
+            tables.push(GxtTable {
+                entries: Self::parse_table(data, offset, format)?,
+                name,
+            });
+        }
+        Ok(Self { format, tables })
+    }
+
+    /// 解析 TKEY + TDAT
+    fn parse_table(
+        data: &[u8],
+        offset: usize,
+        format: GxtFormat,
+    ) -> Result<BTreeMap<String, String>, String> {
+        let key_size = expect_tag(data, offset, b"TKEY")?;
+        let tdat = offset + 8 + key_size;
+        let text_size = expect_tag(data, tdat, b"TDAT")?;
+        let text = data
+            .get(tdat + 8..tdat + 8 + text_size)
+            .ok_or_else(|| "GXT 文件的 TDAT 数据不完整".to_string())?;
+
+        let (entry_size, wide) = match format {
+            GxtFormat::Sa { wide } => (8, wide),
+            _ => (12, true),
+        };
+        let mut entries = BTreeMap::new();
+        for index in 0..key_size / entry_size {
+            let entry = offset + 8 + index * entry_size;
+            let text_offset = read_u32(data, entry)? as usize;
+            let key = match format {
+                GxtFormat::Sa { .. } => format!("{:08X}", read_u32(data, entry + 4)?),
+                _ => read_name(data, entry + 4)?,
+            };
+            entries.insert(key, decode_text(text, text_offset, wide)?);
+        }
+        Ok(entries)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let mut tables: Vec<&GxtTable> = self.tables.iter().collect();
+        // 游戏按表名查找 MAIN 以外的表，MAIN 总是放在第一个
+        tables.sort_by_key(|t| t.name != MAIN_TABLE);
+        for table in &tables {
+            check_name(&table.name, "表名")?;
+        }
+
+        let mut out = Vec::new();
+        let tabl = match self.format {
+            GxtFormat::Gta3 => {
+                if tables.len() != 1 || tables[0].name != MAIN_TABLE {
+                    return Err("GTA III 的 GXT 只能有 MAIN 表".to_string());
+                }
+                self.write_table(tables[0], &mut out)?;
+                return Ok(out);
+            }
+            GxtFormat::Vc => 0,
+            GxtFormat::Sa { wide } => {
+                out.extend_from_slice(&4u16.to_le_bytes());
+                out.extend_from_slice(&(if wide { 16u16 } else { 8 }).to_le_bytes());
+                4
+            }
+        };
+
+        out.extend_from_slice(b"TABL");
+        out.extend_from_slice(&((tables.len() * 12) as u32).to_le_bytes());
+        out.resize(tabl + 8 + tables.len() * 12, 0);
+        for (index, table) in tables.iter().enumerate() {
+            let entry = tabl + 8 + index * 12;
+            out[entry..entry + table.name.len()].copy_from_slice(table.name.as_bytes());
+            let offset = out.len() as u32;
+            out[entry + NAME_LENGTH..entry + 12].copy_from_slice(&offset.to_le_bytes());
+            if table.name != MAIN_TABLE {
+                write_name(&table.name, &mut out);
+            }
+            self.write_table(table, &mut out)?;
+        }
+        Ok(out)
+    }
+
+    fn write_table(&self, table: &GxtTable, out: &mut Vec<u8>) -> Result<(), String> {
+        let (entry_size, wide) = match self.format {
+            GxtFormat::Sa { wide } => (8, wide),
+            _ => (12, true),
+        };
+
+        let mut keys = Vec::new();
+        let mut text = Vec::new();
+        for (key, value) in &table.entries {
+            let offset = (text.len() as u32).to_le_bytes();
+            keys.extend_from_slice(&offset);
+            match self.format {
+                GxtFormat::Sa { .. } => {
+                    let hash = u32::from_str_radix(key, 16)
+                        .map_err(|_| format!("SA 的键 \"{}\" 不是 CRC32", key))?;
+                    keys.extend_from_slice(&hash.to_le_bytes());
+                }
+                _ => {
+                    check_name(key, "键名")?;
+                    write_name(key, &mut keys);
+                }
+            }
+            encode_text(value, wide, &mut text)?;
+        }
+
+        out.extend_from_slice(b"TKEY");
+        out.extend_from_slice(&((table.entries.len() * entry_size) as u32).to_le_bytes());
+        out.extend_from_slice(&keys);
+        out.extend_from_slice(b"TDAT");
+        out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        out.extend_from_slice(&text);
+        // 下一个表从 4 字节边界开始
+        out.resize(out.len().next_multiple_of(4), 0);
+        Ok(())
+    }
+
+    /// 统一键名：SA 的键名转换为 CRC32（已是 8 位十六进制时保持不变），其他版本保持原样
+    pub fn normalize_key(&self, key: &str) -> String {
+        let key = key.trim();
+        match self.format {
+            GxtFormat::Sa { .. } if is_hash_key(key) => key.to_uppercase(),
+            GxtFormat::Sa { .. } => format!("{:08X}", sa_key_hash(key)),
+            _ => key.to_string(),
+        }
+    }
+
+    pub fn get(&self, table: &str, key: &str) -> Option<&str> {
+        self.tables
+            .iter()
+            .find(|t| t.name == table)?
+            .entries
+            .get(&self.normalize_key(key))
+            .map(String::as_str)
+    }
+
+    /// 设置条目，表不存在时新建（III 只能使用 MAIN 表）
+    pub fn set(&mut self, table: &str, key: &str, text: &str) -> Result<(), String> {
+        if self.format == GxtFormat::Gta3 && table != MAIN_TABLE {
+            return Err("GTA III 的 GXT 只能有 MAIN 表".to_string());
+        }
+        let key = self.normalize_key(key);
+        if !matches!(self.format, GxtFormat::Sa { .. }) {
+            check_name(&key, "键名")?;
+        }
+        let index = match self.tables.iter().position(|t| t.name == table) {
+            Some(index) => index,
+            None => {
+                check_name(table, "表名")?;
+                self.tables.push(GxtTable {
+                    name: table.to_string(),
+                    entries: BTreeMap::new(),
+                });
+                self.tables.len() - 1
+            }
+        };
+        self.tables[index].entries.insert(key, text.to_string());
+        Ok(())
+    }
+
+    /// 删除条目，返回是否删除；表变为空时一并删除（MAIN 表保留）
+    pub fn remove(&mut self, table: &str, key: &str) -> bool {
+        let key = self.normalize_key(key);
+        let Some(index) = self.tables.iter().position(|t| t.name == table) else {
+            return false;
+        };
+        let removed = self.tables[index].entries.remove(&key).is_some();
+        if self.tables[index].entries.is_empty() && table != MAIN_TABLE {
+            self.tables.remove(index);
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name_field(name: &str) -> Vec<u8> {
+        let mut field = name.as_bytes().to_vec();
+        field.resize(NAME_LENGTH, 0);
+        field
+    }
+
+    fn wide(text: &str) -> Vec<u8> {
+        let mut out: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        out.extend_from_slice(&[0, 0]);
+        out
+    }
+
+    fn narrow(text: &[u8]) -> Vec<u8> {
+        let mut out = text.to_vec();
+        out.push(0);
+        out
+    }
+
+    /// 按游戏的布局手工拼出 TKEY + TDAT：键字段 + 已编码的文本，结尾补齐到 4 字节
+    fn table(entries: &[(Vec<u8>, Vec<u8>)]) -> Vec<u8> {
+        let mut keys = Vec::new();
+        let mut text = Vec::new();
+        for (key, value) in entries {
+            keys.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            keys.extend_from_slice(key);
+            text.extend_from_slice(value);
+        }
+        let mut out = b"TKEY".to_vec();
+        out.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+        out.extend_from_slice(&keys);
+        out.extend_from_slice(b"TDAT");
+        out.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        out.extend_from_slice(&text);
+        out.resize(out.len().next_multiple_of(4), 0);
+        out
+    }
+
+    /// 拼出带 TABL 的文件（VC、SA），header 为 SA 的版本头
+    fn tabled(header: &[u8], tables: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut out = header.to_vec();
+        out.extend_from_slice(b"TABL");
+        out.extend_from_slice(&((tables.len() * 12) as u32).to_le_bytes());
+        let mut body = Vec::new();
+        let body_start = out.len() + tables.len() * 12;
+        for (name, data) in tables {
+            out.extend_from_slice(&name_field(name));
+            out.extend_from_slice(&((body_start + body.len()) as u32).to_le_bytes());
+            if *name != MAIN_TABLE {
+                body.extend_from_slice(&name_field(name));
+            }
+            body.extend_from_slice(data);
+        }
+        out.extend_from_slice(&body);
+        out
+    }
+
+    fn sa_key(key: &str) -> Vec<u8> {
+        sa_key_hash(key).to_le_bytes().to_vec()
+    }
+
+    /// SA 的条目按 CRC32 从小到大排列
+    fn sa_table(entries: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut entries: Vec<(Vec<u8>, Vec<u8>)> = entries
+            .iter()
+            .map(|(key, text)| (sa_key(key), text.clone()))
+            .collect();
+        entries.sort_by_key(|(key, _)| u32::from_le_bytes([key[0], key[1], key[2], key[3]]));
+        table(&entries)
+    }
+
+    fn assert_round_trip(data: &[u8], format: GxtFormat) -> GxtFile {
+        let file = GxtFile::parse(data).unwrap();
+        assert_eq!(file.format, format);
+        assert_eq!(file.to_bytes().unwrap(), data);
+        file
+    }
+
+    #[test]
+    fn gta3_round_trip() {
+        let data = table(&[
+            (name_field("ABC"), wide("Hello")),
+            (name_field("HELP1"), wide("Élan ~r~vital")),
+            (name_field("Z"), wide("日本")),
+        ]);
+        let file = assert_round_trip(&data, GxtFormat::Gta3);
+        assert_eq!(file.get(MAIN_TABLE, "HELP1"), Some("Élan ~r~vital"));
+        assert_eq!(file.get(MAIN_TABLE, "Z"), Some("日本"));
+    }
+
+    #[test]
+    fn vc_round_trip() {
+        let data = tabled(
+            &[],
+            &[
+                (
+                    MAIN_TABLE,
+                    table(&[
+                        (name_field("FEM_OK"), wide("OK")),
+                        (name_field("IN_VEH"), wide("Vehicle")),
+                    ]),
+                ),
+                ("INTRO", table(&[(name_field("INT_A"), wide("Vice"))])),
+                ("PHIL", table(&[(name_field("PHI_1"), wide("Phil ¡"))])),
+            ],
+        );
+        let file = assert_round_trip(&data, GxtFormat::Vc);
+        assert_eq!(file.get("INTRO", "INT_A"), Some("Vice"));
+        assert_eq!(file.get("PHIL", "PHI_1"), Some("Phil ¡"));
+    }
+
+    #[test]
+    fn sa_round_trip() {
+        for wide_text in [false, true] {
+            let encode = |text: &str| {
+                if wide_text {
+                    wide(text)
+                } else {
+                    narrow(&text.chars().map(|c| c as u8).collect::<Vec<u8>>())
+                }
+            };
+            let bits: u16 = if wide_text { 16 } else { 8 };
+            let mut header = 4u16.to_le_bytes().to_vec();
+            header.extend_from_slice(&bits.to_le_bytes());
+            let data = tabled(
+                &header,
+                &[
+                    (
+                        MAIN_TABLE,
+                        sa_table(&[
+                            ("FEM_OK", encode("OK")),
+                            ("IE23", encode("Café")),
+                            ("BJ_01", encode("Blackjack")),
+                        ]),
+                    ),
+                    ("AMBULAE", sa_table(&[("AMBU_1", encode("Paramedic"))])),
+                ],
+            );
+            let file = assert_round_trip(&data, GxtFormat::Sa { wide: wide_text });
+            assert_eq!(file.get(MAIN_TABLE, "ie23"), Some("Café"));
+            assert_eq!(file.get("AMBULAE", "AMBU_1"), Some("Paramedic"));
+        }
+    }
+
+    #[test]
+    fn sa_narrow_text_keeps_raw_bytes() {
+        let mut header = 4u16.to_le_bytes().to_vec();
+        header.extend_from_slice(&8u16.to_le_bytes());
+        let data = tabled(
+            &header,
+            &[(
+                MAIN_TABLE,
+                sa_table(&[("RAW", narrow(&[0x41, 0x8D, 0xFF, 0x7E]))]),
+            )],
+        );
+        let file = assert_round_trip(&data, GxtFormat::Sa { wide: false });
+        assert_eq!(file.get(MAIN_TABLE, "RAW"), Some("A\u{8D}\u{FF}~"));
+    }
+
+    #[test]
+    fn edits_survive_serialization() {
+        let data = tabled(
+            &[],
+            &[(MAIN_TABLE, table(&[(name_field("FEM_OK"), wide("OK"))]))],
+        );
+        let mut file = GxtFile::parse(&data).unwrap();
+        file.set("NEWTAB", "NEW_1", "Added").unwrap();
+        file.set(MAIN_TABLE, "FEM_OK", "Okay").unwrap();
+        let bytes = file.to_bytes().unwrap();
+        let mut reread = assert_round_trip(&bytes, GxtFormat::Vc);
+        assert_eq!(reread.get("NEWTAB", "NEW_1"), Some("Added"));
+        assert_eq!(reread.get(MAIN_TABLE, "FEM_OK"), Some("Okay"));
+
+        assert!(reread.remove("NEWTAB", "NEW_1"));
+        reread.set(MAIN_TABLE, "FEM_OK", "OK").unwrap();
+        assert_eq!(reread.to_bytes().unwrap(), data);
+    }
+}
@@ -0,0 +1,54 @@
+/// 定义新模型的分段；path、2dfx、txdp 等分段引用已有的模型，不产生新的 ID
+const DEFINITION_SECTIONS: [&str; 7] = ["objs", "tobj", "anim", "weap", "hier", "cars", "peds"];
+
+/// IDE 文件中的一条模型定义
+#[derive(Debug, Clone)]
+pub struct IdeDefinition {
+    pub section: String, // 所在分段（小写）
+    pub id: u32,         // 模型 ID
+    pub model: String,   // 模型名称
+    pub line: usize,     // 行号（从 1 开始）
+}
+
+/// 解析文本 IDE 文件中的模型定义
+/// 分段以 objs、cars 等关键字开始、以 end 结束，每行以逗号分隔，前两项为 ID 和模型名称
+pub fn parse_ide(content: &str) -> Vec<IdeDefinition> {
+    let mut definitions = Vec::new();
+    let mut section: Option<String> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some(current) = &section else {
+            if !line.contains(',') {
+                section = Some(line.to_lowercase());
+            }
+            continue;
+        };
+        if line.eq_ignore_ascii_case("end") {
+            section = None;
+            continue;
+        }
+        if !DEFINITION_SECTIONS.contains(&current.as_str()) {
+            continue;
+        }
+
+        let mut fields = line.split(',').map(str::trim);
+        let (Some(id), Some(model)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if let Ok(id) = id.parse() {
+            definitions.push(IdeDefinition {
+                section: current.clone(),
+                id,
+                model: model.to_string(),
+                line: index + 1,
+            });
+        }
+    }
+
+    definitions
+}
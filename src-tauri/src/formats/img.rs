@@ -0,0 +1,814 @@
+use crate::game::types::{ApiResponse, ImgArchiveInfo, ImgEntryInfo, ImgImportResult};
+use crate::game::utils::get_g2m_dir_path;
+use crate::mod_core::utils::check_relative_path;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// IMG 档案的扇区大小，条目按扇区对齐存放
+pub const SECTOR_SIZE: u64 = 2048;
+/// 目录中每个条目占 32 字节，其中名称 24 字节（含结尾的 0）
+const DIR_ENTRY_SIZE: usize = 32;
+const NAME_LENGTH: usize = 24;
+
+/// IMG 档案版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImgVersion {
+    /// GTA III / VC：目录在单独的 .dir 文件中
+    V1,
+    /// GTA SA：目录在 .img 开头，以 VER2 标记
+    V2,
+}
+
+/// 目录中的条目（偏移和大小以扇区为单位）
+#[derive(Debug, Clone)]
+pub struct ImgEntry {
+    pub name: String,
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// 已打开的 IMG 档案；修改条目时先把数据写入未使用的区域再写目录，中途失败时原目录和它指向的数据仍然有效
+pub struct ImgArchive {
+    img_path: PathBuf,
+    dir_path: Option<PathBuf>,
+    version: ImgVersion,
+    entries: Vec<ImgEntry>,
+}
+
+fn sectors_for(len: u64) -> u64 {
+    len.div_ceil(SECTOR_SIZE)
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn parse_name(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+/// 检查条目名称：只能是 ASCII 文件名，最长 23 个字符
+pub fn validate_entry_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("条目名称不能为空".to_string());
+    }
+    if !name.is_ascii() || name.contains(['/', '\\', '\0']) {
+        return Err(format!("条目名称 \"{}\" 只能使用英文文件名", name));
+    }
+    if name.len() >= NAME_LENGTH {
+        return Err(format!(
+            "条目名称 \"{}\" 超过 {} 个字符",
+            name,
+            NAME_LENGTH - 1
+        ));
+    }
+    Ok(())
+}
+
+/// 查找同目录下主文件名相同、扩展名为 extension 的文件（不区分大小写）
+fn find_sibling(path: &Path, extension: &str) -> Option<PathBuf> {
+    let stem = path.file_stem()?.to_string_lossy().to_lowercase();
+    let parent = path.parent()?;
+    fs::read_dir(parent)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .find(|path| {
+            path.is_file()
+                && path
+                    .file_stem()
+                    .is_some_and(|s| s.to_string_lossy().to_lowercase() == stem)
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+        })
+}
+
+impl ImgArchive {
+    /// 打开 IMG 档案（可传入 .img 或 .dir 路径）
+    /// 以 VER2 开头的为 SA 格式，否则读取同名 .dir 文件（III/VC 格式）
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let img_path = if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("dir"))
+        {
+            find_sibling(path, "img").unwrap_or_else(|| path.with_extension("img"))
+        } else {
+            path.to_path_buf()
+        };
+        let mut file = File::open(&img_path).map_err(|e| format!("打开 IMG 档案失败: {}", e))?;
+        let file_len = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        let mut header = [0u8; 8];
+        if file.read_exact(&mut header).is_ok() && &header[..4] == b"VER2" {
+            let count = read_u32(&header[4..]) as u64;
+            if 8 + count * DIR_ENTRY_SIZE as u64 > file_len {
+                return Err("IMG 档案目录不完整".to_string());
+            }
+            let mut directory = vec![0u8; count as usize * DIR_ENTRY_SIZE];
+            file.read_exact(&mut directory)
+                .map_err(|e| format!("读取 IMG 档案目录失败: {}", e))?;
+            let entries = directory
+                .chunks_exact(DIR_ENTRY_SIZE)
+                .map(|chunk| {
+                    // 流式大小为 0 时使用档案内大小
+                    let streaming = u16::from_le_bytes([chunk[4], chunk[5]]);
+                    let archived = u16::from_le_bytes([chunk[6], chunk[7]]);
+                    ImgEntry {
+                        name: parse_name(&chunk[8..]),
+                        offset: read_u32(chunk),
+                        size: if streaming > 0 { streaming } else { archived } as u32,
+                    }
+                })
+                .collect();
+            return Ok(Self {
+                img_path,
+                dir_path: None,
+                version: ImgVersion::V2,
+                entries,
+            });
+        }
+
+        let dir_path = find_sibling(&img_path, "dir")
+            .ok_or_else(|| "不是 VER2 格式的 IMG 档案，也没有找到同名的 .dir 文件".to_string())?;
+        let directory = fs::read(&dir_path).map_err(|e| format!("读取 .dir 文件失败: {}", e))?;
+        if directory.len() % DIR_ENTRY_SIZE != 0 {
+            return Err(".dir 文件大小不正确".to_string());
+        }
+        let entries = directory
+            .chunks_exact(DIR_ENTRY_SIZE)
+            .map(|chunk| ImgEntry {
+                name: parse_name(&chunk[8..]),
+                offset: read_u32(chunk),
+                size: read_u32(&chunk[4..]),
+            })
+            .collect();
+        Ok(Self {
+            img_path,
+            dir_path: Some(dir_path),
+            version: ImgVersion::V1,
+            entries,
+        })
+    }
+
+    pub fn entries(&self) -> &[ImgEntry] {
+        &self.entries
+    }
+
+    /// 按名称查找条目（不区分大小写）
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| e.name.eq_ignore_ascii_case(name))
+    }
+
+    /// 条目数据的起始扇区：V2 的目录位于档案开头，数据需排在目录之后
+    fn data_start(&self, entry_count: usize) -> u64 {
+        match self.version {
+            ImgVersion::V1 => 0,
+            ImgVersion::V2 => sectors_for(8 + (entry_count * DIR_ENTRY_SIZE) as u64),
+        }
+    }
+
+    /// 已使用区域的结束扇区（新数据追加到这里）
+    fn end_sector(&self) -> u64 {
+        self.entries
+            .iter()
+            .map(|e| e.offset as u64 + e.size as u64)
+            .max()
+            .unwrap_or(0)
+            .max(self.data_start(self.entries.len()))
+    }
+
+    /// 档案信息（含所有条目）
+    pub fn info(&self) -> ImgArchiveInfo {
+        let total_size = fs::metadata(&self.img_path).map(|m| m.len()).unwrap_or(0);
+        let used: u64 = self.data_start(self.entries.len())
+            + self.entries.iter().map(|e| e.size as u64).sum::<u64>();
+        ImgArchiveInfo {
+            version: match self.version {
+                ImgVersion::V1 => "v1",
+                ImgVersion::V2 => "v2",
+            }
+            .to_string(),
+            entry_count: self.entries.len(),
+            total_size,
+            free_size: total_size.saturating_sub(used * SECTOR_SIZE),
+            entries: self
+                .entries
+                .iter()
+                .map(|e| ImgEntryInfo {
+                    name: e.name.clone(),
+                    offset: e.offset as u64 * SECTOR_SIZE,
+                    size: e.size as u64 * SECTOR_SIZE,
+                })
+                .collect(),
+        }
+    }
+
+    /// 读取条目数据（按扇区大小补齐）
+    pub fn read_entry(&self, index: usize) -> Result<Vec<u8>, String> {
+        let entry = &self.entries[index];
+        let mut file =
+            File::open(&self.img_path).map_err(|e| format!("打开 IMG 档案失败: {}", e))?;
+        read_sectors(&mut file, entry.offset as u64, entry.size as u64)
+            .map_err(|e| format!("读取条目 {} 失败: {}", entry.name, e))
+    }
+
+    /// 添加或替换条目，返回是否替换了已有条目
+    /// 新数据总是追加到档案末尾，写入目录前原条目的数据保持不变，旧数据占用的空间在重建档案时回收
+    pub fn put_entry(&mut self, name: &str, data: &[u8]) -> Result<bool, String> {
+        validate_entry_name(name)?;
+        let sectors = sectors_for(data.len() as u64);
+        let limit = match self.version {
+            ImgVersion::V1 => u32::MAX as u64,
+            ImgVersion::V2 => u16::MAX as u64,
+        };
+        if sectors > limit {
+            return Err(format!("文件 {} 太大，超出 IMG 档案单个条目的上限", name));
+        }
+
+        let mut file = self.open_for_write()?;
+        let replaced = match self.find(name) {
+            Some(index) => {
+                let offset = self.end_sector();
+                check_archive_size(offset + sectors)?;
+                write_sectors(&mut file, offset, data)
+                    .map_err(|e| format!("写入条目 {} 失败: {}", name, e))?;
+                let entry = &mut self.entries[index];
+                entry.offset = offset as u32;
+                entry.size = sectors as u32;
+                true
+            }
+            None => {
+                // V2 的目录变大后可能覆盖最前面的条目，先把这些条目移到末尾
+                let start = self.data_start(self.entries.len() + 1);
+                self.relocate_before(&mut file, start)?;
+                let offset = self.end_sector().max(start);
+                check_archive_size(offset + sectors)?;
+                write_sectors(&mut file, offset, data)
+                    .map_err(|e| format!("写入条目 {} 失败: {}", name, e))?;
+                self.entries.push(ImgEntry {
+                    name: name.to_string(),
+                    offset: offset as u32,
+                    size: sectors as u32,
+                });
+                false
+            }
+        };
+        self.save_directory(&mut file)?;
+        Ok(replaced)
+    }
+
+    /// 删除条目（数据占用的空间在重建档案时回收）
+    pub fn remove_entry(&mut self, name: &str) -> Result<ImgEntry, String> {
+        let index = self
+            .find(name)
+            .ok_or_else(|| format!("IMG 档案中没有条目 {}", name))?;
+        let removed = self.entries.remove(index);
+        let mut file = self.open_for_write()?;
+        self.save_directory(&mut file)?;
+        Ok(removed)
+    }
+
+    /// 重建档案：按目录顺序紧密排列所有条目，回收删除和替换留下的空间
+    pub fn rebuild(&mut self) -> Result<(), String> {
+        let temp = self.img_path.with_extension("img.g2m-rebuild");
+        let result = self.write_rebuilt(&temp);
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        let entries = result?;
+
+        if let Some(dir_path) = &self.dir_path {
+            let dir_temp = dir_path.with_extension("dir.g2m-rebuild");
+            fs::write(&dir_temp, encode_v1_directory(&entries))
+                .map_err(|e| format!("写入 .dir 文件失败: {}", e))?;
+            fs::rename(&temp, &self.img_path).map_err(|e| format!("替换 IMG 档案失败: {}", e))?;
+            fs::rename(&dir_temp, dir_path).map_err(|e| format!("替换 .dir 文件失败: {}", e))?;
+        } else {
+            fs::rename(&temp, &self.img_path).map_err(|e| format!("替换 IMG 档案失败: {}", e))?;
+        }
+        self.entries = entries;
+        Ok(())
+    }
+
+    /// 写出重建后的档案，返回新的目录
+    fn write_rebuilt(&self, temp: &Path) -> Result<Vec<ImgEntry>, String> {
+        let mut source =
+            File::open(&self.img_path).map_err(|e| format!("打开 IMG 档案失败: {}", e))?;
+        let mut target = File::create(temp).map_err(|e| format!("创建临时文件失败: {}", e))?;
+
+        let mut entries = Vec::with_capacity(self.entries.len());
+        let mut offset = self.data_start(self.entries.len());
+        for entry in &self.entries {
+            let data = read_sectors(&mut source, entry.offset as u64, entry.size as u64)
+                .map_err(|e| format!("读取条目 {} 失败: {}", entry.name, e))?;
+            write_sectors(&mut target, offset, &data)
+                .map_err(|e| format!("写入条目 {} 失败: {}", entry.name, e))?;
+            entries.push(ImgEntry {
+                name: entry.name.clone(),
+                offset: offset as u32,
+                size: entry.size,
+            });
+            offset += entry.size as u64;
+        }
+        if self.version == ImgVersion::V2 {
+            write_v2_directory(&mut target, &entries)
+                .map_err(|e| format!("写入 IMG 档案目录失败: {}", e))?;
+        }
+        target
+            .sync_all()
+            .map_err(|e| format!("写入临时文件失败: {}", e))?;
+        Ok(entries)
+    }
+
+    fn open_for_write(&self) -> Result<File, String> {
+        File::options()
+            .read(true)
+            .write(true)
+            .open(&self.img_path)
+            .map_err(|e| format!("打开 IMG 档案失败: {}", e))
+    }
+
+    /// 把起始扇区在 start 之前的条目移到档案末尾（不写目录）
+    fn relocate_before(&mut self, file: &mut File, start: u64) -> Result<(), String> {
+        let mut moving: Vec<usize> = (0..self.entries.len())
+            .filter(|&i| (self.entries[i].offset as u64) < start)
+            .collect();
+        moving.sort_by_key(|&i| self.entries[i].offset);
+        for index in moving {
+            let entry = &self.entries[index];
+            let data = read_sectors(file, entry.offset as u64, entry.size as u64)
+                .map_err(|e| format!("读取条目 {} 失败: {}", entry.name, e))?;
+            let offset = self.end_sector().max(start);
+            write_sectors(file, offset, &data)
+                .map_err(|e| format!("移动条目 {} 失败: {}", entry.name, e))?;
+            self.entries[index].offset = offset as u32;
+        }
+        Ok(())
+    }
+
+    fn save_directory(&self, file: &mut File) -> Result<(), String> {
+        match &self.dir_path {
+            Some(dir_path) => {
+                let temp = dir_path.with_extension("dir.g2m-tmp");
+                fs::write(&temp, encode_v1_directory(&self.entries))
+                    .and_then(|_| fs::rename(&temp, dir_path))
+                    .map_err(|e| format!("写入 .dir 文件失败: {}", e))
+            }
+            None => write_v2_directory(file, &self.entries)
+                .map_err(|e| format!("写入 IMG 档案目录失败: {}", e)),
+        }
+    }
+}
+
+/// 条目偏移以 32 位扇区号记录，档案不能超过 u32::MAX 个扇区
+fn check_archive_size(end_sector: u64) -> Result<(), String> {
+    if end_sector > u32::MAX as u64 {
+        return Err("IMG 档案超出大小上限".to_string());
+    }
+    Ok(())
+}
+
+fn read_sectors(file: &mut File, offset: u64, count: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset * SECTOR_SIZE))?;
+    let mut data = vec![0u8; (count * SECTOR_SIZE) as usize];
+    // 档案末尾的条目可能没有补齐整个扇区
+    let mut filled = 0;
+    while filled < data.len() {
+        match file.read(&mut data[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    Ok(data)
+}
+
+/// 从指定扇区开始写入数据，并用 0 补齐到扇区边界
+fn write_sectors(file: &mut File, offset: u64, data: &[u8]) -> io::Result<()> {
+    file.seek(SeekFrom::Start(offset * SECTOR_SIZE))?;
+    file.write_all(data)?;
+    let padding = sectors_for(data.len() as u64) * SECTOR_SIZE - data.len() as u64;
+    file.write_all(&vec![0u8; padding as usize])
+}
+
+fn encode_entry(buffer: &mut Vec<u8>, name: &str) {
+    let mut name_bytes = [0u8; NAME_LENGTH];
+    let len = name.len().min(NAME_LENGTH - 1);
+    name_bytes[..len].copy_from_slice(&name.as_bytes()[..len]);
+    buffer.extend_from_slice(&name_bytes);
+}
+
+fn encode_v1_directory(entries: &[ImgEntry]) -> Vec<u8> {
+    let mut buffer = Vec::with_capacity(entries.len() * DIR_ENTRY_SIZE);
+    for entry in entries {
+        buffer.extend_from_slice(&entry.offset.to_le_bytes());
+        buffer.extend_from_slice(&entry.size.to_le_bytes());
+        encode_entry(&mut buffer, &entry.name);
+    }
+    buffer
+}
+
+fn write_v2_directory(file: &mut File, entries: &[ImgEntry]) -> io::Result<()> {
+    let mut buffer = Vec::with_capacity(8 + entries.len() * DIR_ENTRY_SIZE);
+    buffer.extend_from_slice(b"VER2");
+    buffer.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+    for entry in entries {
+        buffer.extend_from_slice(&entry.offset.to_le_bytes());
+        buffer.extend_from_slice(&(entry.size as u16).to_le_bytes());
+        buffer.extend_from_slice(&0u16.to_le_bytes());
+        encode_entry(&mut buffer, &entry.name);
+    }
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(&buffer)
+}
+
+/// 解析游戏目录中的 IMG 档案路径（如 models/gta3.img）
+fn resolve_archive(game_dir: &str, archive: &str) -> Result<PathBuf, String> {
+    check_relative_path(archive).map_err(|reason| format!("\"{}\" {}", archive, reason))?;
+    let path = Path::new(game_dir).join(archive.trim().replace('\\', "/"));
+    if !path.is_file() {
+        return Err(format!("IMG 档案不存在: {}", archive));
+    }
+    Ok(path)
+}
+
+/// 被替换或删除的原条目备份位置：.gtamodx/img_backup/<档案路径>/<条目名>
+fn get_img_backup_path(game_dir: &str, archive: &str, name: &str) -> PathBuf {
+    get_g2m_dir_path(game_dir)
+        .join("img_backup")
+        .join(archive.trim().replace('\\', "/").to_lowercase())
+        .join(name.to_lowercase())
+}
+
+/// 备份条目原来的数据；已有备份时保留最早的一份，返回是否新建了备份
+fn backup_entry(
+    game_dir: &str,
+    archive: &str,
+    img: &ImgArchive,
+    index: usize,
+) -> Result<bool, String> {
+    let backup = get_img_backup_path(game_dir, archive, &img.entries()[index].name);
+    if backup.exists() {
+        return Ok(false);
+    }
+    let data = img.read_entry(index)?;
+    if let Some(parent) = backup.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建备份目录失败: {}", e))?;
+    }
+    fs::write(&backup, data).map_err(|e| format!("备份条目失败: {}", e))?;
+    Ok(true)
+}
+
+/// 列出 IMG 档案中的条目（Tauri 命令）
+#[tauri::command]
+pub async fn list_img_entries(
+    game_dir: String,
+    archive: String,
+) -> Result<ApiResponse<ImgArchiveInfo>, String> {
+    match resolve_archive(&game_dir, &archive).and_then(|path| ImgArchive::open(&path)) {
+        Ok(img) => Ok(ApiResponse::success(img.info())),
+        Err(e) => Ok(ApiResponse::error(e)),
+    }
+}
+
+/// 把条目导出到目录，未指定名称时导出全部条目（Tauri 命令）
+#[tauri::command]
+pub async fn extract_img_entries(
+    game_dir: String,
+    archive: String,
+    names: Vec<String>,
+    output_dir: String,
+) -> Result<ApiResponse<Vec<String>>, String> {
+    let img = match resolve_archive(&game_dir, &archive).and_then(|path| ImgArchive::open(&path)) {
+        Ok(img) => img,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let indices: Vec<usize> = if names.is_empty() {
+        (0..img.entries().len()).collect()
+    } else {
+        let mut indices = Vec::new();
+        for name in &names {
+            match img.find(name) {
+                Some(index) => indices.push(index),
+                None => return Ok(ApiResponse::error(format!("IMG 档案中没有条目 {}", name))),
+            }
+        }
+        indices
+    };
+
+    let output = Path::new(&output_dir);
+    if let Err(e) = fs::create_dir_all(output) {
+        return Ok(ApiResponse::error(format!("创建导出目录失败: {}", e)));
+    }
+    let mut extracted = Vec::new();
+    for index in indices {
+        let entry = &img.entries()[index];
+        if validate_entry_name(&entry.name).is_err() {
+            continue;
+        }
+        let target = output.join(&entry.name);
+        if let Err(e) = img
+            .read_entry(index)
+            .and_then(|data| fs::write(&target, data).map_err(|e| e.to_string()))
+        {
+            return Ok(ApiResponse::error(format!(
+                "导出条目 {} 失败: {}",
+                entry.name, e
+            )));
+        }
+        extracted.push(target.to_string_lossy().to_string());
+    }
+    Ok(ApiResponse::success(extracted))
+}
+
+/// 把文件导入 IMG 档案（条目名为文件名），同名条目被替换前先备份（Tauri 命令）
+#[tauri::command]
+pub async fn import_img_entries(
+    game_dir: String,
+    archive: String,
+    files: Vec<String>,
+) -> Result<ApiResponse<ImgImportResult>, String> {
+    let mut img =
+        match resolve_archive(&game_dir, &archive).and_then(|path| ImgArchive::open(&path)) {
+            Ok(img) => img,
+            Err(e) => return Ok(ApiResponse::error(e)),
+        };
+
+    let mut result = ImgImportResult {
+        added: Vec::new(),
+        replaced: Vec::new(),
+        backed_up: Vec::new(),
+    };
+    for file in &files {
+        let path = Path::new(file);
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let outcome = fs::read(path)
+            .map_err(|e| format!("读取文件失败: {}", e))
+            .and_then(|data| {
+                if let Some(index) = img.find(&name) {
+                    if backup_entry(&game_dir, &archive, &img, index)? {
+                        result.backed_up.push(name.clone());
+                    }
+                }
+                img.put_entry(&name, &data)
+            });
+        match outcome {
+            Ok(true) => result.replaced.push(name),
+            Ok(false) => result.added.push(name),
+            Err(e) => return Ok(ApiResponse::error(format!("导入 {} 失败: {}", file, e))),
+        }
+    }
+    Ok(ApiResponse::success(result))
+}
+
+/// 从 IMG 档案删除条目，删除前先备份（Tauri 命令）
+#[tauri::command]
+pub async fn delete_img_entries(
+    game_dir: String,
+    archive: String,
+    names: Vec<String>,
+) -> Result<ApiResponse<Vec<String>>, String> {
+    let mut img =
+        match resolve_archive(&game_dir, &archive).and_then(|path| ImgArchive::open(&path)) {
+            Ok(img) => img,
+            Err(e) => return Ok(ApiResponse::error(e)),
+        };
+
+    let mut removed = Vec::new();
+    for name in &names {
+        let Some(index) = img.find(name) else {
+            return Ok(ApiResponse::error(format!("IMG 档案中没有条目 {}", name)));
+        };
+        if let Err(e) =
+            backup_entry(&game_dir, &archive, &img, index).and_then(|_| img.remove_entry(name))
+        {
+            return Ok(ApiResponse::error(format!("删除条目 {} 失败: {}", name, e)));
+        }
+        removed.push(name.clone());
+    }
+    Ok(ApiResponse::success(removed))
+}
+
+/// 用备份还原被替换或删除的条目，还原后删除备份（Tauri 命令）
+#[tauri::command]
+pub async fn restore_img_entries(
+    game_dir: String,
+    archive: String,
+    names: Vec<String>,
+) -> Result<ApiResponse<Vec<String>>, String> {
+    let mut img =
+        match resolve_archive(&game_dir, &archive).and_then(|path| ImgArchive::open(&path)) {
+            Ok(img) => img,
+            Err(e) => return Ok(ApiResponse::error(e)),
+        };
+    if let Some(name) = names
+        .iter()
+        .find(|name| !get_img_backup_path(&game_dir, &archive, name).is_file())
+    {
+        return Ok(ApiResponse::error(format!("条目 {} 没有备份", name)));
+    }
+
+    let mut restored = Vec::new();
+    for name in &names {
+        let backup = get_img_backup_path(&game_dir, &archive, name);
+        // 条目名沿用档案中原有的大小写
+        let entry_name = img
+            .find(name)
+            .map(|index| img.entries()[index].name.clone())
+            .unwrap_or_else(|| name.clone());
+        let outcome = fs::read(&backup)
+            .map_err(|e| format!("读取备份失败: {}", e))
+            .and_then(|data| img.put_entry(&entry_name, &data));
+        if let Err(e) = outcome {
+            return Ok(ApiResponse::error(format!("还原条目 {} 失败: {}", name, e)));
+        }
+        let _ = fs::remove_file(&backup);
+        restored.push(entry_name);
+    }
+    Ok(ApiResponse::success(restored))
+}
+
+/// 重建 IMG 档案，回收未使用的空间（Tauri 命令）
+#[tauri::command]
+pub async fn rebuild_img_archive(
+    game_dir: String,
+    archive: String,
+) -> Result<ApiResponse<ImgArchiveInfo>, String> {
+    let path = match resolve_archive(&game_dir, &archive) {
+        Ok(path) => path,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let result = tokio::task::spawn_blocking(move || {
+        let mut img = ImgArchive::open(&path)?;
+        img.rebuild()?;
+        Ok::<_, String>(img.info())
+    })
+    .await;
+
+    match result {
+        Ok(Ok(info)) => Ok(ApiResponse::success(info)),
+        Ok(Err(e)) => Ok(ApiResponse::error(format!("重建 IMG 档案失败: {}", e))),
+        Err(e) => Ok(ApiResponse::error(format!("重建 IMG 档案失败: {}", e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每个测试使用独立的临时目录
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("g2m-img-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn sample(seed: u8, len: usize) -> Vec<u8> {
+        (0..len)
+            .map(|i| (i as u8).wrapping_mul(31) ^ seed)
+            .collect()
+    }
+
+    /// 条目数据按扇区补 0，比较时只看原始长度并确认补齐部分为 0
+    fn assert_entry(img: &ImgArchive, name: &str, expected: &[u8]) {
+        let index = img.find(name).unwrap();
+        let data = img.read_entry(index).unwrap();
+        assert_eq!(
+            data.len() as u64,
+            sectors_for(expected.len() as u64) * SECTOR_SIZE
+        );
+        assert_eq!(
+            &data[..expected.len()],
+            expected,
+            "条目 {} 的内容不同",
+            name
+        );
+        assert!(data[expected.len()..].iter().all(|&b| b == 0));
+    }
+
+    /// 条目互不重叠，且 V2 的条目都位于目录之后
+    fn assert_layout(img: &ImgArchive) {
+        let mut ranges: Vec<(u64, u64)> = img
+            .entries()
+            .iter()
+            .map(|e| (e.offset as u64, e.offset as u64 + e.size as u64))
+            .collect();
+        ranges.sort();
+        for pair in ranges.windows(2) {
+            assert!(pair[0].1 <= pair[1].0, "条目重叠: {:?}", pair);
+        }
+        let start = img.data_start(img.entries().len());
+        assert!(ranges.iter().all(|&(offset, _)| offset >= start));
+    }
+
+    #[test]
+    fn v1_put_replace_remove_rebuild() {
+        let dir = temp_dir("v1");
+        let img_path = dir.join("test.img");
+        fs::write(&img_path, b"").unwrap();
+        fs::write(dir.join("test.dir"), b"").unwrap();
+
+        let a = sample(1, 3000);
+        let b = sample(2, 100);
+        let c = sample(3, 2048);
+        let mut img = ImgArchive::open(&img_path).unwrap();
+        assert_eq!(img.version, ImgVersion::V1);
+        assert!(!img.put_entry("a.dff", &a).unwrap());
+        assert!(!img.put_entry("b.txd", &b).unwrap());
+        assert!(!img.put_entry("c.col", &c).unwrap());
+
+        // 替换的数据总是追加到末尾，不覆盖原条目仍在使用的扇区
+        let a2 = sample(4, 7000);
+        let b2 = sample(5, 10);
+        let end = img.end_sector();
+        assert!(img.put_entry("A.DFF", &a2).unwrap());
+        assert!(img.put_entry("b.txd", &b2).unwrap());
+        assert_eq!(img.entries()[0].offset as u64, end);
+        assert_eq!(img.entries()[1].offset as u64, end + 4);
+        img.remove_entry("c.col").unwrap();
+
+        let img = ImgArchive::open(&dir.join("test.dir")).unwrap();
+        assert_eq!(img.entries().len(), 2);
+        assert_entry(&img, "a.dff", &a2);
+        assert_entry(&img, "b.txd", &b2);
+        assert_layout(&img);
+
+        let mut img = img;
+        img.rebuild().unwrap();
+        let img = ImgArchive::open(&img_path).unwrap();
+        assert_entry(&img, "a.dff", &a2);
+        assert_entry(&img, "b.txd", &b2);
+        assert_layout(&img);
+        assert_eq!(img.entries()[0].offset, 0);
+        assert_eq!(img.entries()[1].offset, img.entries()[0].size);
+        assert_eq!(fs::metadata(&img_path).unwrap().len(), 5 * SECTOR_SIZE);
+        assert_eq!(
+            fs::metadata(dir.join("test.dir")).unwrap().len(),
+            2 * DIR_ENTRY_SIZE as u64
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn v2_directory_growth_replace_and_rebuild() {
+        let dir = temp_dir("v2");
+        let img_path = dir.join("gta3.img");
+        let mut header = b"VER2".to_vec();
+        header.extend_from_slice(&0u32.to_le_bytes());
+        fs::write(&img_path, header).unwrap();
+
+        // 一个扇区最多容纳 63 个条目（8 字节头 + 32 字节/条目），写入 70 个使目录扩展到第二个扇区
+        let mut expected: Vec<(String, Vec<u8>)> = (0..70)
+            .map(|i| (format!("model{}.dff", i), sample(i as u8, 500 + i * 97)))
+            .collect();
+        let mut img = ImgArchive::open(&img_path).unwrap();
+        assert_eq!(img.version, ImgVersion::V2);
+        for (name, data) in &expected {
+            img.put_entry(name, data).unwrap();
+        }
+        assert_eq!(img.data_start(img.entries().len()), 2);
+
+        let mut img = ImgArchive::open(&img_path).unwrap();
+        assert_eq!(img.entries().len(), 70);
+        for (name, data) in &expected {
+            assert_entry(&img, name, data);
+        }
+        assert_layout(&img);
+
+        // 用更大的数据替换最前面的条目
+        for (i, (name, data)) in expected.iter_mut().enumerate().take(5) {
+            *data = sample(200 + i as u8, 9000);
+            assert!(img.put_entry(name, data).unwrap());
+        }
+        img.remove_entry("model69.dff").unwrap();
+        expected.pop();
+
+        let mut img = ImgArchive::open(&img_path).unwrap();
+        for (name, data) in &expected {
+            assert_entry(&img, name, data);
+        }
+        assert_layout(&img);
+
+        let before = fs::metadata(&img_path).unwrap().len();
+        img.rebuild().unwrap();
+        let img = ImgArchive::open(&img_path).unwrap();
+        assert_eq!(img.entries().len(), expected.len());
+        for (name, data) in &expected {
+            assert_entry(&img, name, data);
+        }
+        assert_layout(&img);
+        let used: u64 = img.entries().iter().map(|e| e.size as u64).sum();
+        let after = fs::metadata(&img_path).unwrap().len();
+        assert_eq!(after, (img.data_start(expected.len()) + used) * SECTOR_SIZE);
+        assert!(after < before);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,376 @@
+use std::fs;
+use std::path::Path;
+
+/// INI 文件中的一行
+enum IniLine {
+    /// 注释、空行等，原样写回
+    Raw(String),
+    /// [分段名]
+    Section { raw: String, name: String },
+    /// 键 = 值（列表分段中没有 = 的行 value 为空）
+    Entry {
+        raw: String,
+        key: String,
+        value: Option<String>,
+    },
+}
+
+/// 保留格式的 INI 文件：未修改的行（包括注释和对齐空格）原样写回
+/// 分段名和键名不区分大小写；值后面的 ; 注释不属于值
+pub struct IniFile {
+    lines: Vec<IniLine>,
+    crlf: bool,
+    trailing_newline: bool,
+    utf8: bool,
+    bom: bool,
+}
+
+/// 去掉行内 ; 注释
+fn strip_comment(text: &str) -> &str {
+    text.find(';').map_or(text, |index| &text[..index]).trim()
+}
+
+fn parse_line(line: &str) -> IniLine {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with(';') || trimmed.starts_with('#') {
+        return IniLine::Raw(line.to_string());
+    }
+    // [分段名] 后面只能有注释，"[a]=1" 这样的行是键
+    let content = strip_comment(trimmed);
+    if let Some(name) = content
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+    {
+        return IniLine::Section {
+            raw: line.to_string(),
+            name: name.trim().to_string(),
+        };
+    }
+    let (key, value) = match content.split_once('=') {
+        Some((key, value)) => (key.trim(), Some(value.trim().to_string())),
+        None => (content, None),
+    };
+    IniLine::Entry {
+        raw: line.to_string(),
+        key: key.to_string(),
+        value,
+    }
+}
+
+impl IniFile {
+    /// 解析 INI 内容；不是 UTF-8 的文件按字节对应到 U+0000–U+00FF，写回时保持原始字节
+    pub fn parse(content: &[u8]) -> Self {
+        let (text, utf8) = match std::str::from_utf8(content) {
+            Ok(text) => (text.to_string(), true),
+            Err(_) => (content.iter().map(|&b| b as char).collect(), false),
+        };
+        let bom = text.starts_with('\u{FEFF}');
+        let text = text.strip_prefix('\u{FEFF}').unwrap_or(&text);
+        let crlf = text.contains("\r\n");
+        let trailing_newline = text.is_empty() || text.ends_with('\n');
+        let body = text.strip_suffix('\n').unwrap_or(text);
+        let lines = if text.is_empty() {
+            Vec::new()
+        } else {
+            body.split('\n')
+                .map(|line| parse_line(line.strip_suffix('\r').unwrap_or(line)))
+                .collect()
+        };
+        Self {
+            lines,
+            crlf,
+            trailing_newline,
+            utf8,
+            bom,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        fs::read(path)
+            .map(|content| Self::parse(&content))
+            .map_err(|e| format!("读取 {} 失败: {}", path.display(), e))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        let newline = if self.crlf { "\r\n" } else { "\n" };
+        let mut text = if self.bom {
+            String::from('\u{FEFF}')
+        } else {
+            String::new()
+        };
+        for (index, line) in self.lines.iter().enumerate() {
+            if index > 0 {
+                text.push_str(newline);
+            }
+            match line {
+                IniLine::Raw(raw) | IniLine::Section { raw, .. } | IniLine::Entry { raw, .. } => {
+                    text.push_str(raw)
+                }
+            }
+        }
+        if self.trailing_newline && !self.lines.is_empty() {
+            text.push_str(newline);
+        }
+        if self.utf8 {
+            return Ok(text.into_bytes());
+        }
+        text.chars()
+            .map(|c| u8::try_from(c as u32).map_err(|_| format!("INI 文件无法保存字符 {}", c)))
+            .collect()
+    }
+
+    /// 先写入临时文件再替换，避免写入中断时损坏原文件
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let bytes = self.to_bytes()?;
+        let temp = path.with_extension("g2m-tmp");
+        fs::write(&temp, bytes)
+            .and_then(|_| fs::rename(&temp, path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&temp);
+                format!("写入 {} 失败: {}", path.display(), e)
+            })
+    }
+
+    /// 所有分段名（按出现顺序，同名分段只保留一个）
+    pub fn sections(&self) -> Vec<String> {
+        let mut sections: Vec<String> = Vec::new();
+        for line in &self.lines {
+            if let IniLine::Section { name, .. } = line {
+                if !sections.iter().any(|s| s.eq_ignore_ascii_case(name)) {
+                    sections.push(name.clone());
+                }
+            }
+        }
+        sections
+    }
+
+    pub fn has_section(&self, section: &str) -> bool {
+        self.lines.iter().any(
+            |l| matches!(l, IniLine::Section { name, .. } if name.eq_ignore_ascii_case(section)),
+        )
+    }
+
+    /// 分段中的所有条目（键, 值）；同名分段的条目合并
+    pub fn entries(&self, section: &str) -> Vec<(String, Option<String>)> {
+        let mut current = false;
+        let mut entries = Vec::new();
+        for line in &self.lines {
+            match line {
+                IniLine::Section { name, .. } => current = name.eq_ignore_ascii_case(section),
+                IniLine::Entry { key, value, .. } if current => {
+                    entries.push((key.clone(), value.clone()))
+                }
+                _ => {}
+            }
+        }
+        entries
+    }
+
+    /// 读取键的值（键重复时以最后一行为准）
+    pub fn get(&self, section: &str, key: &str) -> Option<String> {
+        self.entries(section)
+            .into_iter()
+            .rev()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .and_then(|(_, value)| value)
+    }
+
+    /// 分段中键所在的行
+    fn find_entry(&self, section: &str, key: &str) -> Option<usize> {
+        let mut current = false;
+        let mut found = None;
+        for (index, line) in self.lines.iter().enumerate() {
+            match line {
+                IniLine::Section { name, .. } => current = name.eq_ignore_ascii_case(section),
+                IniLine::Entry { key: k, .. } if current && k.eq_ignore_ascii_case(key) => {
+                    found = Some(index)
+                }
+                _ => {}
+            }
+        }
+        found
+    }
+
+    /// 在分段的最后一个条目之后插入一行，没有该分段时在文件末尾新建
+    fn insert_into_section(&mut self, section: &str, line: IniLine) {
+        let mut current = false;
+        let mut position = None;
+        for (index, l) in self.lines.iter().enumerate() {
+            match l {
+                IniLine::Section { name, .. } => {
+                    current = name.eq_ignore_ascii_case(section);
+                    if current {
+                        position = Some(index + 1);
+                    }
+                }
+                IniLine::Raw(raw) if current && raw.trim().is_empty() => {}
+                _ if current => position = Some(index + 1),
+                _ => {}
+            }
+        }
+        match position {
+            Some(index) => self.lines.insert(index, line),
+            None => {
+                self.add_section(section);
+                self.lines.push(line);
+            }
+        }
+    }
+
+    /// 在文件末尾新建分段（已存在时不做修改），与前面的内容空一行
+    pub fn add_section(&mut self, section: &str) {
+        if self.has_section(section) {
+            return;
+        }
+        if self
+            .lines
+            .last()
+            .is_some_and(|l| !matches!(l, IniLine::Raw(raw) if raw.trim().is_empty()))
+        {
+            self.lines.push(IniLine::Raw(String::new()));
+        }
+        self.lines.push(IniLine::Section {
+            raw: format!("[{}]", section),
+            name: section.to_string(),
+        });
+    }
+
+    /// 设置键的值：已有的行只替换值（保留键名、对齐和行内注释），否则追加到分段末尾
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        if let Some(index) = self.find_entry(section, key) {
+            if let IniLine::Entry { raw, .. } = &self.lines[index] {
+                if let Some(eq) = raw.find('=') {
+                    let after = &raw[eq + 1..];
+                    let value_start = eq + 1 + (after.len() - after.trim_start().len());
+                    let value_end = raw[value_start..]
+                        .find(';')
+                        .map_or(raw.len(), |i| value_start + i);
+                    // 后面有行内注释时补齐空格，保持注释的对齐
+                    let region = if value_end < raw.len() {
+                        let width = (value_end - value_start).max(value.len() + 1);
+                        format!("{:<width$}", value)
+                    } else {
+                        value.to_string()
+                    };
+                    let new_raw = format!("{}{}{}", &raw[..value_start], region, &raw[value_end..]);
+                    self.lines[index] = parse_line(&new_raw);
+                    return;
+                }
+            }
+            self.lines[index] = parse_line(&format!("{}={}", key, value));
+            return;
+        }
+        self.insert_into_section(section, parse_line(&format!("{}={}", key, value)));
+    }
+
+    /// 在列表分段中添加一行（已有同名行时不添加）
+    pub fn add_item(&mut self, section: &str, item: &str) {
+        if self.find_entry(section, item).is_none() {
+            self.insert_into_section(section, parse_line(item));
+        }
+    }
+
+    /// 删除分段中键名相同的所有行，返回是否删除
+    pub fn remove(&mut self, section: &str, key: &str) -> bool {
+        let mut current = false;
+        let before = self.lines.len();
+        self.lines.retain(|line| match line {
+            IniLine::Section { name, .. } => {
+                current = name.eq_ignore_ascii_case(section);
+                true
+            }
+            IniLine::Entry { key: k, .. } => !(current && k.eq_ignore_ascii_case(key)),
+            _ => true,
+        });
+        self.lines.len() != before
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(content: &[u8]) -> IniFile {
+        let ini = IniFile::parse(content);
+        assert_eq!(
+            ini.to_bytes().unwrap(),
+            content,
+            "{:?}",
+            String::from_utf8_lossy(content)
+        );
+        ini
+    }
+
+    #[test]
+    fn parse_and_write_back_unchanged() {
+        let samples: [&[u8]; 9] = [
+            b"",
+            b"\n",
+            b"[Main]\nkey=value\n",
+            b"[Main]\r\nkey = value ; comment\r\n\r\n# other\r\n",
+            b"[Main]\nno newline at end",
+            b"\xEF\xBB\xBF[Main]\r\nName=\xE4\xB8\xAD\xE6\x96\x87\r\n",
+            b"; latin-1\n[Caf\xE9]\nName=\xE9t\xE9  ; \xA9\n",
+            b"  [ Spaced ]  ; section comment\n\tkey\t=\t1\t\nlist item\n\n\n",
+            b"[a]=1\n[Main]\n;key=1\n=empty key\n",
+        ];
+        for sample in samples {
+            round_trip(sample);
+        }
+    }
+
+    #[test]
+    fn reads_sections_and_values() {
+        let ini = round_trip(b"[Main]\r\nA = 1 ; one\r\n[Files]\r\nmod.asi\r\n[main]\r\na=2\r\n");
+        assert_eq!(ini.sections(), vec!["Main", "Files"]);
+        assert_eq!(ini.get("MAIN", "a").as_deref(), Some("2"));
+        assert_eq!(ini.entries("Files"), vec![("mod.asi".to_string(), None)]);
+    }
+
+    #[test]
+    fn set_keeps_alignment_and_comments() {
+        let mut ini =
+            IniFile::parse(b"[Main]\r\nWidth   = 800   ; width\r\nHeight=600\r\nName = x ; c\r\n");
+        ini.set("main", "width", "1024");
+        ini.set("Main", "HEIGHT", "720");
+        ini.set("Main", "Name", "longer");
+        assert_eq!(
+            ini.to_bytes().unwrap(),
+            b"[Main]\r\nWidth   = 1024  ; width\r\nHeight=720\r\nName = longer ; c\r\n"
+        );
+    }
+
+    #[test]
+    fn set_and_add_item_insert_into_section() {
+        let mut ini = IniFile::parse(b"[Main]\nA=1\n\n[Other]\nB=2");
+        ini.set("Main", "C", "3");
+        ini.add_item("Other", "item.cs");
+        ini.add_item("Other", "ITEM.CS");
+        ini.set("New", "D", "4");
+        assert_eq!(
+            ini.to_bytes().unwrap(),
+            b"[Main]\nA=1\nC=3\n\n[Other]\nB=2\nitem.cs\n\n[New]\nD=4"
+        );
+    }
+
+    #[test]
+    fn add_then_remove_restores_original() {
+        let original: &[u8] = b"\xEF\xBB\xBF[Main]\r\nA=1 ; keep\r\n\r\n[List]\r\nfirst\r\n";
+        let mut ini = IniFile::parse(original);
+        ini.set("Main", "B", "2");
+        ini.add_item("List", "second");
+        assert!(ini.remove("main", "b"));
+        assert!(ini.remove("LIST", "Second"));
+        assert!(!ini.remove("List", "missing"));
+        assert_eq!(ini.to_bytes().unwrap(), original);
+    }
+
+    #[test]
+    fn latin1_file_keeps_raw_bytes() {
+        let mut ini = IniFile::parse(b"[Caf\xE9]\nName=\xE9t\xE9 ; \xA9\n");
+        ini.set("Caf\u{E9}", "Name", "hiver");
+        assert_eq!(ini.to_bytes().unwrap(), b"[Caf\xE9]\nName=hiver ; \xA9\n");
+        ini.set("Caf\u{E9}", "Name", "中");
+        assert!(ini.to_bytes().is_err());
+    }
+}
@@ -0,0 +1,89 @@
+/// 二进制 IPL 的文件头：bnry + 6 个分段的数量 + 6 组（偏移, 大小）
+const BINARY_HEADER_SIZE: usize = 76;
+const BINARY_INST_SIZE: usize = 40;
+const BINARY_CAR_SIZE: usize = 48;
+
+/// IPL 文件中放置的一个模型（inst 中的物体或 cars 中的车辆生成点）
+#[derive(Debug, Clone)]
+pub struct IplInstance {
+    pub id: i32,               // 模型 ID
+    pub model: Option<String>, // 模型名称（仅文本 IPL 的 inst 分段有）
+}
+
+fn read_i32(data: &[u8], offset: usize) -> Option<i32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// 解析 IPL 文件中的 inst 和 cars 分段，支持文本格式和 SA 的二进制格式（bnry）
+pub fn parse_ipl(data: &[u8]) -> Result<Vec<IplInstance>, String> {
+    if data.starts_with(b"bnry") {
+        parse_binary_ipl(data)
+    } else {
+        Ok(parse_text_ipl(&String::from_utf8_lossy(data)))
+    }
+}
+
+fn parse_text_ipl(content: &str) -> Vec<IplInstance> {
+    let mut instances = Vec::new();
+    let mut section: Option<String> = None;
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some(current) = &section else {
+            if !line.contains(',') {
+                section = Some(line.to_lowercase());
+            }
+            continue;
+        };
+        if line.eq_ignore_ascii_case("end") {
+            section = None;
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let instance = match current.as_str() {
+            // id, 模型名称, ...
+            "inst" => fields
+                .first()
+                .and_then(|id| id.parse().ok())
+                .map(|id| IplInstance {
+                    id,
+                    model: fields.get(1).map(|m| m.to_string()),
+                }),
+            // x, y, z, 角度, id, ...
+            "cars" => fields
+                .get(4)
+                .and_then(|id| id.parse().ok())
+                .map(|id| IplInstance { id, model: None }),
+            _ => None,
+        };
+        instances.extend(instance);
+    }
+
+    instances
+}
+
+fn parse_binary_ipl(data: &[u8]) -> Result<Vec<IplInstance>, String> {
+    if data.len() < BINARY_HEADER_SIZE {
+        return Err("二进制 IPL 文件头不完整".to_string());
+    }
+    let count = |index: usize| read_i32(data, 4 + index * 4).unwrap_or(0).max(0) as usize;
+    let offset = |index: usize| read_i32(data, 28 + index * 8).unwrap_or(0).max(0) as usize;
+
+    let mut instances = Vec::new();
+    // 分段顺序：inst、未知、未知、未知、cars、未知
+    for (section, item_size, id_offset) in [(0, BINARY_INST_SIZE, 28), (4, BINARY_CAR_SIZE, 16)] {
+        let start = offset(section);
+        for index in 0..count(section) {
+            let id = read_i32(data, start + index * item_size + id_offset)
+                .ok_or_else(|| "二进制 IPL 数据不完整".to_string())?;
+            instances.push(IplInstance { id, model: None });
+        }
+    }
+    Ok(instances)
+}
@@ -0,0 +1,16 @@
+pub mod cleo;
+pub mod dat;
+pub mod fxt;
+pub mod gxt;
+pub mod ide;
+pub mod img;
+pub mod ini;
+pub mod ipl;
+pub mod rw;
+pub mod text;
+
+pub use cleo::analyze_cleo_script;
+pub use dat::{add_dat_entries, list_dat_entries, remove_dat_entries};
+pub use img::{delete_img_entries, extract_img_entries, import_img_entries, list_img_entries, rebuild_img_archive, restore_img_entries};
+pub use rw::inspect_game_asset;
+pub use text::{diff_text_tables, dump_text_table};
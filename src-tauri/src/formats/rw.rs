@@ -0,0 +1,337 @@
+use crate::game::descriptors::game_engine;
+use crate::game::types::{
+    ApiResponse, AssetGameMismatch, GameAssetInfo, PlannedFileCopy, RwFrameInfo, RwGeometryInfo,
+    RwTextureInfo,
+};
+use std::fs;
+use std::path::Path;
+
+// RenderWare 二进制流的分块类型
+const CHUNK_STRUCT: u32 = 0x01;
+const CHUNK_EXTENSION: u32 = 0x03;
+const CHUNK_MATERIAL_LIST: u32 = 0x08;
+const CHUNK_FRAME_LIST: u32 = 0x0E;
+const CHUNK_GEOMETRY: u32 = 0x0F;
+const CHUNK_CLUMP: u32 = 0x10;
+const CHUNK_ATOMIC: u32 = 0x14;
+const CHUNK_TEXTURE_NATIVE: u32 = 0x15;
+const CHUNK_TEXTURE_DICTIONARY: u32 = 0x16;
+const CHUNK_GEOMETRY_LIST: u32 = 0x1A;
+/// Rockstar 的节点名称插件，保存帧的名称
+const CHUNK_FRAME_NAME: u32 = 0x0253F2FE;
+
+const CHUNK_HEADER_SIZE: usize = 12;
+const FRAME_SIZE: usize = 56;
+
+/// TXD 中纹理的平台：PC 上 III/VC 为 D3D8，SA 为 D3D9
+const PLATFORM_D3D8: u32 = 8;
+const PLATFORM_D3D9: u32 = 9;
+
+/// 二进制流中的一个分块，version 为解码后的 RenderWare 版本（如 0x36003）
+struct Chunk<'a> {
+    kind: u32,
+    version: u32,
+    data: &'a [u8],
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn parse_c_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).to_string()
+}
+
+/// 解码分块头中的库版本标记；3.1.0.1 之前的文件直接保存版本号的高位
+fn decode_version(stamp: u32) -> u32 {
+    if stamp & 0xFFFF_0000 != 0 {
+        (((stamp >> 14) & 0x3FF00) + 0x30000) | ((stamp >> 16) & 0x3F)
+    } else {
+        stamp << 8
+    }
+}
+
+/// 格式化版本号，如 0x36003 -> 3.6.0.3
+fn format_version(version: u32) -> String {
+    format!(
+        "{}.{}.{}.{}",
+        version >> 16,
+        (version >> 12) & 0xF,
+        (version >> 8) & 0xF,
+        version & 0xFF
+    )
+}
+
+/// 使用该 RenderWare 版本的游戏
+/// III 使用 3.1/3.3，VC 使用 3.3/3.4，SA 使用 3.6；3.3.0.2 在 III 和 VC 中都有
+fn games_for_version(version: u32) -> Vec<String> {
+    let games: &[&str] = match version {
+        v if v >= 0x35000 => &["gtasa"],
+        v if v >= 0x34000 => &["gtavc"],
+        v if v >= 0x33000 => &["gta3", "gtavc"],
+        _ => &["gta3"],
+    };
+    games.iter().map(|g| g.to_string()).collect()
+}
+
+fn read_chunk(data: &[u8], offset: usize) -> Result<(Chunk<'_>, usize), String> {
+    let header = data
+        .get(offset..offset + CHUNK_HEADER_SIZE)
+        .ok_or_else(|| "RenderWare 分块头不完整".to_string())?;
+    let kind = read_u32(header, 0).unwrap_or_default();
+    let size = read_u32(header, 4).unwrap_or_default() as usize;
+    let stamp = read_u32(header, 8).unwrap_or_default();
+
+    let start = offset + CHUNK_HEADER_SIZE;
+    let body = start
+        .checked_add(size)
+        .and_then(|end| data.get(start..end))
+        .ok_or_else(|| format!("RenderWare 分块 0x{:X} 的数据不完整", kind))?;
+    let chunk = Chunk {
+        kind,
+        version: decode_version(stamp),
+        data: body,
+    };
+    Ok((chunk, start + size))
+}
+
+/// 读取分块内依次排列的子分块
+fn children(data: &[u8]) -> Result<Vec<Chunk<'_>>, String> {
+    let mut chunks = Vec::new();
+    let mut offset = 0;
+    while offset + CHUNK_HEADER_SIZE <= data.len() {
+        let (chunk, next) = read_chunk(data, offset)?;
+        chunks.push(chunk);
+        offset = next;
+    }
+    Ok(chunks)
+}
+
+fn struct_data<'a>(chunks: &[Chunk<'a>]) -> Result<&'a [u8], String> {
+    chunks
+        .iter()
+        .find(|c| c.kind == CHUNK_STRUCT)
+        .map(|c| c.data)
+        .ok_or_else(|| "RenderWare 数据缺少 Struct 分块".to_string())
+}
+
+/// 纹理格式名称：压缩纹理为 DXTn，其他为像素格式（调色板纹理加 PAL 前缀）
+fn raster_format_name(
+    platform: u32,
+    raster_format: u32,
+    d3d_format: u32,
+    compression: u8,
+) -> String {
+    if platform == PLATFORM_D3D9 {
+        let four_cc = d3d_format.to_le_bytes();
+        if four_cc.starts_with(b"DXT") {
+            return String::from_utf8_lossy(&four_cc).to_string();
+        }
+    } else if (1..=5).contains(&compression) {
+        return format!("DXT{}", compression);
+    }
+
+    let pixel = match raster_format & 0x0F00 {
+        0x0100 => "1555",
+        0x0200 => "565",
+        0x0300 => "4444",
+        0x0400 => "LUM8",
+        0x0500 => "8888",
+        0x0600 => "888",
+        0x0A00 => "555",
+        _ => "未知",
+    };
+    if raster_format & 0x2000 != 0 {
+        format!("PAL8 {}", pixel)
+    } else if raster_format & 0x4000 != 0 {
+        format!("PAL4 {}", pixel)
+    } else {
+        pixel.to_string()
+    }
+}
+
+fn parse_texture_native(chunk: &Chunk) -> Result<RwTextureInfo, String> {
+    let data = struct_data(&children(chunk.data)?)?;
+    let platform = read_u32(data, 0).unwrap_or_default();
+    if platform != PLATFORM_D3D8 && platform != PLATFORM_D3D9 {
+        return Err(format!(
+            "不支持的纹理平台 0x{:X}（仅支持 PC 版本）",
+            platform
+        ));
+    }
+    if data.len() < 88 {
+        return Err("纹理数据不完整".to_string());
+    }
+
+    // 平台(4) 过滤方式(4) 名称(32) 遮罩名称(32) 格式(4) Alpha/D3D 格式(4)
+    // 宽(2) 高(2) 位深(1) Mipmap 层数(1) 类型(1) 压缩/标志(1)
+    let raster_format = read_u32(data, 72).unwrap_or_default();
+    let d3d_format = read_u32(data, 76).unwrap_or_default();
+    Ok(RwTextureInfo {
+        name: parse_c_string(&data[8..40]),
+        mask: Some(parse_c_string(&data[40..72])).filter(|m| !m.is_empty()),
+        width: read_u16(data, 80).unwrap_or_default(),
+        height: read_u16(data, 82).unwrap_or_default(),
+        depth: data[84],
+        mip_count: data[85],
+        raster_format: raster_format_name(platform, raster_format, d3d_format, data[87]),
+    })
+}
+
+fn parse_txd(chunk: &Chunk) -> Result<Vec<RwTextureInfo>, String> {
+    children(chunk.data)?
+        .iter()
+        .filter(|c| c.kind == CHUNK_TEXTURE_NATIVE)
+        .map(parse_texture_native)
+        .collect()
+}
+
+fn parse_frames(chunk: &Chunk) -> Result<Vec<RwFrameInfo>, String> {
+    let chunks = children(chunk.data)?;
+    let data = struct_data(&chunks)?;
+    let count = read_u32(data, 0).unwrap_or_default() as usize;
+
+    // 每帧的扩展分块依次排列在 Struct 之后，名称保存在其中的节点名称插件里
+    let mut extensions = chunks.iter().filter(|c| c.kind == CHUNK_EXTENSION);
+    let mut frames = Vec::new();
+    for index in 0..count {
+        // 旋转矩阵(36) 位置(12) 父帧(4) 标志(4)
+        let parent = read_u32(data, 4 + index * FRAME_SIZE + 48)
+            .ok_or_else(|| "帧列表数据不完整".to_string())?;
+        let name = match extensions.next() {
+            Some(extension) => children(extension.data)?
+                .iter()
+                .find(|c| c.kind == CHUNK_FRAME_NAME)
+                .map(|c| parse_c_string(c.data)),
+            None => None,
+        };
+        frames.push(RwFrameInfo {
+            name,
+            parent: (parent != u32::MAX).then_some(parent),
+        });
+    }
+    Ok(frames)
+}
+
+fn parse_geometry(chunk: &Chunk) -> Result<RwGeometryInfo, String> {
+    let chunks = children(chunk.data)?;
+    let data = struct_data(&chunks)?;
+    // 标志(4) 三角形数(4) 顶点数(4) 变形目标数(4)
+    let (Some(triangle_count), Some(vertex_count)) = (read_u32(data, 4), read_u32(data, 8)) else {
+        return Err("几何体数据不完整".to_string());
+    };
+    let material_count = match chunks.iter().find(|c| c.kind == CHUNK_MATERIAL_LIST) {
+        Some(list) => read_u32(struct_data(&children(list.data)?)?, 0).unwrap_or_default(),
+        None => 0,
+    };
+    Ok(RwGeometryInfo {
+        vertex_count,
+        triangle_count,
+        material_count,
+    })
+}
+
+fn parse_dff(chunk: &Chunk) -> Result<(Vec<RwFrameInfo>, Vec<RwGeometryInfo>, u32), String> {
+    let chunks = children(chunk.data)?;
+    let atomic_count = read_u32(struct_data(&chunks)?, 0).unwrap_or_default();
+
+    let frames = match chunks.iter().find(|c| c.kind == CHUNK_FRAME_LIST) {
+        Some(list) => parse_frames(list)?,
+        None => Vec::new(),
+    };
+    let geometries = match chunks.iter().find(|c| c.kind == CHUNK_GEOMETRY_LIST) {
+        Some(list) => children(list.data)?
+            .iter()
+            .filter(|c| c.kind == CHUNK_GEOMETRY)
+            .map(parse_geometry)
+            .collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+    let atomic_count =
+        atomic_count.max(chunks.iter().filter(|c| c.kind == CHUNK_ATOMIC).count() as u32);
+    Ok((frames, geometries, atomic_count))
+}
+
+/// 解析 TXD 纹理字典或 DFF 模型，按文件开头的分块类型识别
+pub fn inspect_asset(data: &[u8]) -> Result<GameAssetInfo, String> {
+    let kind = read_u32(data, 0);
+    if kind != Some(CHUNK_TEXTURE_DICTIONARY) && kind != Some(CHUNK_CLUMP) {
+        return Err("不是 TXD 或 DFF 文件".to_string());
+    }
+    let (chunk, _) = read_chunk(data, 0)?;
+    let mut info = GameAssetInfo {
+        kind: String::new(),
+        rw_version: format_version(chunk.version),
+        games: games_for_version(chunk.version),
+        textures: Vec::new(),
+        frames: Vec::new(),
+        geometries: Vec::new(),
+        atomic_count: 0,
+    };
+    match chunk.kind {
+        CHUNK_TEXTURE_DICTIONARY => {
+            info.kind = "txd".to_string();
+            info.textures = parse_txd(&chunk)?;
+        }
+        _ => {
+            info.kind = "dff".to_string();
+            (info.frames, info.geometries, info.atomic_count) = parse_dff(&chunk)?;
+        }
+    }
+    Ok(info)
+}
+
+/// 是否为 TXD 或 DFF 文件（按扩展名判断）
+pub fn is_rw_asset(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("txd") || e.eq_ignore_ascii_case("dff"))
+}
+
+/// 检查即将安装的 TXD/DFF 文件（包括 img:// 目标）的 RenderWare 版本是否适用于当前游戏
+/// 自定义游戏按其基于的引擎检查；无法识别游戏类型或解析失败的文件不报告
+pub fn check_asset_games(
+    game_type: Option<&str>,
+    copies: &[PlannedFileCopy],
+) -> Vec<AssetGameMismatch> {
+    let Some(engine) = game_type.and_then(game_engine) else {
+        return Vec::new();
+    };
+    copies
+        .iter()
+        .filter(|c| is_rw_asset(&c.target))
+        .filter_map(|copy| {
+            let info = fs::read(&copy.source)
+                .ok()
+                .and_then(|data| inspect_asset(&data).ok())?;
+            if info.games.iter().any(|g| g.eq_ignore_ascii_case(engine)) {
+                return None;
+            }
+            Some(AssetGameMismatch {
+                file: copy.target.clone(),
+                rw_version: info.rw_version,
+                games: info.games,
+            })
+        })
+        .collect()
+}
+
+/// 查看 TXD 纹理字典或 DFF 模型的内容和适用的游戏
+#[tauri::command]
+pub async fn inspect_game_asset(path: String) -> Result<ApiResponse<GameAssetInfo>, String> {
+    let data = match fs::read(&path) {
+        Ok(data) => data,
+        Err(e) => return Ok(ApiResponse::error(format!("无法读取文件 {}: {}", path, e))),
+    };
+    match inspect_asset(&data) {
+        Ok(info) => Ok(ApiResponse::success(info)),
+        Err(e) => Ok(ApiResponse::error(e)),
+    }
+}
@@ -0,0 +1,183 @@
+use crate::formats::fxt::{FxtFile, FXT_TABLE};
+use crate::formats::gxt::GxtFile;
+use crate::game::types::{
+    ApiResponse, TextEntry, TextEntryDiff, TextTableDiff, TextTableDump, TextTableInfo,
+};
+use std::fs;
+use std::path::Path;
+
+/// 游戏的文本表：GXT（III/VC/SA）或 CLEO 的 FXT
+pub enum TextFile {
+    Gxt(GxtFile),
+    Fxt(FxtFile),
+}
+
+fn is_fxt(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("fxt"))
+}
+
+impl TextFile {
+    /// 读取文本表，按扩展名区分 FXT，其他文件按 GXT 解析
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = fs::read(path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+        if is_fxt(path) {
+            return Ok(Self::Fxt(FxtFile::parse(&data)));
+        }
+        GxtFile::parse(&data)
+            .map(Self::Gxt)
+            .map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// 先写入临时文件再替换，避免写入中断时损坏原文件
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let bytes = match self {
+            Self::Gxt(gxt) => gxt.to_bytes()?,
+            Self::Fxt(fxt) => fxt.to_bytes(),
+        };
+        let temp = path.with_extension("g2m-tmp");
+        fs::write(&temp, bytes)
+            .and_then(|_| fs::rename(&temp, path))
+            .map_err(|e| {
+                let _ = fs::remove_file(&temp);
+                format!("写入 {} 失败: {}", path.display(), e)
+            })
+    }
+
+    /// 格式名称：gta3、gtavc、gtasa（8 位）、gtasa16 或 fxt
+    pub fn format_name(&self) -> &'static str {
+        match self {
+            Self::Gxt(gxt) => gxt.format.name(),
+            Self::Fxt(_) => "fxt",
+        }
+    }
+
+    /// 所有表及其条目
+    pub fn tables(&self) -> Vec<TextTableInfo> {
+        let table = |name: &str, entries: Vec<(String, String)>| TextTableInfo {
+            name: name.to_string(),
+            entries: entries
+                .into_iter()
+                .map(|(key, text)| TextEntry { key, text })
+                .collect(),
+        };
+        match self {
+            Self::Gxt(gxt) => gxt
+                .tables
+                .iter()
+                .map(|t| table(&t.name, t.entries.clone().into_iter().collect()))
+                .collect(),
+            Self::Fxt(fxt) => vec![table(FXT_TABLE, fxt.entries())],
+        }
+    }
+
+    pub fn get(&self, table: &str, key: &str) -> Option<&str> {
+        match self {
+            Self::Gxt(gxt) => gxt.get(table, key),
+            Self::Fxt(fxt) => fxt.get(key).filter(|_| table == FXT_TABLE),
+        }
+    }
+
+    pub fn set(&mut self, table: &str, key: &str, text: &str) -> Result<(), String> {
+        match self {
+            Self::Gxt(gxt) => gxt.set(table, key, text),
+            Self::Fxt(_) if table != FXT_TABLE => Err(format!("FXT 文件没有 {} 表", table)),
+            Self::Fxt(fxt) => fxt.set(key, text),
+        }
+    }
+
+    pub fn remove(&mut self, table: &str, key: &str) -> bool {
+        match self {
+            Self::Gxt(gxt) => gxt.remove(table, key),
+            Self::Fxt(fxt) => table == FXT_TABLE && fxt.remove(key),
+        }
+    }
+}
+
+/// 比较两个文本表（格式必须相同，SA 的键为 CRC32 才能对应）
+pub fn diff_text_files(base: &TextFile, other: &TextFile) -> Result<TextTableDiff, String> {
+    if base.format_name() != other.format_name() {
+        return Err(format!(
+            "文本表格式不同（{} 与 {}），无法比较",
+            base.format_name(),
+            other.format_name()
+        ));
+    }
+
+    let mut diff = TextTableDiff {
+        added: Vec::new(),
+        changed: Vec::new(),
+        removed: Vec::new(),
+    };
+    for table in other.tables() {
+        for entry in table.entries {
+            let old_text = base.get(&table.name, &entry.key);
+            if old_text == Some(entry.text.as_str()) {
+                continue;
+            }
+            let change = TextEntryDiff {
+                table: table.name.clone(),
+                key: entry.key,
+                old_text: old_text.map(str::to_string),
+                new_text: Some(entry.text),
+            };
+            if change.old_text.is_some() {
+                diff.changed.push(change);
+            } else {
+                diff.added.push(change);
+            }
+        }
+    }
+    for table in base.tables() {
+        for entry in table.entries {
+            if other.get(&table.name, &entry.key).is_none() {
+                diff.removed.push(TextEntryDiff {
+                    table: table.name.clone(),
+                    key: entry.key,
+                    old_text: Some(entry.text),
+                    new_text: None,
+                });
+            }
+        }
+    }
+    Ok(diff)
+}
+
+/// 读取 GXT/FXT 文本表的内容，可只返回指定的表（Tauri 命令）
+#[tauri::command]
+pub async fn dump_text_table(
+    path: String,
+    table: Option<String>,
+) -> Result<ApiResponse<TextTableDump>, String> {
+    let text = match TextFile::load(Path::new(&path)) {
+        Ok(text) => text,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let mut tables = text.tables();
+    if let Some(name) = table.as_deref().map(str::trim).filter(|t| !t.is_empty()) {
+        tables.retain(|t| t.name.eq_ignore_ascii_case(name));
+        if tables.is_empty() {
+            return Ok(ApiResponse::error(format!("文本表中没有 {} 表", name)));
+        }
+    }
+    Ok(ApiResponse::success(TextTableDump {
+        format: text.format_name().to_string(),
+        tables,
+    }))
+}
+
+/// 比较两个 GXT/FXT 文件：other 相对 base 新增、修改和删除的条目（Tauri 命令）
+#[tauri::command]
+pub async fn diff_text_tables(
+    base: String,
+    other: String,
+) -> Result<ApiResponse<TextTableDiff>, String> {
+    let result = TextFile::load(Path::new(&base)).and_then(|base| {
+        let other = TextFile::load(Path::new(&other))?;
+        diff_text_files(&base, &other)
+    });
+    match result {
+        Ok(diff) => Ok(ApiResponse::success(diff)),
+        Err(e) => Ok(ApiResponse::error(e)),
+    }
+}
@@ -0,0 +1,427 @@
+use crate::game::download::get_extract_records;
+use crate::game::lock::GameDirLock;
+use crate::game::snapshot::{file_matches, get_object_path, hash_file, scan_game_dir};
+use crate::game::types::{
+    ApiResponse, G2MModInfo, GameBaseline, GameFileChanges, GameFileVerification, GameSnapshotFile,
+    ModFileChanges, RestoredVanillaFile, SkippedVanillaFile, VanillaRestoreResult,
+};
+use crate::game::utils::{get_config_dir, get_g2m_dir_path, read_g2m_json};
+use crate::mod_core::img_target::{is_img_target, split_img_target};
+use crate::mod_core::ownership::load_ownership_index;
+use crate::mod_core::transaction::get_mod_backup_dir;
+use chrono::Utc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+fn get_baseline_path(game_dir: &Path) -> PathBuf {
+    get_g2m_dir_path(&game_dir.to_string_lossy()).join("baseline.json")
+}
+
+fn load_baseline(game_dir: &Path) -> Option<GameBaseline> {
+    let content = fs::read_to_string(get_baseline_path(game_dir)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 扫描游戏目录（跳过 .gtamodx）
+fn scan_original_files(game_dir: &Path) -> io::Result<BTreeMap<String, (PathBuf, u64, u64)>> {
+    let g2m_dir = get_g2m_dir_path(&game_dir.to_string_lossy());
+    Ok(scan_game_dir(game_dir, &[g2m_dir.as_path()])?.files)
+}
+
+/// 计算游戏目录中每个文件的哈希，保存为 .gtamodx/baseline.json
+fn capture_baseline(game_dir: &Path) -> Result<GameBaseline, String> {
+    let scanned = scan_original_files(game_dir).map_err(|e| format!("扫描游戏目录失败: {}", e))?;
+    let mut files = Vec::new();
+    for (relative, (path, size, modified)) in scanned {
+        let hash = hash_file(&path).map_err(|e| format!("读取文件失败: {}\n{}", relative, e))?;
+        files.push(GameSnapshotFile {
+            path: relative,
+            size,
+            hash,
+            modified,
+        });
+    }
+    let baseline = GameBaseline {
+        time: Utc::now().timestamp_millis().to_string(),
+        files,
+    };
+
+    let path = get_baseline_path(game_dir);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建 .gtamodx 目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&baseline)
+        .map_err(|e| format!("序列化 baseline.json 失败: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("写入 baseline.json 失败: {}", e))?;
+    Ok(baseline)
+}
+
+/// 首次添加游戏时在后台记录原版文件基线（已有基线时不覆盖）
+/// 记录期间持有游戏目录的锁，安装等操作要等记录完成后才能进行，MOD 文件不会被记为原版文件
+pub fn spawn_baseline_capture(game_dir: String) {
+    let game_path = PathBuf::from(game_dir);
+    if get_baseline_path(&game_path).exists() {
+        return;
+    }
+    let lock = match GameDirLock::acquire(&game_path, "记录原版文件基线") {
+        Ok(lock) => lock,
+        Err(e) => {
+            eprintln!("警告: 跳过记录原版文件基线: {}", e);
+            return;
+        }
+    };
+    tokio::task::spawn_blocking(move || {
+        let _lock = lock;
+        if let Err(e) = capture_baseline(&game_path) {
+            eprintln!("警告: 记录原版文件基线失败: {}", e);
+        }
+    });
+}
+
+/// 单个文件相对基线的变化
+enum FileChange {
+    Modified,
+    Missing,
+    Added,
+}
+
+/// 比较当前游戏目录与基线，返回（相对路径, 变化类型）和未变化的文件数量
+fn diff_against_baseline(
+    game_dir: &Path,
+    baseline: &GameBaseline,
+) -> Result<(Vec<(String, FileChange)>, usize), String> {
+    let scanned = scan_original_files(game_dir).map_err(|e| format!("扫描游戏目录失败: {}", e))?;
+    // Windows 下文件名不区分大小写，按小写比较
+    let current: HashMap<String, (&String, &(PathBuf, u64, u64))> = scanned
+        .iter()
+        .map(|(relative, entry)| (relative.to_lowercase(), (relative, entry)))
+        .collect();
+    let original: HashSet<String> = baseline
+        .files
+        .iter()
+        .map(|file| file.path.to_lowercase())
+        .collect();
+
+    let mut changes = Vec::new();
+    let mut unchanged = 0;
+    for file in &baseline.files {
+        match current.get(&file.path.to_lowercase()) {
+            Some((_, entry)) if file_matches(file, entry) => unchanged += 1,
+            Some(_) => changes.push((file.path.clone(), FileChange::Modified)),
+            None => changes.push((file.path.clone(), FileChange::Missing)),
+        }
+    }
+    for (lower, (relative, _)) in &current {
+        if !original.contains(lower) {
+            changes.push(((*relative).clone(), FileChange::Added));
+        }
+    }
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok((changes, unchanged))
+}
+
+/// 查找管理文件的 MOD：优先使用所有权记录，旧版本安装的 MOD 按安装清单查找
+/// 写入了 IMG 档案条目的 MOD 也视为该档案的管理者
+pub(crate) fn file_owners(game_dir: &Path, mods: &[G2MModInfo]) -> impl Fn(&str) -> Option<u32> {
+    let ownership = load_ownership_index(game_dir);
+    let mut installed: HashMap<String, u32> = HashMap::new();
+    for mod_info in mods {
+        for relative in &mod_info.installed_files {
+            installed.insert(relative.replace('\\', "/").to_lowercase(), mod_info.id);
+        }
+    }
+    for mod_info in mods {
+        for relative in mod_info.installed_files.iter().filter(|f| is_img_target(f)) {
+            let (archive, _) = split_img_target(relative);
+            installed.entry(archive.to_lowercase()).or_insert(mod_info.id);
+        }
+    }
+    move |relative: &str| {
+        ownership
+            .owner_of(relative)
+            .or_else(|| installed.get(&relative.to_lowercase()).copied())
+    }
+}
+
+/// 对照原版基线校验游戏文件，按是否属于已知 MOD 分组（Tauri 命令）
+#[tauri::command]
+pub async fn verify_game_files(
+    game_dir: String,
+) -> Result<ApiResponse<GameFileVerification>, String> {
+    let game_path = PathBuf::from(&game_dir);
+    let Some(baseline) = load_baseline(&game_path) else {
+        return Ok(ApiResponse::error(
+            "没有原版文件基线（仅在首次添加游戏时记录）".to_string(),
+        ));
+    };
+    let mods = read_g2m_json(&game_dir)
+        .map(|config| config.mods)
+        .unwrap_or_default();
+
+    let result = tokio::task::spawn_blocking(move || {
+        let (changes, unchanged_files) = diff_against_baseline(&game_path, &baseline)?;
+        let owner_of = file_owners(&game_path, &mods);
+
+        let mut by_mod: BTreeMap<u32, GameFileChanges> = BTreeMap::new();
+        let mut unknown = GameFileChanges::default();
+        for (relative, change) in changes {
+            let group = match owner_of(&relative) {
+                Some(id) if mods.iter().any(|m| m.id == id) => by_mod.entry(id).or_default(),
+                _ => &mut unknown,
+            };
+            match change {
+                FileChange::Modified => group.modified.push(relative),
+                FileChange::Missing => group.missing.push(relative),
+                FileChange::Added => group.added.push(relative),
+            }
+        }
+
+        Ok::<_, String>(GameFileVerification {
+            baseline_time: baseline.time.clone(),
+            checked_files: baseline.files.len(),
+            unchanged_files,
+            mods: by_mod
+                .into_iter()
+                .map(|(mod_id, changes)| ModFileChanges {
+                    mod_id,
+                    mod_name: mods
+                        .iter()
+                        .find(|m| m.id == mod_id)
+                        .map(|m| m.name.clone())
+                        .unwrap_or_default(),
+                    changes,
+                })
+                .collect(),
+            unknown,
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(verification)) => Ok(ApiResponse::success(verification)),
+        Ok(Err(e)) => Ok(ApiResponse::error(format!("校验游戏文件失败: {}", e))),
+        Err(e) => Ok(ApiResponse::error(format!("校验游戏文件失败: {}", e))),
+    }
+}
+
+/// 原版文件的来源
+enum VanillaSource {
+    File(PathBuf, String),
+    Zip(PathBuf),
+}
+
+/// 先复制到临时文件并校验哈希，一致时再替换目标文件
+fn copy_verified(
+    target: &Path,
+    expected: &GameSnapshotFile,
+    write: impl FnOnce(&mut fs::File) -> io::Result<u64>,
+) -> io::Result<bool> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp = target.with_file_name(format!(
+        "{}.g2m-restore",
+        target.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    let written = fs::File::create(&temp).and_then(|mut file| write(&mut file));
+    let verified = written.is_ok_and(|size| size == expected.size)
+        && hash_file(&temp).is_ok_and(|hash| hash == expected.hash);
+    if !verified {
+        let _ = fs::remove_file(&temp);
+        return Ok(false);
+    }
+    if target.is_file() {
+        fs::remove_file(target)?;
+    }
+    fs::rename(&temp, target)?;
+    Ok(true)
+}
+
+/// 依次尝试各个来源还原单个文件，返回使用的来源说明
+fn restore_from_sources(
+    target: &Path,
+    expected: &GameSnapshotFile,
+    sources: &[VanillaSource],
+) -> Option<String> {
+    for source in sources {
+        match source {
+            VanillaSource::File(path, label) => {
+                let size_matches = fs::metadata(path).is_ok_and(|m| m.len() == expected.size);
+                if size_matches
+                    && copy_verified(target, expected, |out| {
+                        io::copy(&mut fs::File::open(path)?, out)
+                    })
+                    .unwrap_or(false)
+                {
+                    return Some(label.clone());
+                }
+            }
+            VanillaSource::Zip(zip_path) => {
+                let Some(mut archive) = fs::File::open(zip_path)
+                    .ok()
+                    .and_then(|file| ZipArchive::new(file).ok())
+                else {
+                    continue;
+                };
+                let wanted = expected.path.to_lowercase();
+                let index = (0..archive.len()).find(|&i| {
+                    archive.by_index(i).is_ok_and(|entry| {
+                        entry.name().replace('\\', "/").to_lowercase() == wanted
+                            && entry.size() == expected.size
+                    })
+                });
+                let Some(index) = index else {
+                    continue;
+                };
+                let restored = copy_verified(target, expected, |out| {
+                    let mut entry = archive.by_index(index).map_err(io::Error::other)?;
+                    io::copy(&mut entry, out)
+                })
+                .unwrap_or(false);
+                if restored {
+                    return Some(zip_path.to_string_lossy().to_string());
+                }
+            }
+        }
+    }
+    None
+}
+
+/// 把被修改或缺失的原版文件还原（Tauri 命令）
+/// 来源依次为：MOD 安装时的备份、快照仓库、下载的游戏压缩包；来源文件的哈希必须与基线一致
+/// 未指定文件时还原所有不属于已知 MOD 的修改；属于已知 MOD 的文件请先禁用或卸载该 MOD
+#[tauri::command]
+pub async fn restore_vanilla_files(
+    game_dir: String,
+    files: Option<Vec<String>>,
+    app_handle: tauri::AppHandle,
+) -> Result<ApiResponse<VanillaRestoreResult>, String> {
+    let game_path = PathBuf::from(&game_dir);
+    let Some(baseline) = load_baseline(&game_path) else {
+        return Ok(ApiResponse::error(
+            "没有原版文件基线（仅在首次添加游戏时记录）".to_string(),
+        ));
+    };
+    let mods = read_g2m_json(&game_dir)
+        .map(|config| config.mods)
+        .unwrap_or_default();
+    let config_dir = get_config_dir(&app_handle).ok();
+
+    // 下载的游戏压缩包：解压到本游戏目录的优先
+    let mut archives: Vec<(bool, PathBuf)> = get_extract_records(app_handle)
+        .await
+        .ok()
+        .and_then(|response| response.data)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|record| {
+            let same_dir = Path::new(&record.game_dir) == game_path;
+            (same_dir, PathBuf::from(record.zip_path))
+        })
+        .filter(|(_, zip_path)| zip_path.is_file())
+        .collect();
+    archives.sort_by_key(|(same_dir, _)| !*same_dir);
+    archives.dedup_by(|a, b| a.1 == b.1);
+
+    let result = tokio::task::spawn_blocking(move || {
+        let (changes, _) = diff_against_baseline(&game_path, &baseline)?;
+        let changed: HashSet<String> = changes
+            .iter()
+            .filter(|(_, change)| !matches!(change, FileChange::Added))
+            .map(|(relative, _)| relative.to_lowercase())
+            .collect();
+        let owner_of = file_owners(&game_path, &mods);
+        let originals: HashMap<String, &GameSnapshotFile> = baseline
+            .files
+            .iter()
+            .map(|file| (file.path.to_lowercase(), file))
+            .collect();
+
+        let mut result = VanillaRestoreResult {
+            restored_files: Vec::new(),
+            skipped_files: Vec::new(),
+        };
+        let mut skip = |path: &str, reason: String| {
+            result.skipped_files.push(SkippedVanillaFile {
+                path: path.to_string(),
+                reason,
+            })
+        };
+
+        let mut targets = Vec::new();
+        match files {
+            Some(files) => {
+                for path in files {
+                    let relative = path.replace('\\', "/");
+                    match originals.get(&relative.to_lowercase()) {
+                        None => skip(&relative, "不在原版文件清单中".to_string()),
+                        Some(_) if !changed.contains(&relative.to_lowercase()) => {
+                            skip(&relative, "与原版一致".to_string())
+                        }
+                        Some(file) => targets.push(*file),
+                    }
+                }
+            }
+            None => targets.extend(
+                baseline
+                    .files
+                    .iter()
+                    .filter(|file| changed.contains(&file.path.to_lowercase())),
+            ),
+        }
+
+        let mut restored = Vec::new();
+        for file in targets {
+            if let Some(mod_info) =
+                owner_of(&file.path).and_then(|id| mods.iter().find(|m| m.id == id))
+            {
+                skip(
+                    &file.path,
+                    format!("由 MOD \"{}\" 管理，请先禁用或卸载该 MOD", mod_info.name),
+                );
+                continue;
+            }
+
+            let mut sources: Vec<VanillaSource> = mods
+                .iter()
+                .map(|m| {
+                    VanillaSource::File(
+                        get_mod_backup_dir(&game_path, m.id).join(&file.path),
+                        format!("MOD 备份 (ID {})", m.id),
+                    )
+                })
+                .collect();
+            if let Some(config_dir) = &config_dir {
+                sources.push(VanillaSource::File(
+                    get_object_path(config_dir, &file.hash),
+                    "快照仓库".to_string(),
+                ));
+            }
+            sources.extend(
+                archives
+                    .iter()
+                    .map(|(_, zip_path)| VanillaSource::Zip(zip_path.clone())),
+            );
+
+            match restore_from_sources(&game_path.join(&file.path), file, &sources) {
+                Some(source) => restored.push(RestoredVanillaFile {
+                    path: file.path.clone(),
+                    source,
+                }),
+                None => skip(&file.path, "没有找到与原版一致的备份".to_string()),
+            }
+        }
+        result.restored_files = restored;
+        Ok::<_, String>(result)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(result)) => Ok(ApiResponse::success(result)),
+        Ok(Err(e)) => Ok(ApiResponse::error(format!("还原原版文件失败: {}", e))),
+        Err(e) => Ok(ApiResponse::error(format!("还原原版文件失败: {}", e))),
+    }
+}
@@ -1,7 +1,10 @@
 use crate::game::utils::{
-    calculate_file_md5, copy_dir_all, detect_game_type_from_exe, get_config_dir,
-    get_game_version_from_md5, read_g2m_json, write_g2m_json,
+    copy_dir_all, detect_game_type_from_exe, get_bundled_dir, get_config_dir, read_g2m_json,
+    write_g2m_json,
 };
+use crate::game::descriptors::find_game_descriptor;
+use crate::game::versions::identify_game_exe;
+use crate::mod_core::variables::resolve_plugin_dir;
 use crate::game::{
     ApiResponse, CopyImageResponse, GameInfo, GameList, ModInstallRequest, ModInstallResult,
 };
@@ -11,6 +14,7 @@ use rand::{thread_rng, Rng};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use tauri::Manager;
 
 // 游戏启动功能
 #[tauri::command]
@@ -104,6 +108,38 @@ pub async fn open_game_folder(game_dir: String) -> Result<ApiResponse<()>, Strin
     }
 }
 
+// 打开游戏的用户文件目录（文档中的存档和设置目录，如 GTA San Andreas User Files）
+#[tauri::command]
+pub async fn open_user_files_folder(
+    game_type: String,
+    app_handle: tauri::AppHandle,
+) -> Result<ApiResponse<()>, String> {
+    let Some(folder) = find_game_descriptor(&game_type).and_then(|d| d.user_files_dir.as_deref())
+    else {
+        return Ok(ApiResponse::error(format!(
+            "游戏类型 {} 没有用户文件目录",
+            game_type
+        )));
+    };
+    let documents = match app_handle.path().document_dir() {
+        Ok(dir) => dir,
+        Err(e) => return Ok(ApiResponse::error(format!("获取文档目录失败: {}", e))),
+    };
+
+    let user_files_path = documents.join(folder);
+    if !user_files_path.exists() {
+        return Ok(ApiResponse::error(format!(
+            "用户文件目录不存在，请先运行一次游戏: {}",
+            user_files_path.display()
+        )));
+    }
+
+    match tauri_plugin_opener::open_path(&user_files_path, None::<&str>) {
+        Ok(_) => Ok(ApiResponse::success(())),
+        Err(e) => Ok(ApiResponse::error(format!("打开目录失败: {}", e))),
+    }
+}
+
 // 游戏数据存储功能
 #[tauri::command]
 pub async fn save_game(
@@ -164,17 +200,11 @@ pub async fn save_game(
         detect_game_type_from_exe(&exe)
     };
 
-    // 计算MD5值和版本
+    // 计算主程序指纹并识别版本
     let game_path = Path::new(&dir);
     let exe_path = game_path.join(&exe);
-    let md5 = if exe_path.exists() {
-        calculate_file_md5(&exe_path).ok()
-    } else {
-        None
-    };
-
-    let version = if let (Some(ref gt), Some(ref md5_hash)) = (game_type.as_ref(), md5.as_ref()) {
-        get_game_version_from_md5(gt, md5_hash)
+    let exe_info = if exe_path.exists() {
+        identify_game_exe(game_type.as_deref(), &exe_path).ok()
     } else {
         None
     };
@@ -188,9 +218,12 @@ pub async fn save_game(
         exe,
         img,
         r#type: game_type,
-        version,
-        md5,
+        version: exe_info.as_ref().and_then(|info| info.version.clone()),
+        md5: exe_info.as_ref().map(|info| info.md5.clone()),
+        sha256: exe_info.as_ref().map(|info| info.sha256.clone()),
+        loader_compatible: exe_info.as_ref().and_then(|info| info.loader_compatible),
         deleted: false, // 新游戏默认未删除
+        snapshots: Vec::new(),
     };
 
     // 添加到游戏列表
@@ -217,6 +250,9 @@ pub async fn save_game(
         &new_game.r#type,
     );
 
+    // 在后台记录原版文件基线（.gtamodx/baseline.json），用于之后校验和还原原版文件
+    crate::game::baseline::spawn_baseline_capture(new_game.dir.clone());
+
     Ok(ApiResponse::success(()))
 }
 
@@ -321,18 +357,17 @@ pub async fn update_game(
             game.deleted = deleted_value;
         }
 
-        // 重新计算MD5和版本（如果目录和exe发生变化）
+        // 重新计算主程序指纹和版本（如果目录和exe发生变化）
         let game_path = Path::new(&dir);
         let exe_path = game_path.join(&exe);
         if exe_path.exists() {
-            if let Ok(md5_hash) = calculate_file_md5(&exe_path) {
-                game.md5 = Some(md5_hash.clone());
-                // 根据MD5识别版本
-                let detected_type = detect_game_type_from_exe(&exe);
-                let game_type_for_version = r#type.as_deref().or(detected_type.as_deref());
-                if let Some(gt) = game_type_for_version {
-                    game.version = get_game_version_from_md5(gt, &md5_hash);
-                }
+            let detected_type = detect_game_type_from_exe(&exe);
+            let game_type_for_version = r#type.as_deref().or(detected_type.as_deref());
+            if let Ok(exe_info) = identify_game_exe(game_type_for_version, &exe_path) {
+                game.md5 = Some(exe_info.md5);
+                game.sha256 = Some(exe_info.sha256);
+                game.version = exe_info.version;
+                game.loader_compatible = exe_info.loader_compatible;
             }
         }
 
@@ -417,6 +452,9 @@ pub async fn delete_game(id: u32, app_handle: tauri::AppHandle) -> Result<ApiRes
         }
     }
 
+    // 删除该游戏的快照
+    crate::game::snapshot::remove_game_snapshots(&config_dir, id);
+
     Ok(ApiResponse::success(()))
 }
 
@@ -563,36 +601,7 @@ pub async fn install_mod_prerequisites(
     }
 
     // 获取 G2M/Module 目录路径
-    let module_dir = if cfg!(debug_assertions) {
-        // 在开发环境中，使用项目根目录
-        let current_dir =
-            std::env::current_dir().map_err(|e| format!("获取当前目录失败: {}", e))?;
-
-        // 如果当前目录是 src-tauri，则向上找到项目根目录
-        let project_root = if current_dir
-            .file_name()
-            .and_then(|name| name.to_str())
-            .map(|name| name == "src-tauri")
-            .unwrap_or(false)
-        {
-            current_dir
-                .parent()
-                .ok_or("无法获取项目根目录")?
-                .to_path_buf()
-        } else {
-            current_dir
-        };
-
-        project_root.join("src-tauri").join("G2M").join("Module")
-    } else {
-        // 在生产环境中，使用exe文件所在目录
-        let exe_dir = std::env::current_exe()
-            .map_err(|e| format!("获取程序路径失败: {}", e))?
-            .parent()
-            .ok_or("无法获取程序目录")?
-            .to_path_buf();
-        exe_dir.join("G2M").join("Module")
-    };
+    let module_dir = get_bundled_dir("Module")?;
 
     if !module_dir.exists() {
         return Ok(ApiResponse::error(format!(
@@ -608,28 +617,20 @@ pub async fn install_mod_prerequisites(
     let components_to_install = if let Some(components) = &request.components {
         components.clone()
     } else {
-        // 如果没有指定组件，安装默认组件
-        vec!["dinput8".to_string(), "cleo".to_string()]
+        // 如果没有指定组件，安装游戏描述中的默认组件
+        find_game_descriptor(&request.game_type)
+            .map(|d| d.prerequisites.clone())
+            .unwrap_or_else(|| vec!["dinput8".to_string(), "cleo".to_string()])
     };
 
     println!("准备安装组件: {:?}", components_to_install);
 
     // 创建 plugins 和 scripts 文件夹（如果需要）
     let plugins_dir = game_path.join("plugins");
-    let scripts_dir = game_path.join("scripts");
     let mut plugins_created = false;
 
-    // 辅助函数：确定 ASI 文件的安装目录（优先级：plugins > scripts > 根目录）
-    let determine_asi_install_dir = || -> PathBuf {
-        if plugins_dir.exists() {
-            plugins_dir.clone()
-        } else if scripts_dir.exists() {
-            scripts_dir.clone()
-        } else {
-            // 默认创建并使用 plugins 目录
-            game_path.join("plugins")
-        }
-    };
+    // 确定 ASI 文件的安装目录，与安装 MOD 时使用同一规则
+    let determine_asi_install_dir = || resolve_plugin_dir(game_path);
 
     // 辅助函数：判断路径是否为 plugins 目录
     let is_plugins_dir = |path: &PathBuf| -> bool {
@@ -681,146 +682,64 @@ pub async fn install_mod_prerequisites(
             }
         }
 
-        match request.game_type.as_str() {
-            "gta3" => {
-                let cleo3_dir = module_dir.join("CLEO.III_v2.1.1");
-                if cleo3_dir.exists() {
-                    // 复制 III.CLEO.asi 到目标目录（plugins/scripts/根目录）
-                    let asi_source = cleo3_dir.join("III.CLEO.asi");
-                    let asi_dest = asi_dest_dir.join("III.CLEO.asi");
+        let descriptor = find_game_descriptor(&request.game_type);
+        match descriptor.and_then(|d| d.cleo.as_ref().map(|cleo| (d, cleo))) {
+            Some((descriptor, cleo)) => {
+                let cleo_module_dir = module_dir.join(&cleo.module);
+                if cleo_module_dir.exists() {
+                    // 复制 CLEO 的 ASI 文件到目标目录（plugins/scripts/根目录）
+                    let asi_source = cleo_module_dir.join(&cleo.asi);
+                    let asi_dest = asi_dest_dir.join(&cleo.asi);
                     if asi_source.exists() {
                         if let Err(e) = fs::copy(&asi_source, &asi_dest) {
                             return Ok(ApiResponse::error(format!(
-                                "复制 III.CLEO.asi 失败: {}",
-                                e
+                                "复制 {} 失败: {}",
+                                cleo.asi, e
                             )));
                         }
                         let dest_path_str = if is_plugins_dir(&asi_dest_dir) {
-                            "plugins/III.CLEO.asi".to_string()
+                            format!("plugins/{}", cleo.asi)
                         } else if is_scripts_dir(&asi_dest_dir) {
-                            "scripts/III.CLEO.asi".to_string()
+                            format!("scripts/{}", cleo.asi)
                         } else {
-                            "III.CLEO.asi".to_string()
+                            cleo.asi.clone()
                         };
                         installed_files.push(dest_path_str);
                     }
 
-                    // 复制 CLEO 文件夹到游戏根目录
-                    let cleo_source = cleo3_dir.join("CLEO");
-                    let cleo_dest = game_path.join("CLEO");
-                    if cleo_source.exists() {
-                        if let Err(e) = copy_dir_all(&cleo_source, &cleo_dest) {
-                            return Ok(ApiResponse::error(format!("复制 CLEO 目录失败: {}", e)));
-                        }
-                        created_directories.push("CLEO".to_string());
-                    }
-                    println!("已安装: CLEO for GTA III");
-                }
-            }
-            "gtavc" => {
-                let cleovc_dir = module_dir.join("CLEO.VC_v2.1.1");
-                if cleovc_dir.exists() {
-                    // 复制 VC.CLEO.asi 到目标目录（plugins/scripts/根目录）
-                    let asi_source = cleovc_dir.join("VC.CLEO.asi");
-                    let asi_dest = asi_dest_dir.join("VC.CLEO.asi");
-                    if asi_source.exists() {
-                        if let Err(e) = fs::copy(&asi_source, &asi_dest) {
-                            return Ok(ApiResponse::error(format!("复制 VC.CLEO.asi 失败: {}", e)));
-                        }
-                        let dest_path_str = if is_plugins_dir(&asi_dest_dir) {
-                            "plugins/VC.CLEO.asi".to_string()
-                        } else if is_scripts_dir(&asi_dest_dir) {
-                            "scripts/VC.CLEO.asi".to_string()
-                        } else {
-                            "VC.CLEO.asi".to_string()
-                        };
-                        installed_files.push(dest_path_str);
-                    }
-
-                    // 复制 CLEO 文件夹到游戏根目录
-                    let cleo_source = cleovc_dir.join("CLEO");
-                    let cleo_dest = game_path.join("CLEO");
-                    if cleo_source.exists() {
-                        if let Err(e) = copy_dir_all(&cleo_source, &cleo_dest) {
-                            return Ok(ApiResponse::error(format!("复制 CLEO 目录失败: {}", e)));
-                        }
-                        created_directories.push("CLEO".to_string());
-                    }
-                    println!("已安装: CLEO for GTA Vice City");
-                }
-            }
-            "gtasa" => {
-                let cleosa_dir = module_dir.join("CLEO.SA_v4.44");
-                if cleosa_dir.exists() {
-                    // 复制 CLEO.asi 到目标目录（plugins/scripts/根目录）
-                    let asi_source = cleosa_dir.join("CLEO.asi");
-                    let asi_dest = asi_dest_dir.join("CLEO.asi");
-                    if asi_source.exists() {
-                        if let Err(e) = fs::copy(&asi_source, &asi_dest) {
-                            return Ok(ApiResponse::error(format!("复制 CLEO.asi 失败: {}", e)));
-                        }
-                        let dest_path_str = if is_plugins_dir(&asi_dest_dir) {
-                            "plugins/CLEO.asi".to_string()
-                        } else if is_scripts_dir(&asi_dest_dir) {
-                            "scripts/CLEO.asi".to_string()
-                        } else {
-                            "CLEO.asi".to_string()
-                        };
-                        installed_files.push(dest_path_str);
-                    }
-
-                    // 复制相关 DLL 文件到游戏根目录
-                    let dll_files = ["bass.dll", "vorbisFile.dll", "vorbisHooked.dll"];
-                    for dll_file in &dll_files {
-                        let dll_source = cleosa_dir.join(dll_file);
-                        let dll_dest = game_path.join(dll_file);
-                        if dll_source.exists() {
-                            if let Err(e) = fs::copy(&dll_source, &dll_dest) {
+                    // 复制相关文件（如 SA 的 bass.dll）到游戏根目录
+                    for file in &cleo.files {
+                        let file_source = cleo_module_dir.join(file);
+                        let file_dest = game_path.join(file);
+                        if file_source.exists() {
+                            if let Err(e) = fs::copy(&file_source, &file_dest) {
                                 return Ok(ApiResponse::error(format!(
                                     "复制 {} 失败: {}",
-                                    dll_file, e
+                                    file, e
                                 )));
                             }
-                            installed_files.push(dll_file.to_string());
+                            installed_files.push(file.clone());
                         }
                     }
 
-                    // 复制 cleo 文件夹到游戏根目录
-                    let cleo_source = cleosa_dir.join("cleo");
-                    let cleo_dest = game_path.join("cleo");
-                    if cleo_source.exists() {
-                        if let Err(e) = copy_dir_all(&cleo_source, &cleo_dest) {
-                            return Ok(ApiResponse::error(format!("复制 cleo 目录失败: {}", e)));
-                        }
-                        created_directories.push("cleo".to_string());
-                    }
-
-                    // 复制 scripts 文件夹到游戏根目录
-                    let scripts_source = cleosa_dir.join("scripts");
-                    let scripts_dest = game_path.join("scripts");
-                    if scripts_source.exists() {
-                        if let Err(e) = copy_dir_all(&scripts_source, &scripts_dest) {
-                            return Ok(ApiResponse::error(format!("复制 scripts 目录失败: {}", e)));
-                        }
-                        created_directories.push("scripts".to_string());
-                    }
-
-                    // 复制 cleo_sdk 文件夹到游戏根目录
-                    let sdk_source = cleosa_dir.join("cleo_sdk");
-                    let sdk_dest = game_path.join("cleo_sdk");
-                    if sdk_source.exists() {
-                        if let Err(e) = copy_dir_all(&sdk_source, &sdk_dest) {
-                            return Ok(ApiResponse::error(format!(
-                                "复制 cleo_sdk 目录失败: {}",
-                                e
-                            )));
+                    // 复制 CLEO 等文件夹到游戏根目录
+                    for dir in &cleo.dirs {
+                        let dir_source = cleo_module_dir.join(dir);
+                        let dir_dest = game_path.join(dir);
+                        if dir_source.exists() {
+                            if let Err(e) = copy_dir_all(&dir_source, &dir_dest) {
+                                return Ok(ApiResponse::error(format!(
+                                    "复制 {} 目录失败: {}",
+                                    dir, e
+                                )));
+                            }
+                            created_directories.push(dir.clone());
                         }
-                        created_directories.push("cleo_sdk".to_string());
                     }
-                    println!("已安装: CLEO for GTA San Andreas");
+                    println!("已安装: CLEO for {}", descriptor.name);
                 }
             }
-            _ => {
+            None => {
                 println!("警告: 游戏类型 {} 不支持 CLEO", request.game_type);
             }
         }
@@ -1259,15 +1178,15 @@ pub async fn process_image_upload(
     }))
 }
 
-/// 获取游戏目录下的已安装MOD列表
+/// 获取游戏目录下的已安装MOD列表及当前配置方案
 #[tauri::command]
-pub async fn get_game_mods(game_dir: String) -> Result<ApiResponse<Vec<crate::game::types::G2MModInfo>>, String> {
-    // 读取 .gtamodx/mods.json
-    match read_g2m_json(&game_dir) {
-        Some(config) => Ok(ApiResponse::success(config.mods)),
-        None => {
-            // 如果配置文件不存在，返回空列表
-            Ok(ApiResponse::success(Vec::new()))
-        }
-    }
+pub async fn get_game_mods(game_dir: String) -> Result<ApiResponse<crate::game::types::GameModList>, String> {
+    // 读取 .gtamodx/mods.json，配置文件不存在时返回空列表
+    let mods = read_g2m_json(&game_dir)
+        .map(|config| config.mods)
+        .unwrap_or_default();
+    Ok(ApiResponse::success(crate::game::types::GameModList {
+        mods,
+        active_profile: crate::mod_core::profiles::get_active_profile(Path::new(&game_dir)),
+    }))
 }
@@ -0,0 +1,187 @@
+use crate::game::types::{ApiResponse, BundledCleo, GameDescriptor};
+use crate::game::utils::get_bundled_dir;
+use crate::mod_core::utils::check_relative_path;
+use serde::Deserialize;
+use std::fs;
+use std::sync::OnceLock;
+
+static GAME_DESCRIPTORS: OnceLock<Vec<GameDescriptor>> = OnceLock::new();
+
+/// 游戏描述可以基于的游戏引擎（内置的三部游戏）
+const ENGINES: [&str; 3] = ["gta3", "gtavc", "gtasa"];
+
+/// 游戏描述数据文件（G2M/Data/games.json）
+#[derive(Debug, Deserialize)]
+struct GameDescriptorFile {
+    #[serde(default)]
+    games: Vec<GameDescriptor>,
+}
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+/// 内置的三部游戏
+fn builtin_descriptors() -> Vec<GameDescriptor> {
+    vec![
+        GameDescriptor {
+            id: "gta3".to_string(),
+            name: "GTA III".to_string(),
+            full_name: "Grand Theft Auto III".to_string(),
+            engine: "gta3".to_string(),
+            executables: strings(&["gta3.exe"]),
+            cleo_dir: "CLEO".to_string(),
+            data_file: Some("data/gta3.dat".to_string()),
+            user_files_dir: Some("GTA3 User Files".to_string()),
+            download_name: Some("Grand Theft Auto III".to_string()),
+            prerequisites: strings(&["dinput8", "cleo"]),
+            cleo: Some(BundledCleo {
+                module: "CLEO.III_v2.1.1".to_string(),
+                asi: "III.CLEO.asi".to_string(),
+                files: Vec::new(),
+                dirs: strings(&["CLEO"]),
+            }),
+        },
+        GameDescriptor {
+            id: "gtavc".to_string(),
+            name: "GTA Vice City".to_string(),
+            full_name: "Grand Theft Auto: Vice City".to_string(),
+            engine: "gtavc".to_string(),
+            executables: strings(&["gta-vc.exe"]),
+            cleo_dir: "CLEO".to_string(),
+            data_file: Some("data/gta_vc.dat".to_string()),
+            user_files_dir: Some("GTA Vice City User Files".to_string()),
+            download_name: Some("Grand Theft Auto Vice City".to_string()),
+            prerequisites: strings(&["dinput8", "cleo"]),
+            cleo: Some(BundledCleo {
+                module: "CLEO.VC_v2.1.1".to_string(),
+                asi: "VC.CLEO.asi".to_string(),
+                files: Vec::new(),
+                dirs: strings(&["CLEO"]),
+            }),
+        },
+        GameDescriptor {
+            id: "gtasa".to_string(),
+            name: "GTA San Andreas".to_string(),
+            full_name: "Grand Theft Auto: San Andreas".to_string(),
+            engine: "gtasa".to_string(),
+            executables: strings(&["gta_sa.exe", "gta-sa.exe", "gtasa.exe"]),
+            cleo_dir: "cleo".to_string(),
+            data_file: Some("data/gta.dat".to_string()),
+            user_files_dir: Some("GTA San Andreas User Files".to_string()),
+            download_name: Some("Grand Theft Auto San Andreas".to_string()),
+            prerequisites: strings(&["dinput8", "cleo"]),
+            cleo: Some(BundledCleo {
+                module: "CLEO.SA_v4.44".to_string(),
+                asi: "CLEO.asi".to_string(),
+                files: strings(&["bass.dll", "vorbisFile.dll", "vorbisHooked.dll"]),
+                dirs: strings(&["cleo", "scripts", "cleo_sdk"]),
+            }),
+        },
+    ]
+}
+
+/// 检查数据文件中的游戏描述，路径都必须是相对路径
+fn check_descriptor(descriptor: &GameDescriptor) -> Result<(), String> {
+    if descriptor.id.trim().is_empty() {
+        return Err("游戏类型ID不能为空".to_string());
+    }
+    if !ENGINES.contains(&descriptor.engine.as_str()) {
+        return Err(format!(
+            "游戏 \"{}\" 的 engine 必须是 {} 之一",
+            descriptor.id,
+            ENGINES.join("、")
+        ));
+    }
+    if descriptor.executables.is_empty() {
+        return Err(format!("游戏 \"{}\" 没有指定主程序", descriptor.id));
+    }
+    let cleo_paths = descriptor.cleo.iter().flat_map(|cleo| {
+        [&cleo.module, &cleo.asi]
+            .into_iter()
+            .chain(&cleo.files)
+            .chain(&cleo.dirs)
+    });
+    let paths = descriptor
+        .executables
+        .iter()
+        .chain([&descriptor.cleo_dir])
+        .chain(&descriptor.data_file)
+        .chain(cleo_paths);
+    for path in paths {
+        check_relative_path(path)
+            .map_err(|reason| format!("游戏 \"{}\" 中的 \"{}\" {}", descriptor.id, path, reason))?;
+    }
+    Ok(())
+}
+
+/// 读取 G2M/Data/games.json 中的游戏描述，ID 与内置游戏相同时替换内置的描述
+fn load_descriptors() -> Vec<GameDescriptor> {
+    let mut descriptors = builtin_descriptors();
+    let path = match get_bundled_dir("Data") {
+        Ok(dir) => dir.join("games.json"),
+        Err(_) => return descriptors,
+    };
+    if !path.exists() {
+        return descriptors;
+    }
+    let file = fs::read_to_string(&path)
+        .map_err(|e| format!("读取游戏描述文件失败: {}", e))
+        .and_then(|content| {
+            serde_json::from_str::<GameDescriptorFile>(&content)
+                .map_err(|e| format!("解析游戏描述文件失败: {}", e))
+        });
+    let games = match file {
+        Ok(file) => file.games,
+        Err(e) => {
+            eprintln!("警告: {}", e);
+            return descriptors;
+        }
+    };
+
+    for mut descriptor in games {
+        descriptor.id = descriptor.id.trim().to_lowercase();
+        descriptor.engine = descriptor.engine.trim().to_lowercase();
+        if let Err(e) = check_descriptor(&descriptor) {
+            eprintln!("警告: 忽略游戏描述: {}", e);
+            continue;
+        }
+        match descriptors.iter_mut().find(|d| d.id == descriptor.id) {
+            Some(existing) => *existing = descriptor,
+            None => descriptors.push(descriptor),
+        }
+    }
+    descriptors
+}
+
+/// 所有游戏描述（首次使用时读取数据文件）
+pub fn game_descriptors() -> &'static [GameDescriptor] {
+    GAME_DESCRIPTORS.get_or_init(load_descriptors)
+}
+
+/// 按游戏类型ID查找游戏描述（不区分大小写）
+pub fn find_game_descriptor(game_type: &str) -> Option<&'static GameDescriptor> {
+    game_descriptors()
+        .iter()
+        .find(|d| d.id.eq_ignore_ascii_case(game_type.trim()))
+}
+
+/// 游戏类型基于的游戏引擎（gta3、gtavc 或 gtasa），未知的游戏类型返回 None
+pub fn game_engine(game_type: &str) -> Option<&'static str> {
+    find_game_descriptor(game_type).map(|d| d.engine.as_str())
+}
+
+/// 按主程序文件名查找游戏描述（不区分大小写）
+pub fn find_game_descriptor_by_exe(exe_name: &str) -> Option<&'static GameDescriptor> {
+    game_descriptors().iter().find(|d| {
+        d.executables
+            .iter()
+            .any(|exe| exe.eq_ignore_ascii_case(exe_name))
+    })
+}
+
+/// 获取所有游戏描述（Tauri 命令）
+#[tauri::command]
+pub async fn get_game_descriptors() -> Result<ApiResponse<Vec<GameDescriptor>>, String> {
+    Ok(ApiResponse::success(game_descriptors().to_vec()))
+}
@@ -1,5 +1,6 @@
 use crate::game::{ApiResponse, GameDetectionResult};
-use crate::game::utils::{calculate_file_md5, detect_game_type_from_exe, get_game_version_from_md5};
+use crate::game::descriptors::game_descriptors;
+use crate::game::versions::identify_game_exe;
 use std::path::Path;
 
 #[tauri::command]
@@ -41,44 +42,32 @@ pub async fn detect_game(path: String) -> Result<GameDetectionResult, String> {
             game_name: None,
             version: None,
             md5: None,
+            sha256: None,
+            loader_compatible: None,
             error: Some("指定的路径不存在或不是文件夹".to_string()),
         });
     }
 
-    // 检测游戏主程序
-    let game_executables = [
-        ("gta3.exe", "Grand Theft Auto III"),
-        ("gta-vc.exe", "Grand Theft Auto: Vice City"),
-        ("gtasa.exe", "Grand Theft Auto: San Andreas"),
-        ("gta-sa.exe", "Grand Theft Auto: San Andreas"),
-        ("gta_sa.exe", "Grand Theft Auto: San Andreas"),
-    ];
+    // 按游戏描述检测游戏主程序
+    let game_executables = game_descriptors()
+        .iter()
+        .flat_map(|d| d.executables.iter().map(move |exe| (exe, d)));
 
-    for (exe_name, game_name) in &game_executables {
+    for (exe_name, descriptor) in game_executables {
         let exe_path = game_dir.join(exe_name);
         if exe_path.exists() && exe_path.is_file() {
-            // 使用统一的游戏类型识别函数
-            let game_type = detect_game_type_from_exe(exe_name);
-
-            // 计算MD5值
-            let md5_result = calculate_file_md5(&exe_path);
-            let md5 = md5_result.ok();
-
-            // 根据MD5识别版本
-            let version =
-                if let (Some(ref gt), Some(ref md5_hash)) = (game_type.as_ref(), md5.as_ref()) {
-                    get_game_version_from_md5(gt, md5_hash)
-                } else {
-                    None
-                };
+            // 计算主程序指纹并识别版本
+            let exe_info = identify_game_exe(Some(&descriptor.id), &exe_path).ok();
 
             return Ok(GameDetectionResult {
                 success: true,
-                r#type: game_type,
-                executable: Some(exe_name.to_string()),
-                game_name: Some(game_name.to_string()),
-                version,
-                md5,
+                r#type: Some(descriptor.id.clone()),
+                executable: Some(exe_name.clone()),
+                game_name: Some(descriptor.full_name.clone()),
+                version: exe_info.as_ref().and_then(|info| info.version.clone()),
+                md5: exe_info.as_ref().map(|info| info.md5.clone()),
+                sha256: exe_info.as_ref().map(|info| info.sha256.clone()),
+                loader_compatible: exe_info.as_ref().and_then(|info| info.loader_compatible),
                 error: None,
             });
         }
@@ -91,6 +80,8 @@ pub async fn detect_game(path: String) -> Result<GameDetectionResult, String> {
         game_name: None,
         version: None,
         md5: None,
+        sha256: None,
+        loader_compatible: None,
         error: None,
     })
 }
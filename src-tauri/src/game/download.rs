@@ -1,3 +1,4 @@
+use crate::game::descriptors::find_game_descriptor;
 use crate::game::types::ApiResponse;
 use serde::{Deserialize, Serialize};
 use std::fs::{File, OpenOptions};
@@ -184,14 +185,11 @@ fn write_extract_log(app_handle: &AppHandle, log: &ExtractLog) -> Result<(), Str
     Ok(())
 }
 
-// 游戏文件名映射
-fn get_game_filename(game_type: &str) -> Option<&str> {
-    match game_type {
-        "gta3" => Some("Grand%20Theft%20Auto%20III.zip"),
-        "gtavc" => Some("Grand%20Theft%20Auto%20Vice%20City.zip"),
-        "gtasa" => Some("Grand%20Theft%20Auto%20San%20Andreas.zip"),
-        _ => None,
-    }
+// 游戏下载包文件名（URL 编码），由游戏描述中的下载名称生成
+fn get_game_filename(game_type: &str) -> Option<String> {
+    find_game_descriptor(game_type)
+        .and_then(|d| d.download_name.as_deref())
+        .map(|name| format!("{}.zip", name.replace(' ', "%20")))
 }
 
 // 下载游戏命令
@@ -269,24 +267,19 @@ pub async fn download_game(
     Ok(ApiResponse::success(zip_path.to_string_lossy().to_string()))
 }
 
-// 获取游戏类型对应的文件夹名
+// 获取游戏类型对应的文件夹名（与下载包同名，没有下载包时使用游戏名称）
 fn get_game_folder_name(game_type: &str) -> &str {
-    match game_type {
-        "gta3" => "Grand Theft Auto III",
-        "gtavc" => "Grand Theft Auto Vice City",
-        "gtasa" => "Grand Theft Auto San Andreas",
-        _ => "Unknown Game",
+    match find_game_descriptor(game_type) {
+        Some(d) => d.download_name.as_deref().unwrap_or(&d.name),
+        None => "Unknown Game",
     }
 }
 
-// 获取游戏类型对应的可执行文件名
+// 获取游戏类型对应的可执行文件名（游戏描述中的默认主程序）
 fn get_game_exe_name(game_type: &str) -> &str {
-    match game_type {
-        "gta3" => "gta3.exe",
-        "gtavc" => "gtavc.exe",
-        "gtasa" => "gta_sa.exe",
-        _ => "",
-    }
+    find_game_descriptor(game_type)
+        .and_then(|d| d.executables.first())
+        .map_or("", |exe| exe.as_str())
 }
 
 // 查找可用的游戏目录名（处理重复）
@@ -1,9 +1,14 @@
 pub mod types;
+pub mod baseline;
+pub mod descriptors;
 pub mod core;
 pub mod download;
+pub mod lock;
 pub mod utils;
 pub mod detection;
 pub mod prerequisites;
+pub mod snapshot;
+pub mod versions;
 
 // 重新导出所有公共类型
 pub use types::*;
\ No newline at end of file
@@ -0,0 +1,54 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+/// 正在修改的游戏目录及其正在进行的操作
+static BUSY_GAME_DIRS: Mutex<Vec<(String, &'static str)>> = Mutex::new(Vec::new());
+
+/// 游戏目录的比较键：Windows 下路径不区分大小写，统一使用 / 分隔
+fn lock_key(game_dir: &Path) -> String {
+    let path = game_dir
+        .canonicalize()
+        .unwrap_or_else(|_| game_dir.to_path_buf());
+    path.to_string_lossy().replace('\\', "/").to_lowercase()
+}
+
+/// 游戏目录的独占锁：安装、卸载 MOD 等修改游戏目录的操作与记录原版文件基线不能同时进行
+/// 离开作用域时自动释放
+pub struct GameDirLock {
+    key: String,
+}
+
+impl GameDirLock {
+    /// 获取游戏目录的锁，已有其他操作进行时返回错误（不等待）
+    pub fn acquire(game_dir: &Path, action: &'static str) -> Result<Self, String> {
+        let key = lock_key(game_dir);
+        let mut busy = BUSY_GAME_DIRS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((_, running)) = busy.iter().find(|(k, _)| *k == key) {
+            return Err(format!("游戏目录正在{}，请完成后再{}", running, action));
+        }
+        busy.push((key.clone(), action));
+        Ok(Self { key })
+    }
+}
+
+impl Drop for GameDirLock {
+    fn drop(&mut self) {
+        let mut busy = BUSY_GAME_DIRS.lock().unwrap_or_else(|e| e.into_inner());
+        busy.retain(|(k, _)| *k != self.key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_operation_is_rejected_until_release() {
+        let game_dir = std::env::temp_dir().join(format!("g2m-lock-{}", std::process::id()));
+        let lock = GameDirLock::acquire(&game_dir, "记录原版文件基线").unwrap();
+        let error = GameDirLock::acquire(&game_dir, "安装 MOD").err().unwrap();
+        assert!(error.contains("记录原版文件基线"), "{}", error);
+        drop(lock);
+        assert!(GameDirLock::acquire(&game_dir, "安装 MOD").is_ok());
+    }
+}
@@ -2,7 +2,8 @@ use crate::game::{
     ApiResponse, CustomPrerequisiteFile, CustomPrerequisiteInfo,
     CustomPrerequisiteInstallRequest, ManualLoaderBinding, ModLoaderStatus,
 };
-use crate::game::utils::{copy_dir_all, find_file_case_insensitive};
+use crate::game::utils::{copy_dir_all, find_file_case_insensitive, read_pe_file_version};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use tauri::AppHandle;
@@ -101,10 +102,13 @@ pub async fn check_mod_loaders(
     let mut has_cleo_redux = false;
     let mut found_loaders = Vec::new();
     let mut missing_loaders = Vec::new();
+    // 检测到的加载器文件，用于读取版本信息
+    let mut loader_files: BTreeMap<&str, PathBuf> = BTreeMap::new();
 
     // 检查 dinput8.dll (在游戏根目录，不区分大小写)
-    if let Some((_path, name)) = find_file_case_insensitive(game_path, "dinput8.dll") {
+    if let Some((path, name)) = find_file_case_insensitive(game_path, "dinput8.dll") {
         has_dinput8 = true;
+        loader_files.insert("dinput8", path);
         found_loaders.push(format!("dinput8.dll (游戏根目录/{})", name));
     } else {
         missing_loaders.push("dinput8.dll".to_string());
@@ -140,6 +144,7 @@ pub async fn check_mod_loaders(
                             && !file_name_lower.contains("redux")
                         {
                             has_cleo = true;
+                            loader_files.insert("cleo", entry.path());
                             found_loaders.push(format!("CLEO ({}/{})", dir_name, file_name_str));
                             break;
                         }
@@ -186,8 +191,9 @@ pub async fn check_mod_loaders(
     // 检查所有可能目录中的 modloader.asi（根目录、plugins目录、scripts目录）
     // 不区分大小写查找 modloader.asi，无论是否已找到文件夹，都继续检测 .asi 文件
     for (dir_name, check_dir) in &check_dirs {
-        if let Some((_path, found_name)) = find_file_case_insensitive(check_dir, "modloader.asi") {
+        if let Some((path, found_name)) = find_file_case_insensitive(check_dir, "modloader.asi") {
             has_modloader = true; // 只要找到 modloader.asi 就认为已安装
+            loader_files.entry("modloader").or_insert(path);
             found_loaders.push(format!("ModLoader ({}/{})", dir_name, found_name));
             // 不break，继续检查其他目录，记录所有找到的modloader.asi
         }
@@ -199,8 +205,9 @@ pub async fn check_mod_loaders(
 
     // 检查 CLEO Redux：在根目录、plugins目录、scripts目录中查找 cleo_redux.asi（不区分大小写）
     for (dir_name, check_dir) in &check_dirs {
-        if let Some((_path, found_name)) = find_file_case_insensitive(check_dir, "cleo_redux.asi") {
+        if let Some((path, found_name)) = find_file_case_insensitive(check_dir, "cleo_redux.asi") {
             has_cleo_redux = true;
+            loader_files.insert("cleo_redux", path);
             found_loaders.push(format!("CLEO Redux ({}/{})", dir_name, found_name));
             break; // 找到 CLEO Redux 后立即停止搜索
         }
@@ -225,6 +232,13 @@ pub async fn check_mod_loaders(
                 "游戏根目录"
             };
 
+            if let Some(loader) = ["cleo", "cleo_redux", "modloader", "dinput8"]
+                .into_iter()
+                .find(|l| *l == binding.loader_type)
+            {
+                loader_files.insert(loader, binding_path.clone());
+            }
+
             match binding.loader_type.as_str() {
                 "cleo" => {
                     // 移除标准检测到的 CLEO 信息
@@ -307,6 +321,25 @@ pub async fn check_mod_loaders(
         .map(|b| b.loader_type.clone())
         .collect();
 
+    // 读取加载器文件中的版本信息（没有版本资源的文件不记录）
+    let loader_versions = loader_files
+        .iter()
+        .filter_map(|(loader, path)| {
+            read_pe_file_version(path).map(|version| (loader.to_string(), version))
+        })
+        .collect();
+
+    let loader_files = loader_files
+        .iter()
+        .map(|(loader, path)| {
+            let relative = path.strip_prefix(game_path).unwrap_or(path);
+            (
+                loader.to_string(),
+                relative.to_string_lossy().replace('\\', "/"),
+            )
+        })
+        .collect();
+
     let status = ModLoaderStatus {
         has_dinput8,
         has_modloader,
@@ -315,6 +348,8 @@ pub async fn check_mod_loaders(
         missing_loaders,
         found_loaders,
         manual_bindings,
+        loader_versions,
+        loader_files,
     };
 
     Ok(ApiResponse::success(status))
@@ -0,0 +1,760 @@
+use crate::game::lock::GameDirLock;
+use crate::game::types::{
+    ApiResponse, GameInfo, GameList, GameSnapshotFile, GameSnapshotInfo, GameSnapshotManifest,
+    GameSnapshotRestoreResult,
+};
+use crate::game::utils::get_config_dir;
+use crate::mod_core::transaction::move_file;
+use chrono::{Local, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// 快照仓库：G2M/Config/Snapshots/
+/// objects/<前两位>/<SHA-256> 按内容存放文件，相同内容只存一份
+/// manifests/<游戏ID>/<快照ID>.json 记录每个快照的文件清单
+fn get_snapshot_root(config_dir: &Path) -> PathBuf {
+    config_dir.join("Snapshots")
+}
+
+pub(crate) fn get_object_path(config_dir: &Path, hash: &str) -> PathBuf {
+    get_snapshot_root(config_dir)
+        .join("objects")
+        .join(&hash[..2])
+        .join(hash)
+}
+
+fn get_manifest_dir(config_dir: &Path, game_id: u32) -> PathBuf {
+    get_snapshot_root(config_dir)
+        .join("manifests")
+        .join(game_id.to_string())
+}
+
+fn get_manifest_path(config_dir: &Path, game_id: u32, snapshot_id: &str) -> PathBuf {
+    get_manifest_dir(config_dir, game_id).join(format!("{}.json", snapshot_id))
+}
+
+fn load_game_list(config_dir: &Path) -> Result<GameList, String> {
+    let content = fs::read_to_string(config_dir.join("GameList.json"))
+        .map_err(|e| format!("读取游戏列表失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析游戏列表失败: {}", e))
+}
+
+fn save_game_list(config_dir: &Path, game_list: &GameList) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(game_list)
+        .map_err(|e| format!("序列化游戏列表失败: {}", e))?;
+    fs::write(config_dir.join("GameList.json"), content)
+        .map_err(|e| format!("保存游戏列表失败: {}", e))
+}
+
+fn load_manifest(
+    config_dir: &Path,
+    game_id: u32,
+    snapshot_id: &str,
+) -> Option<GameSnapshotManifest> {
+    // 快照ID是毫秒时间戳，拒绝其他字符以免拼出游戏快照目录之外的路径
+    if snapshot_id.is_empty() || !snapshot_id.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let content = fs::read_to_string(get_manifest_path(config_dir, game_id, snapshot_id)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 计算文件的 SHA-256（分块读取，不把整个文件读入内存）
+pub(crate) fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn modified_millis(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// 游戏目录中的文件：相对路径 -> （完整路径, 大小, 修改时间）
+pub(crate) struct ScannedDir {
+    pub files: BTreeMap<String, (PathBuf, u64, u64)>,
+    pub directories: Vec<String>,
+}
+
+/// 扫描游戏目录（不跟随符号链接），skip 中的目录会被跳过
+pub(crate) fn scan_game_dir(game_dir: &Path, skip: &[&Path]) -> io::Result<ScannedDir> {
+    let skip: Vec<PathBuf> = skip
+        .iter()
+        .filter_map(|dir| dir.canonicalize().ok())
+        .collect();
+    let mut scanned = ScannedDir {
+        files: BTreeMap::new(),
+        directories: Vec::new(),
+    };
+    let mut pending = vec![game_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+            if file_type.is_symlink() {
+                continue;
+            }
+            let relative = path
+                .strip_prefix(game_dir)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            if file_type.is_dir() {
+                if path.canonicalize().is_ok_and(|dir| skip.contains(&dir)) {
+                    continue;
+                }
+                scanned.directories.push(relative);
+                pending.push(path);
+            } else {
+                let metadata = entry.metadata()?;
+                scanned
+                    .files
+                    .insert(relative, (path, metadata.len(), modified_millis(&metadata)));
+            }
+        }
+    }
+    scanned.directories.sort();
+    Ok(scanned)
+}
+
+/// 把文件存入快照仓库（已存在相同内容时跳过），返回新存入的字节数
+fn store_object(config_dir: &Path, hash: &str, source: &Path) -> io::Result<u64> {
+    let object = get_object_path(config_dir, hash);
+    if object.is_file() {
+        return Ok(0);
+    }
+    let parent = object.parent().unwrap_or(config_dir);
+    fs::create_dir_all(parent)?;
+    let temp = parent.join(format!("{}.tmp", hash));
+    let size = fs::copy(source, &temp)?;
+    fs::rename(&temp, &object)?;
+    Ok(size)
+}
+
+/// 为游戏目录创建快照
+/// 与上一个快照相比大小和修改时间都未变化的文件直接沿用之前的哈希，不重新读取
+fn take_snapshot(
+    config_dir: &Path,
+    game: &GameInfo,
+    name: String,
+) -> Result<(GameSnapshotManifest, GameSnapshotInfo), String> {
+    let game_dir = Path::new(&game.dir);
+    let scanned =
+        scan_game_dir(game_dir, &[config_dir]).map_err(|e| format!("扫描游戏目录失败: {}", e))?;
+
+    let previous: HashMap<String, GameSnapshotFile> = game
+        .snapshots
+        .last()
+        .and_then(|info| load_manifest(config_dir, game.id, &info.id))
+        .map(|manifest| {
+            manifest
+                .files
+                .into_iter()
+                .map(|file| (file.path.clone(), file))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut files = Vec::new();
+    let mut total_size = 0;
+    let mut stored_size = 0;
+    for (relative, (path, size, modified)) in scanned.files {
+        let cached = previous.get(&relative).filter(|file| {
+            file.size == size
+                && file.modified == modified
+                && get_object_path(config_dir, &file.hash).is_file()
+        });
+        let hash = match cached {
+            Some(file) => file.hash.clone(),
+            None => {
+                let hash =
+                    hash_file(&path).map_err(|e| format!("读取文件失败: {}\n{}", relative, e))?;
+                stored_size += store_object(config_dir, &hash, &path)
+                    .map_err(|e| format!("保存文件到快照仓库失败: {}\n{}", relative, e))?;
+                hash
+            }
+        };
+        total_size += size;
+        files.push(GameSnapshotFile {
+            path: relative,
+            size,
+            hash,
+            modified,
+        });
+    }
+
+    let now = Utc::now().timestamp_millis().to_string();
+    let manifest = GameSnapshotManifest {
+        id: now.clone(),
+        game_id: game.id,
+        name,
+        time: now,
+        files,
+        directories: scanned.directories,
+    };
+
+    let manifest_dir = get_manifest_dir(config_dir, game.id);
+    fs::create_dir_all(&manifest_dir).map_err(|e| format!("创建快照目录失败: {}", e))?;
+    let content = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("序列化快照清单失败: {}", e))?;
+    fs::write(
+        get_manifest_path(config_dir, game.id, &manifest.id),
+        content,
+    )
+    .map_err(|e| format!("写入快照清单失败: {}", e))?;
+
+    let info = GameSnapshotInfo {
+        id: manifest.id.clone(),
+        name: manifest.name.clone(),
+        time: manifest.time.clone(),
+        file_count: manifest.files.len(),
+        total_size,
+        stored_size,
+    };
+    Ok((manifest, info))
+}
+
+/// 判断当前文件与清单中的文件是否相同：大小一致时比较 SHA-256
+/// 复制和解压工具通常会保留修改时间，大小和修改时间都相同的文件内容仍可能不同，因此不以修改时间为准
+pub(crate) fn file_matches(file: &GameSnapshotFile, current: &(PathBuf, u64, u64)) -> bool {
+    let (path, size, _) = current;
+    *size == file.size && hash_file(path).is_ok_and(|hash| hash == file.hash)
+}
+
+/// 恢复快照时的工作目录：G2M/Config/Snapshots/restore/<游戏ID>/
+/// staging/ 暂存从仓库取出的文件，backup/ 存放被替换或删除的当前文件，恢复完成后删除
+fn get_restore_dir(config_dir: &Path, game_id: u32) -> PathBuf {
+    get_snapshot_root(config_dir)
+        .join("restore")
+        .join(game_id.to_string())
+}
+
+/// 从快照仓库取出单个文件到暂存区，并恢复原修改时间
+fn stage_object(config_dir: &Path, file: &GameSnapshotFile, staged: &Path) -> io::Result<()> {
+    if let Some(parent) = staged.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(get_object_path(config_dir, &file.hash), staged)?;
+    if file.modified > 0 {
+        let modified = UNIX_EPOCH + Duration::from_millis(file.modified);
+        let _ = fs::File::options()
+            .write(true)
+            .open(staged)
+            .and_then(|f| f.set_modified(modified));
+    }
+    Ok(())
+}
+
+/// 恢复快照时对游戏目录中单个文件的操作，失败时据此回滚
+struct RestoredFile {
+    target: PathBuf,
+    /// 原文件移到的备份路径（原来不存在时为 None）
+    backup: Option<PathBuf>,
+    /// 是否已把快照中的文件放到 target（删除多余文件时为 false）
+    placed: bool,
+}
+
+/// 撤销已执行的操作：删除放入的文件、移回原文件、删除新建的目录，返回无法恢复的路径
+fn rollback_restore(applied: &[RestoredFile], created: &[PathBuf]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for op in applied.iter().rev() {
+        if op.placed && op.target.is_file() {
+            if let Err(e) = fs::remove_file(&op.target) {
+                errors.push(format!("{} ({})", op.target.display(), e));
+                continue;
+            }
+        }
+        if let Some(backup) = &op.backup {
+            if let Err(e) = move_file(backup, &op.target) {
+                errors.push(format!("{} ({})", op.target.display(), e));
+            }
+        }
+    }
+    for dir in created.iter().rev() {
+        let _ = fs::remove_dir(dir);
+    }
+    errors
+}
+
+/// 删除多余文件、替换变化的文件；当前文件先移到备份目录，出错时由调用方回滚
+fn apply_restore(
+    game_dir: &Path,
+    backup_dir: &Path,
+    removed: &[String],
+    staged: &[(PathBuf, String)],
+    applied: &mut Vec<RestoredFile>,
+    created: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    for relative in removed {
+        let target = game_dir.join(relative);
+        let backup = backup_dir.join(relative);
+        backup
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| move_file(&target, &backup))
+            .map_err(|e| format!("删除文件失败: {}\n{}", relative, e))?;
+        applied.push(RestoredFile {
+            target,
+            backup: Some(backup),
+            placed: false,
+        });
+    }
+
+    for (staged_path, relative) in staged {
+        let target = game_dir.join(relative);
+        if let Some(parent) = target.parent() {
+            let mut missing = Vec::new();
+            let mut current = Some(parent);
+            while let Some(dir) = current.filter(|dir| !dir.exists() && *dir != game_dir) {
+                missing.push(dir.to_path_buf());
+                current = dir.parent();
+            }
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}\n{}", relative, e))?;
+            created.extend(missing.into_iter().rev());
+        }
+
+        let backup = if target.exists() {
+            if target.is_dir() {
+                return Err(format!(
+                    "目标路径是已存在的目录，无法还原为文件: {}",
+                    relative
+                ));
+            }
+            let backup = backup_dir.join(relative);
+            backup
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| move_file(&target, &backup))
+                .map_err(|e| format!("备份当前文件失败: {}\n{}", relative, e))?;
+            Some(backup)
+        } else {
+            None
+        };
+        let moved = move_file(staged_path, &target);
+        applied.push(RestoredFile {
+            target,
+            backup,
+            placed: true,
+        });
+        moved.map_err(|e| format!("还原文件失败: {}\n{}", relative, e))?;
+    }
+    Ok(())
+}
+
+/// 把游戏目录恢复到快照时的状态：还原变化的文件，删除之后新增的文件和目录
+/// 先把需要还原的文件全部取到暂存区，再替换游戏目录中的文件；中途出错时恢复所有已替换或删除的文件
+fn restore_snapshot(
+    config_dir: &Path,
+    game_dir: &Path,
+    manifest: &GameSnapshotManifest,
+) -> Result<GameSnapshotRestoreResult, String> {
+    // 先确认快照仓库完整，避免还原到一半才发现文件缺失
+    if let Some(missing) = manifest
+        .files
+        .iter()
+        .find(|file| !get_object_path(config_dir, &file.hash).is_file())
+    {
+        return Err(format!("快照仓库中缺少文件，无法恢复: {}", missing.path));
+    }
+
+    let scanned =
+        scan_game_dir(game_dir, &[config_dir]).map_err(|e| format!("扫描游戏目录失败: {}", e))?;
+    // Windows 下文件名不区分大小写，按小写比较
+    let current: HashMap<String, &(PathBuf, u64, u64)> = scanned
+        .files
+        .iter()
+        .map(|(relative, entry)| (relative.to_lowercase(), entry))
+        .collect();
+    let expected: HashSet<String> = manifest
+        .files
+        .iter()
+        .map(|file| file.path.to_lowercase())
+        .collect();
+
+    let mut result = GameSnapshotRestoreResult {
+        snapshot_id: manifest.id.clone(),
+        restored_files: Vec::new(),
+        removed_files: Vec::new(),
+        removed_directories: Vec::new(),
+        unchanged_files: 0,
+    };
+
+    let removed: Vec<String> = scanned
+        .files
+        .keys()
+        .filter(|relative| !expected.contains(&relative.to_lowercase()))
+        .cloned()
+        .collect();
+    let mut changed = Vec::new();
+    for file in &manifest.files {
+        let unchanged = current
+            .get(&file.path.to_lowercase())
+            .is_some_and(|entry| file_matches(file, entry));
+        if unchanged {
+            result.unchanged_files += 1;
+        } else {
+            changed.push(file);
+        }
+    }
+
+    let restore_dir = get_restore_dir(config_dir, manifest.game_id);
+    let staging_dir = restore_dir.join("staging");
+    let backup_dir = restore_dir.join("backup");
+    let _ = fs::remove_dir_all(&restore_dir);
+
+    // 第一阶段：从仓库取出文件到暂存区，此时游戏目录不受影响
+    let mut staged = Vec::with_capacity(changed.len());
+    for file in changed {
+        let staged_path = staging_dir.join(&file.path);
+        if let Err(e) = stage_object(config_dir, file, &staged_path) {
+            let _ = fs::remove_dir_all(&restore_dir);
+            return Err(format!("暂存文件失败: {}\n{}", file.path, e));
+        }
+        staged.push((staged_path, file.path.clone()));
+    }
+
+    // 第二阶段：备份并删除多余文件，把暂存的文件移到游戏目录
+    let mut applied = Vec::new();
+    let mut created = Vec::new();
+    if let Err(e) = apply_restore(
+        game_dir,
+        &backup_dir,
+        &removed,
+        &staged,
+        &mut applied,
+        &mut created,
+    ) {
+        let rollback_errors = rollback_restore(&applied, &created);
+        let _ = fs::remove_dir_all(&restore_dir);
+        return if rollback_errors.is_empty() {
+            Err(format!("{}\n已回滚所有更改", e))
+        } else {
+            Err(format!(
+                "{}\n回滚时出现错误，请检查以下路径:\n{}",
+                e,
+                rollback_errors.join("\n")
+            ))
+        };
+    }
+    let _ = fs::remove_dir_all(&restore_dir);
+    result.removed_files = removed;
+    result.restored_files = staged.into_iter().map(|(_, relative)| relative).collect();
+
+    let expected_dirs: HashSet<String> = manifest
+        .directories
+        .iter()
+        .map(|dir| dir.to_lowercase())
+        .collect();
+    for dir in &manifest.directories {
+        let _ = fs::create_dir_all(game_dir.join(dir));
+    }
+    // 从最深的目录开始删除
+    for dir in scanned.directories.iter().rev() {
+        if !expected_dirs.contains(&dir.to_lowercase())
+            && fs::remove_dir(game_dir.join(dir)).is_ok()
+        {
+            result.removed_directories.push(dir.clone());
+        }
+    }
+
+    Ok(result)
+}
+
+/// 删除不再被任何快照引用的文件
+fn collect_unused_objects(config_dir: &Path) {
+    let root = get_snapshot_root(config_dir);
+    let mut used = HashSet::new();
+    for game_dir in fs::read_dir(root.join("manifests"))
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        for manifest in fs::read_dir(game_dir.path())
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let manifest = fs::read_to_string(manifest.path())
+                .ok()
+                .and_then(|content| serde_json::from_str::<GameSnapshotManifest>(&content).ok());
+            if let Some(manifest) = manifest {
+                used.extend(manifest.files.into_iter().map(|file| file.hash));
+            }
+        }
+    }
+
+    for prefix in fs::read_dir(root.join("objects"))
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        for object in fs::read_dir(prefix.path()).into_iter().flatten().flatten() {
+            if !used.contains(&*object.file_name().to_string_lossy()) {
+                let _ = fs::remove_file(object.path());
+            }
+        }
+        let _ = fs::remove_dir(prefix.path());
+    }
+}
+
+/// 删除游戏的全部快照（删除游戏时调用）
+pub fn remove_game_snapshots(config_dir: &Path, game_id: u32) {
+    let dir = get_manifest_dir(config_dir, game_id);
+    if dir.exists() {
+        let _ = fs::remove_dir_all(&dir);
+        collect_unused_objects(config_dir);
+    }
+}
+
+/// 创建游戏目录快照（Tauri 命令）
+#[tauri::command]
+pub async fn create_game_snapshot(
+    id: u32,
+    name: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<ApiResponse<GameSnapshotInfo>, String> {
+    let config_dir = match get_config_dir(&app_handle) {
+        Ok(dir) => dir,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let mut game_list = match load_game_list(&config_dir) {
+        Ok(list) => list,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let Some(game) = game_list.games.iter().find(|g| g.id == id).cloned() else {
+        return Ok(ApiResponse::error("未找到指定的游戏".to_string()));
+    };
+    if !Path::new(&game.dir).is_dir() {
+        return Ok(ApiResponse::error("游戏目录不存在".to_string()));
+    }
+
+    let name = name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("快照 {}", Local::now().format("%Y-%m-%d %H:%M:%S")));
+    let snapshot_config_dir = config_dir.clone();
+    let info =
+        match tokio::task::spawn_blocking(move || take_snapshot(&snapshot_config_dir, &game, name))
+            .await
+        {
+            Ok(Ok((_, info))) => info,
+            Ok(Err(e)) => return Ok(ApiResponse::error(format!("创建快照失败: {}", e))),
+            Err(e) => return Ok(ApiResponse::error(format!("创建快照失败: {}", e))),
+        };
+
+    if let Some(game) = game_list.games.iter_mut().find(|g| g.id == id) {
+        game.snapshots.push(info.clone());
+    }
+    if let Err(e) = save_game_list(&config_dir, &game_list) {
+        return Ok(ApiResponse::error(e));
+    }
+    Ok(ApiResponse::success(info))
+}
+
+/// 把游戏目录恢复到快照时的状态（Tauri 命令）
+#[tauri::command]
+pub async fn restore_game_snapshot(
+    id: u32,
+    snapshot_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<ApiResponse<GameSnapshotRestoreResult>, String> {
+    let config_dir = match get_config_dir(&app_handle) {
+        Ok(dir) => dir,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let game_list = match load_game_list(&config_dir) {
+        Ok(list) => list,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let Some(game) = game_list.games.iter().find(|g| g.id == id) else {
+        return Ok(ApiResponse::error("未找到指定的游戏".to_string()));
+    };
+    let Some(manifest) = load_manifest(&config_dir, id, &snapshot_id) else {
+        return Ok(ApiResponse::error("未找到指定的快照".to_string()));
+    };
+    let game_dir = PathBuf::from(&game.dir);
+    if !game_dir.is_dir() {
+        return Ok(ApiResponse::error("游戏目录不存在".to_string()));
+    }
+    let lock = match GameDirLock::acquire(&game_dir, "恢复快照") {
+        Ok(lock) => lock,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+
+    match tokio::task::spawn_blocking(move || {
+        let _lock = lock;
+        restore_snapshot(&config_dir, &game_dir, &manifest)
+    })
+    .await
+    {
+        Ok(Ok(result)) => Ok(ApiResponse::success(result)),
+        Ok(Err(e)) => Ok(ApiResponse::error(format!("恢复快照失败: {}", e))),
+        Err(e) => Ok(ApiResponse::error(format!("恢复快照失败: {}", e))),
+    }
+}
+
+/// 删除快照，并清理不再被引用的文件（Tauri 命令）
+#[tauri::command]
+pub async fn delete_game_snapshot(
+    id: u32,
+    snapshot_id: String,
+    app_handle: tauri::AppHandle,
+) -> Result<ApiResponse<()>, String> {
+    let config_dir = match get_config_dir(&app_handle) {
+        Ok(dir) => dir,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let mut game_list = match load_game_list(&config_dir) {
+        Ok(list) => list,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let Some(game) = game_list.games.iter_mut().find(|g| g.id == id) else {
+        return Ok(ApiResponse::error("未找到指定的游戏".to_string()));
+    };
+    let initial_len = game.snapshots.len();
+    game.snapshots.retain(|s| s.id != snapshot_id);
+    if game.snapshots.len() == initial_len {
+        return Ok(ApiResponse::error("未找到指定的快照".to_string()));
+    }
+    if let Err(e) = save_game_list(&config_dir, &game_list) {
+        return Ok(ApiResponse::error(e));
+    }
+
+    let _ = fs::remove_file(get_manifest_path(&config_dir, id, &snapshot_id));
+    let _ = tokio::task::spawn_blocking(move || collect_unused_objects(&config_dir)).await;
+    Ok(ApiResponse::success(()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_size_and_mtime_is_not_enough() {
+        let dir = std::env::temp_dir().join(format!("g2m-snapshot-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gta3.img");
+        fs::write(&path, b"original").unwrap();
+        let metadata = fs::metadata(&path).unwrap();
+        let file = GameSnapshotFile {
+            path: "gta3.img".to_string(),
+            size: metadata.len(),
+            hash: hash_file(&path).unwrap(),
+            modified: modified_millis(&metadata),
+        };
+        assert!(file_matches(
+            &file,
+            &(path.clone(), file.size, file.modified)
+        ));
+
+        // 内容不同但大小相同，并保留原修改时间
+        fs::write(&path, b"replaced").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(metadata.modified().unwrap()))
+            .unwrap();
+        let current = fs::metadata(&path).unwrap();
+        assert_eq!(modified_millis(&current), file.modified);
+        assert!(!file_matches(
+            &file,
+            &(path.clone(), current.len(), modified_millis(&current))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 在 config_dir 中存入文件内容，返回清单中的记录
+    fn snapshot_file(config_dir: &Path, path: &str, content: &[u8]) -> GameSnapshotFile {
+        let source = config_dir.join("source");
+        fs::write(&source, content).unwrap();
+        let hash = hash_file(&source).unwrap();
+        store_object(config_dir, &hash, &source).unwrap();
+        fs::remove_file(&source).unwrap();
+        GameSnapshotFile {
+            path: path.to_string(),
+            size: content.len() as u64,
+            hash,
+            modified: 0,
+        }
+    }
+
+    fn restore_dirs(name: &str) -> (PathBuf, PathBuf, GameSnapshotManifest) {
+        let root = std::env::temp_dir().join(format!("g2m-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let game_dir = root.join("game");
+        let config_dir = root.join("config");
+        fs::create_dir_all(game_dir.join("data")).unwrap();
+        fs::create_dir_all(&config_dir).unwrap();
+        let manifest = GameSnapshotManifest {
+            id: "1".to_string(),
+            game_id: 1,
+            name: "快照".to_string(),
+            time: "1".to_string(),
+            files: vec![
+                snapshot_file(&config_dir, "data/handling.cfg", b"original handling"),
+                snapshot_file(&config_dir, "gta_sa.exe", b"original exe"),
+            ],
+            directories: vec!["data".to_string()],
+        };
+        (game_dir, config_dir, manifest)
+    }
+
+    #[test]
+    fn restore_replaces_changed_and_removes_new_files() {
+        let (game_dir, config_dir, manifest) = restore_dirs("snapshot-restore");
+        fs::write(game_dir.join("data/handling.cfg"), b"modded handling").unwrap();
+        fs::write(game_dir.join("gta_sa.exe"), b"original exe").unwrap();
+        fs::create_dir_all(game_dir.join("modloader")).unwrap();
+        fs::write(game_dir.join("modloader/mod.txt"), b"mod").unwrap();
+
+        let result = restore_snapshot(&config_dir, &game_dir, &manifest).unwrap();
+        assert_eq!(result.restored_files, vec!["data/handling.cfg"]);
+        assert_eq!(result.removed_files, vec!["modloader/mod.txt"]);
+        assert_eq!(result.removed_directories, vec!["modloader"]);
+        assert_eq!(result.unchanged_files, 1);
+        assert_eq!(
+            fs::read(game_dir.join("data/handling.cfg")).unwrap(),
+            b"original handling"
+        );
+        assert!(!game_dir.join("modloader").exists());
+        assert!(!get_restore_dir(&config_dir, 1).exists());
+        fs::remove_dir_all(game_dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn failed_restore_rolls_back_files() {
+        let (game_dir, config_dir, manifest) = restore_dirs("snapshot-rollback");
+        fs::write(game_dir.join("data/handling.cfg"), b"modded handling").unwrap();
+        fs::write(game_dir.join("extra.asi"), b"plugin").unwrap();
+        // 快照中的文件在游戏目录中是目录，还原到这里时失败
+        fs::create_dir_all(game_dir.join("gta_sa.exe")).unwrap();
+
+        let error = restore_snapshot(&config_dir, &game_dir, &manifest).unwrap_err();
+        assert!(error.contains("已回滚所有更改"), "{}", error);
+        assert_eq!(
+            fs::read(game_dir.join("data/handling.cfg")).unwrap(),
+            b"modded handling"
+        );
+        assert_eq!(fs::read(game_dir.join("extra.asi")).unwrap(), b"plugin");
+        assert!(!get_restore_dir(&config_dir, 1).exists());
+        fs::remove_dir_all(game_dir.parent().unwrap()).unwrap();
+    }
+}
@@ -1,4 +1,6 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GameInfo {
@@ -10,10 +12,113 @@ pub struct GameInfo {
     pub img: Option<String>,
     #[serde(alias = "game_type")]
     pub r#type: Option<String>,
-    pub version: Option<String>, // 游戏版本：1.0, 1.1, steam, diy等
+    pub version: Option<String>, // 游戏版本：1.0, 1.01, steam, compact等（根据版本数据库识别）
     pub md5: Option<String>, // 主程序文件的MD5值
     #[serde(default)]
+    pub sha256: Option<String>, // 主程序文件的SHA-256值
+    #[serde(default)]
+    pub loader_compatible: Option<bool>, // 主程序是否可以使用 CLEO 和 ModLoader，未识别时为空
+    #[serde(default)]
     pub deleted: bool, // 软删除标记，默认为false
+    #[serde(default)]
+    pub snapshots: Vec<GameSnapshotInfo>, // 游戏目录快照（按创建时间排序）
+}
+
+// 游戏目录快照摘要（记录在 GameList.json 中）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameSnapshotInfo {
+    pub id: String,      // 快照ID（创建时间的毫秒时间戳）
+    pub name: String,    // 快照名称
+    pub time: String,    // 创建时间（毫秒时间戳）
+    pub file_count: usize, // 文件数量
+    pub total_size: u64, // 文件总大小（字节）
+    pub stored_size: u64, // 本次新存入快照仓库的大小（字节），未变化的文件不重复存储
+}
+
+// 快照清单中的单个文件
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameSnapshotFile {
+    pub path: String, // 相对游戏目录的路径（使用 /）
+    pub size: u64,    // 文件大小（字节）
+    pub hash: String, // 文件内容的 SHA-256
+    #[serde(default)]
+    pub modified: u64, // 修改时间（毫秒时间戳），用于下次快照时跳过未变化的文件
+}
+
+// 快照清单（Snapshots/manifests/<游戏ID>/<快照ID>.json）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameSnapshotManifest {
+    pub id: String,
+    pub game_id: u32,
+    pub name: String,
+    pub time: String,
+    pub files: Vec<GameSnapshotFile>,
+    #[serde(default)]
+    pub directories: Vec<String>, // 所有目录（含空目录），恢复时用于删除之后新增的目录
+}
+
+// 原版文件基线（.gtamodx/baseline.json），首次添加游戏时记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameBaseline {
+    pub time: String,                 // 记录时间（毫秒时间戳）
+    pub files: Vec<GameSnapshotFile>, // 原版文件（不含 .gtamodx）
+}
+
+// 相对原版基线的文件变化
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct GameFileChanges {
+    pub modified: Vec<String>, // 内容与原版不同的文件
+    pub missing: Vec<String>,  // 原版中有、现在缺失的文件
+    pub added: Vec<String>,    // 原版中没有的文件
+}
+
+// 由某个已安装 MOD 管理的文件变化
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModFileChanges {
+    pub mod_id: u32,
+    pub mod_name: String,
+    pub changes: GameFileChanges,
+}
+
+// 校验游戏文件的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameFileVerification {
+    pub baseline_time: String,       // 基线记录时间
+    pub checked_files: usize,        // 基线中的文件数量
+    pub unchanged_files: usize,      // 与原版一致的文件数量
+    pub mods: Vec<ModFileChanges>,   // 已知 MOD 管理的文件变化（按 MOD 分组）
+    pub unknown: GameFileChanges,    // 不属于任何已知 MOD 的文件变化
+}
+
+// 已还原的原版文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoredVanillaFile {
+    pub path: String,   // 相对游戏目录的路径
+    pub source: String, // 来源说明，如 "MOD 备份 (ID 3)"、"快照仓库"、下载包路径
+}
+
+// 未能还原的文件及原因
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkippedVanillaFile {
+    pub path: String,
+    pub reason: String,
+}
+
+// 还原原版文件的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VanillaRestoreResult {
+    pub restored_files: Vec<RestoredVanillaFile>,
+    pub skipped_files: Vec<SkippedVanillaFile>,
+}
+
+// 恢复快照的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameSnapshotRestoreResult {
+    pub snapshot_id: String,
+    pub restored_files: Vec<String>, // 被还原或重新创建的文件
+    pub removed_files: Vec<String>,  // 快照之后新增、已删除的文件
+    pub removed_directories: Vec<String>, // 快照之后新增、已删除的目录
+    pub unchanged_files: usize,      // 未变化的文件数量
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -28,11 +133,52 @@ pub struct GameDetectionResult {
     pub r#type: Option<String>,
     pub executable: Option<String>,
     pub game_name: Option<String>,
-    pub version: Option<String>, // 游戏版本：1.0, 1.1, steam, diy等
+    pub version: Option<String>, // 游戏版本：1.0, 1.01, steam, compact等（根据版本数据库识别）
     pub md5: Option<String>, // 主程序文件的MD5值
+    #[serde(default)]
+    pub sha256: Option<String>, // 主程序文件的SHA-256值
+    #[serde(default)]
+    pub loader_compatible: Option<bool>, // 主程序是否可以使用 CLEO 和 ModLoader，未识别时为空
     pub error: Option<String>,
 }
 
+// 游戏描述：一种游戏类型的主程序、目录和前置组件（内置 III/VC/SA，可由 G2M/Data/games.json 添加）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GameDescriptor {
+    pub id: String,               // 游戏类型ID，如 gtasa
+    pub name: String,             // 显示名称，如 GTA San Andreas
+    pub full_name: String,        // 完整名称，如 Grand Theft Auto: San Andreas
+    pub engine: String,           // 基于的游戏（gta3、gtavc 或 gtasa），资源、CLEO 和版本检查按此进行
+    pub executables: Vec<String>, // 主程序文件名，第一个为默认主程序
+    #[serde(default = "default_cleo_dir")]
+    pub cleo_dir: String, // CLEO 目录名（SA 为小写 cleo）
+    #[serde(default)]
+    pub data_file: Option<String>, // 主配置文件，如 data/gta.dat
+    #[serde(default)]
+    pub user_files_dir: Option<String>, // 文档中的用户文件目录名，如 GTA San Andreas User Files
+    #[serde(default)]
+    pub download_name: Option<String>, // 下载包和解压目录的名称，不提供下载时为空
+    #[serde(default)]
+    pub prerequisites: Vec<String>, // 未指定组件时默认安装的前置组件，如 dinput8、cleo
+    #[serde(default)]
+    pub cleo: Option<BundledCleo>, // 随程序提供的 CLEO，不支持时为空
+}
+
+fn default_cleo_dir() -> String {
+    "CLEO".to_string()
+}
+
+// 随程序提供的 CLEO（位于 G2M/Module 下）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BundledCleo {
+    pub module: String, // G2M/Module 下的目录名，如 CLEO.SA_v4.44
+    pub asi: String,    // 安装到插件目录的 ASI 文件
+    #[serde(default)]
+    pub files: Vec<String>, // 复制到游戏根目录的文件
+    #[serde(default)]
+    pub dirs: Vec<String>, // 复制到游戏根目录的目录
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
@@ -72,6 +218,10 @@ pub struct ModLoaderStatus {
     pub missing_loaders: Vec<String>,
     pub found_loaders: Vec<String>,
     pub manual_bindings: Vec<String>, // 手动绑定的加载器类型列表，如 ["cleo", "dinput8"]
+    #[serde(default)]
+    pub loader_versions: BTreeMap<String, String>, // 从文件版本信息读取的加载器版本，如 {"cleo": "4.4.4.0"}
+    #[serde(default)]
+    pub loader_files: BTreeMap<String, String>, // 检测到的加载器文件（相对游戏目录），如 {"cleo": "CLEO.asi"}
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -122,22 +272,139 @@ pub struct ManualLoaderBinding {
 }
 
 // g2m.json 配置文件结构（MOD配置文件）
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct G2MModConfig {
+    #[serde(default)]
+    pub schema_version: u32,                 // 配置格式版本（旧配置没有此字段，视为 0）
     pub name: String,                        // MOD名称
     #[serde(default)]
     pub author: Option<String>,              // 作者信息（可选）
-    pub modfile: Vec<ModFileEntry>,          // 文件/文件夹列表
+    #[serde(default)]
+    pub version: Option<String>,             // MOD 版本（可选，更新时记录到 mods.json）
+    #[serde(default)]
+    pub modfile: Vec<ModFileEntry>,          // 文件/文件夹列表（总是安装）
+    #[serde(default)]
+    pub dat_entries: Vec<DatEntry>,          // 需要写入 data/*.dat 的行（如 IDE、IPL、IMG）
+    #[serde(default)]
+    pub options: Vec<ModOptionGroup>,        // 可选组件分组（安装时由用户选择）
+    #[serde(default)]
+    pub requires: Vec<ModRequirement>,       // 依赖的 MOD 或加载器
+    #[serde(default)]
+    pub conflicts_with: Vec<String>,         // 不能同时安装的 MOD 名称
+    #[serde(default)]
+    pub game_types: Vec<String>,             // 支持的游戏类型（gta3、gtavc、gtasa），为空表示不限
 }
 
-// MOD文件/文件夹安装配置
+// g2m.json 中的依赖声明：依赖另一个 MOD，或依赖某个加载器（可指定最低版本）
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct ModRequirement {
+    #[serde(default, rename = "mod")]
+    pub mod_name: Option<String>,            // 依赖的 MOD 名称（与 mods.json 中的名称匹配）
+    #[serde(default)]
+    pub loader: Option<String>,              // 依赖的加载器：cleo、cleo_redux、modloader、dinput8
+    #[serde(default)]
+    pub min_version: Option<String>,         // 加载器最低版本，如 "4.4"
+}
+
+// g2m.json 中的可选组件分组
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct ModOptionGroup {
+    pub id: String,                          // 分组标识（记录到 mods.json 中）
+    pub name: String,                        // 显示名称
+    #[serde(default)]
+    pub description: Option<String>,         // 说明
+    pub r#type: ModOptionGroupType,          // 单选或多选
+    #[serde(default)]
+    pub conditions: ModOptionConditions,     // 显示该分组的条件
+    pub options: Vec<ModOption>,             // 组件列表
+}
+
+// 可选组件分组的选择方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModOptionGroupType {
+    SelectOne,                               // 必须且只能选择一个
+    SelectMany,                              // 可以选择任意个
+}
+
+// 单个可选组件
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+pub struct ModOption {
+    pub id: String,                          // 组件标识（在分组内唯一）
+    pub name: String,                        // 显示名称
+    #[serde(default)]
+    pub description: Option<String>,         // 说明
+    #[serde(default)]
+    pub default: bool,                       // 是否默认选中
+    #[serde(default)]
+    pub conditions: ModOptionConditions,     // 可以选择该组件的条件
+    #[serde(default)]
+    pub modfile: Vec<ModFileEntry>,          // 选中时安装的文件/文件夹
+}
+
+// 可选组件的条件，所有条件都满足时才可选择
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
+pub struct ModOptionConditions {
+    #[serde(default)]
+    pub game_types: Vec<String>,             // 限定的游戏类型，为空表示不限
+    #[serde(default)]
+    pub loaders: Vec<String>,                // 需要已安装的加载器（cleo、cleo_redux、modloader、dinput8）
+}
+
+// 返回给界面的可选组件分组（已按当前游戏计算可用性）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModOptionGroupState {
+    pub id: String,                          // 分组标识
+    pub name: String,                        // 显示名称
+    pub description: Option<String>,         // 说明
+    pub r#type: ModOptionGroupType,          // 单选或多选
+    pub available: bool,                     // 分组条件是否满足（不满足时不安装其中任何组件）
+    pub options: Vec<ModOptionState>,        // 组件列表
+}
+
+// 返回给界面的可选组件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModOptionState {
+    pub id: String,                          // 组件标识
+    pub name: String,                        // 显示名称
+    pub description: Option<String>,         // 说明
+    pub available: bool,                     // 条件是否满足
+    pub unavailable_reason: Option<String>,  // 不可选择的原因
+    pub selected: bool,                      // 默认是否选中
+}
+
+// MOD 的可选组件列表
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModInstallOptions {
+    pub mod_name: String,                    // MOD名称
+    pub groups: Vec<ModOptionGroupState>,    // 可选组件分组，没有 g2m.json 或未声明时为空
+}
+
+// 安装前检查依赖声明发现的问题
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RequirementIssue {
+    pub kind: String,                        // 问题类型：game_type、missing_mod、missing_loader、loader_version、conflicting_mod
+    pub message: String,                     // 问题说明
+    pub blocking: bool,                      // 是否阻止安装（否则仅为警告）
+}
+
+// MOD文件/文件夹安装配置
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 pub struct ModFileEntry {
     pub source: String,                      // 源路径（相对于MOD根目录）
-    pub target: String,                      // 目标路径（相对于游戏目录，如 "CLEO/script.cs" 或 "modloader/models"）
+    pub target: String,                      // 目标路径（相对于游戏目录，如 "CLEO/script.cs" 或 "modloader/models"；写入 IMG 档案时为 "img://models/gta3.img/infernus.dff"）
     pub is_directory: bool,                  // 是否为目录
 }
 
+// data/*.dat（gta.dat、default.dat 等）中的一行，如 IDE DATA\MAPS\foo.ide
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+pub struct DatEntry {
+    #[serde(default)]
+    pub file: Option<String>,                // 所在文件（相对游戏目录，如 "data/default.dat"），为空时使用游戏的主配置文件
+    pub keyword: String,                     // 类型关键字，如 IDE、IPL、IMG、COLFILE
+    pub path: String,                        // 关键字后的参数（通常为相对游戏目录的文件路径）
+}
+
 // .gtamodx/info.json 文件结构（游戏信息）
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct G2MGameInfo {
@@ -154,6 +421,47 @@ pub struct G2MGameInfo {
 pub struct G2MModsList {
     #[serde(default)]
     pub mods: Vec<G2MModInfo>,
+    #[serde(default)]
+    pub next_id: u32, // 下一个可分配的 MOD ID，只增不减，卸载后的 ID 不会再分配给新 MOD
+}
+
+// 游戏目录下的 MOD 列表及当前配置方案
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameModList {
+    pub mods: Vec<G2MModInfo>,               // 已安装的 MOD
+    pub active_profile: Option<String>,      // 当前使用的配置方案名称
+}
+
+// MOD 配置方案（.gtamodx/profiles/<名称>.json）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModProfile {
+    pub name: String,                        // 方案名称
+    #[serde(default)]
+    pub enabled_mods: Vec<u32>,              // 启用的 MOD ID，其余 MOD 切换时禁用
+    #[serde(default)]
+    pub loaders: Vec<String>,                // 存在的加载器（cleo、cleo_redux、modloader、dinput8）
+    #[serde(default)]
+    pub time: String,                        // 最后保存时间（毫秒时间戳）
+}
+
+// 游戏的全部配置方案
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModProfileList {
+    pub active: Option<String>,              // 当前使用的方案名称
+    pub profiles: Vec<ModProfile>,           // 所有方案
+}
+
+// 切换配置方案的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModProfileSwitchResult {
+    pub profile: String,                     // 切换到的方案名称
+    pub enabled_mods: Vec<u32>,              // 本次启用的 MOD ID
+    pub disabled_mods: Vec<u32>,             // 本次禁用的 MOD ID
+    pub enabled_loaders: Vec<String>,        // 本次恢复的加载器
+    pub disabled_loaders: Vec<String>,       // 本次停用的加载器
+    pub missing_mods: Vec<u32>,              // 方案中已不存在的 MOD ID
+    pub missing_loaders: Vec<String>,        // 方案需要但游戏目录中没有的加载器
+    pub skipped_mods: Vec<u32>,              // 没有安装清单、无法切换的 MOD ID
 }
 
 // 兼容旧版本的 g2m.json 文件结构（用于迁移）
@@ -177,10 +485,30 @@ pub struct G2MModInfo {
     pub name: String, // MOD名称
     #[serde(default)]
     pub author: Option<String>, // 作者信息（可选）
+    #[serde(default)]
+    pub version: Option<String>, // MOD 版本（来自 g2m.json）
     #[serde(default, alias = "category")]
     pub r#type: Option<String>, // 安装类型（cleo、modloader、asi 等）
     #[serde(default)]
     pub install_path: Option<String>, // 相对游戏目录的安装路径提示
+    #[serde(default)]
+    pub installed_files: Vec<String>, // 安装清单：本次安装写入的所有文件（相对游戏目录）
+    #[serde(default)]
+    pub created_directories: Vec<String>, // 安装清单：本次安装新建的所有目录（相对游戏目录）
+    #[serde(default)]
+    pub backed_up_files: Vec<String>, // 安装时被覆盖的原文件（备份在 .gtamodx/backup/<id>/ 下）
+    #[serde(default)]
+    pub dat_entries: Vec<DatEntry>, // 安装时声明的 .dat 行（file 已解析；写入的行带有该 MOD 的标记）
+    #[serde(default)]
+    pub text_changes: Vec<ModTextChange>, // 合并到 GXT/FXT 文本表的条目（卸载或禁用时恢复）
+    #[serde(default)]
+    pub modloader_priority: Option<u8>, // ModLoader 优先级（modloader 类型，来自 modloader.ini 当前方案）
+    #[serde(default)]
+    pub modloader_ignored: bool, // 是否被 modloader.ini 当前方案忽略
+    #[serde(default = "default_enabled")]
+    pub enabled: bool, // 是否启用（禁用时文件被移出生效位置）
+    #[serde(default)]
+    pub selected_options: BTreeMap<String, Vec<String>>, // 安装时选择的可选组件（分组标识 -> 组件标识）
 }
 
 // 为向后兼容生成默认ID（从1开始）
@@ -188,6 +516,11 @@ fn generate_default_id() -> u32 {
     1
 }
 
+// 旧记录没有 enabled 字段，视为已启用
+fn default_enabled() -> bool {
+    true
+}
+
 // 用户MOD安装请求
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UserModInstallRequest {
@@ -198,6 +531,10 @@ pub struct UserModInstallRequest {
     pub overwrite: bool,                     // 是否覆盖冲突文件/目录
     #[serde(default)]
     pub target_directory: Option<String>,    // 目标安装目录（相对游戏目录的路径，可选）
+    #[serde(default)]
+    pub ignore_requirements: bool,           // 是否忽略 g2m.json 依赖声明中阻止安装的问题
+    #[serde(default)]
+    pub selected_options: Option<BTreeMap<String, Vec<String>>>, // 选择的可选组件（分组标识 -> 组件标识），为空时使用默认选择
 }
 
 // 用户MOD安装结果
@@ -205,4 +542,338 @@ pub struct UserModInstallRequest {
 pub struct UserModInstallResult {
     pub installed_files: Vec<String>,        // 已安装的文件列表（相对游戏目录）
     pub created_directories: Vec<String>,    // 创建的目录列表（相对游戏目录）
-}
\ No newline at end of file
+    #[serde(default)]
+    pub backed_up_files: Vec<String>,        // 被覆盖并已备份的原文件列表（相对游戏目录）
+    #[serde(default)]
+    pub conflicts: Vec<FileConflict>,        // 安装前检测到的文件冲突
+    #[serde(default)]
+    pub requirement_issues: Vec<RequirementIssue>, // 依赖声明检查结果（已忽略的问题和警告）
+    #[serde(default)]
+    pub selected_options: BTreeMap<String, Vec<String>>, // 实际安装的可选组件
+    #[serde(default)]
+    pub model_collisions: Vec<ModelIdCollision>, // 新安装的 IDE 文件与已有定义重复的模型 ID
+    #[serde(default)]
+    pub asset_mismatches: Vec<AssetGameMismatch>, // 不适用于当前游戏的 TXD/DFF 文件
+    #[serde(default)]
+    pub missing_cleo_plugins: Vec<MissingCleoPlugin>, // CLEO 脚本需要但未安装的插件
+}
+
+// 用户MOD更新结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserModUpdateResult {
+    pub mod_id: u32,                         // MOD ID（更新后保持不变）
+    pub previous_version: Option<String>,    // 更新前的版本
+    pub version: Option<String>,             // 更新后的版本
+    pub added_files: Vec<String>,            // 新版本新增的文件（相对游戏目录）
+    pub updated_files: Vec<String>,          // 内容有变化、已替换的文件
+    pub unchanged_files: Vec<String>,        // 内容相同、未复制的文件
+    pub removed_files: Vec<String>,          // 新版本不再包含、已删除的文件
+    pub restored_files: Vec<String>,         // 删除旧文件后恢复的原文件
+    pub kept_files: Vec<String>,             // 已被其他 MOD 覆盖、保持不变的文件
+    pub selected_options: BTreeMap<String, Vec<String>>, // 沿用的可选组件选择
+    pub requirement_issues: Vec<RequirementIssue>, // 依赖声明检查结果（警告）
+}
+
+// 用户MOD卸载结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserModUninstallResult {
+    pub removed_files: Vec<String>,          // 已删除的文件列表（相对游戏目录）
+    pub removed_directories: Vec<String>,    // 已清理的空目录列表（相对游戏目录）
+    pub missing_files: Vec<String>,          // 清单中已不存在的文件
+    pub restored_files: Vec<String>,         // 已从备份恢复的原文件
+    #[serde(default)]
+    pub kept_files: Vec<String>,             // 仍被其他 MOD 占用而保留的文件
+}
+
+// MOD 安装计划（预览，不修改游戏目录）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserModInstallPlan {
+    pub mod_name: String,                    // MOD名称
+    pub mod_author: Option<String>,          // 作者信息（来自 g2m.json）
+    pub r#type: Option<String>,              // 安装类型（cleo、modloader、asi 等）
+    pub install_path: Option<String>,        // 安装路径提示（变量格式）
+    pub uses_config: bool,                   // 是否按 g2m.json 安装
+    pub copies: Vec<PlannedFileCopy>,        // 将要复制的文件
+    pub directories_to_create: Vec<String>,  // 将要新建的目录（相对游戏目录）
+    pub overwritten_files: Vec<String>,      // 将被覆盖的已存在文件（相对游戏目录）
+    pub conflicts: Vec<FileConflict>,        // 与其他 MOD 或原版文件的冲突
+    pub requires_overwrite: bool,            // 是否需要用户确认覆盖才能安装
+    pub requirement_issues: Vec<RequirementIssue>, // 依赖声明检查结果
+    pub selected_options: BTreeMap<String, Vec<String>>, // 将要安装的可选组件
+    pub dat_entries: Vec<DatEntry>,          // 将要写入 .dat 文件的行（file 已解析）
+    pub model_collisions: Vec<ModelIdCollision>, // 将要安装的 IDE 文件与已有定义重复的模型 ID
+    pub asset_mismatches: Vec<AssetGameMismatch>, // 不适用于当前游戏的 TXD/DFF 文件
+    pub missing_cleo_plugins: Vec<MissingCleoPlugin>, // CLEO 脚本需要但未安装的插件
+}
+
+// 安装计划中的单个文件复制
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlannedFileCopy {
+    pub source: String,                      // 源文件路径
+    pub target: String,                      // 目标路径（相对游戏目录）
+}
+
+// g2m.json 校验错误
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct G2MConfigValidationError {
+    pub path: String,                        // 出错位置的 JSON 路径（如 $.modfile[0].target）
+    pub message: String,                     // 错误说明
+}
+
+// g2m.json 校验结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct G2MConfigValidationResult {
+    pub valid: bool,                         // 配置是否可用
+    pub schema_version: Option<u32>,         // 配置文件中声明的版本（无法解析时为空）
+    pub migrated: bool,                      // 是否需要从旧版本迁移
+    pub errors: Vec<G2MConfigValidationError>, // 所有错误
+}
+
+// 安装目标与已有文件的冲突
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileConflict {
+    pub path: String,                        // 冲突文件（相对游戏目录）
+    pub owner_mod_id: Option<u32>,           // 当前占用该文件的 MOD ID
+    pub owner_mod_name: Option<String>,      // 当前占用该文件的 MOD 名称
+    pub is_vanilla: bool,                    // 是否为不属于任何 MOD 的原版文件
+}
+
+// .gtamodx/ownership.json 文件归属索引
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct FileOwnershipIndex {
+    #[serde(default)]
+    pub files: BTreeMap<String, Vec<u32>>,   // 小写相对路径 -> 按写入顺序排列的 MOD ID（最后一个为当前所有者）
+}
+
+// IMG 档案中的条目
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ImgEntryInfo {
+    pub name: String, // 条目名称，如 infernus.dff
+    pub offset: u64,  // 在 .img 中的偏移（字节，按 2048 字节扇区对齐）
+    pub size: u64,    // 占用大小（字节，按扇区计）
+}
+
+// IMG 档案信息
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImgArchiveInfo {
+    pub version: String,          // "v1"（III/VC，.dir + .img）或 "v2"（SA，VER2）
+    pub entry_count: usize,       // 条目数量
+    pub total_size: u64,          // .img 文件大小（字节）
+    pub free_size: u64,           // 未被条目使用的空间（字节），重建档案后回收
+    pub entries: Vec<ImgEntryInfo>,
+}
+
+// 向 IMG 档案导入文件的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImgImportResult {
+    pub added: Vec<String>,     // 新增的条目
+    pub replaced: Vec<String>,  // 被替换的条目
+    pub backed_up: Vec<String>, // 本次备份的原条目（.gtamodx/img_backup/）
+}
+
+// .dat 文件中的条目
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatFileEntry {
+    pub line: usize,                // 行号（从 1 开始）
+    pub keyword: String,            // 类型关键字
+    pub path: String,               // 关键字后的参数
+    pub owner_mod_id: Option<u32>,  // 最先写入该行的 MOD（手动添加或原版的行为空）
+    pub owner_mod_ids: Vec<u32>,    // 所有需要该行的 MOD，全部卸载后才删除该行
+}
+
+// .dat 文件的内容
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatFileInfo {
+    pub file: String,               // 文件路径（相对游戏目录）
+    pub entries: Vec<DatFileEntry>, // 所有条目（不含注释和空行）
+}
+
+// 添加或删除 .dat 条目的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatEditResult {
+    pub changed: Vec<DatEntry>,     // 实际添加或删除的行（file 已解析）
+    pub unchanged: Vec<DatEntry>,   // 已存在（添加时）或不存在（删除时）而未改动的行
+}
+
+// TXD 中的一张纹理
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RwTextureInfo {
+    pub name: String,               // 纹理名称
+    pub mask: Option<String>,       // Alpha 遮罩名称
+    pub width: u16,                 // 宽度（像素）
+    pub height: u16,                // 高度（像素）
+    pub depth: u8,                  // 位深
+    pub mip_count: u8,              // Mipmap 层数
+    pub raster_format: String,      // 纹理格式（DXT1、8888、PAL8 8888 等）
+}
+
+// DFF 中的一个帧
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RwFrameInfo {
+    pub name: Option<String>,       // 帧名称（没有节点名称插件时为空）
+    pub parent: Option<u32>,        // 父帧的序号（根帧为空）
+}
+
+// DFF 中的一个几何体
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RwGeometryInfo {
+    pub vertex_count: u32,          // 顶点数
+    pub triangle_count: u32,        // 三角形数
+    pub material_count: u32,        // 材质数
+}
+
+// TXD/DFF 文件的内容
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameAssetInfo {
+    pub kind: String,                    // "txd" 或 "dff"
+    pub rw_version: String,              // RenderWare 版本（如 3.6.0.3）
+    pub games: Vec<String>,              // 使用该版本的游戏类型（gta3、gtavc、gtasa）
+    pub textures: Vec<RwTextureInfo>,    // 纹理（仅 TXD）
+    pub frames: Vec<RwFrameInfo>,        // 帧（仅 DFF）
+    pub geometries: Vec<RwGeometryInfo>, // 几何体（仅 DFF）
+    pub atomic_count: u32,               // Atomic 数量（仅 DFF）
+}
+
+// 与当前游戏的 RenderWare 版本不符的 TXD/DFF 文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssetGameMismatch {
+    pub file: String,               // 目标路径（相对游戏目录）
+    pub rw_version: String,         // 文件的 RenderWare 版本
+    pub games: Vec<String>,         // 该版本适用的游戏类型
+}
+
+// 文本表中的一个条目
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextEntry {
+    pub key: String,                // 键名（SA 为 CRC32 的 8 位十六进制）
+    pub text: String,               // 文本
+}
+
+// 文本表中的一个表
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextTableInfo {
+    pub name: String,               // 表名（FXT 为 MAIN）
+    pub entries: Vec<TextEntry>,    // 条目
+}
+
+// GXT/FXT 文件的内容
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextTableDump {
+    pub format: String,             // gta3、gtavc、gtasa（8 位）、gtasa16 或 fxt
+    pub tables: Vec<TextTableInfo>, // 所有表
+}
+
+// 两个文本表之间不同的条目
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextEntryDiff {
+    pub table: String,              // 表名
+    pub key: String,                // 键名
+    pub old_text: Option<String>,   // 原文本（新增的条目为空）
+    pub new_text: Option<String>,   // 新文本（删除的条目为空）
+}
+
+// 两个文本表的比较结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextTableDiff {
+    pub added: Vec<TextEntryDiff>,   // 新增的条目
+    pub changed: Vec<TextEntryDiff>, // 内容不同的条目
+    pub removed: Vec<TextEntryDiff>, // 删除的条目
+}
+
+// MOD 合并到文本表的一个条目
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModTextChange {
+    pub file: String,               // 文本表文件（相对游戏目录）
+    pub table: String,              // 表名
+    pub key: String,                // 键名
+    pub text: String,               // MOD 写入的文本
+    pub previous: Option<String>,   // 合并前的文本（原来没有该条目时为空）
+}
+
+// 合并文本表的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TextMergeResult {
+    pub file: String,                // 文本表文件（相对游戏目录）
+    pub changed: Vec<TextEntryDiff>, // 已写入的条目
+    pub unchanged: usize,            // 与已安装文件相同、无需修改的条目数
+}
+
+// IDE 文件中的一条模型定义及其来源
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelDefinition {
+    pub id: u32,                    // 模型 ID
+    pub model: String,              // 模型名称
+    pub section: String,            // 所在分段（objs、cars、peds 等）
+    pub file: String,               // IDE 文件（相对游戏目录）
+    pub line: usize,                // 行号（从 1 开始）
+    pub mod_id: Option<u32>,        // 引入该文件的 MOD（原版文件为空）
+    pub mod_name: Option<String>,   // MOD 名称（不是由本工具安装的 modloader 子目录为目录名）
+}
+
+// 多处定义了同一个模型 ID
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelIdCollision {
+    pub id: u32,                             // 重复的模型 ID
+    pub definitions: Vec<ModelDefinition>,   // 所有定义
+    pub ipl_files: Vec<String>,              // 放置了该 ID 的 IPL 文件（相对游戏目录）
+}
+
+// 多处定义了同一个模型名称
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelNameCollision {
+    pub model: String,                       // 重复的模型名称
+    pub definitions: Vec<ModelDefinition>,   // 所有定义
+    pub ipl_files: Vec<String>,              // 按名称放置了该模型的文本 IPL 文件（相对游戏目录）
+}
+
+// 模型 ID 冲突检查结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelCollisionReport {
+    pub ide_files: Vec<String>,                      // 检查的 IDE 文件（相对游戏目录）
+    pub ipl_files: Vec<String>,                      // 检查的 IPL 文件（相对游戏目录）
+    pub definition_count: usize,                     // 模型定义总数
+    pub id_collisions: Vec<ModelIdCollision>,        // 重复的模型 ID
+    pub name_collisions: Vec<ModelNameCollision>,    // 重复的模型名称
+}
+
+// CLEO 脚本使用的一个插件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CleoPluginUsage {
+    pub plugin: String,             // 插件名称（如 IniFiles，对应 IniFiles.cleo）
+    pub opcodes: Vec<String>,       // 脚本中使用的该插件的指令（如 0AF0）
+}
+
+// CLEO 脚本的分析结果（按字节码推测）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CleoScriptInfo {
+    pub games: Vec<String>,              // 可能适用的游戏类型（gta3、gtavc、gtasa）
+    pub cleo_version: Option<String>,    // 需要的 CLEO 主版本（SA 为 4 或 5，III/VC 为 2），无法确定游戏时为空
+    pub plugins: Vec<CleoPluginUsage>,   // 需要的 CLEO 插件
+    pub text_file: Option<String>,       // 同名的 FXT 文本文件
+}
+
+// CLEO 脚本需要但游戏目录中没有的插件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MissingCleoPlugin {
+    pub file: String,               // 脚本的目标路径（相对游戏目录）
+    pub plugin: String,             // 缺少的插件名称
+    pub opcodes: Vec<String>,       // 脚本中使用的该插件的指令
+}
+
+// modloader 目录中的一个 MOD（按 modloader.ini 当前方案）
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModLoaderModEntry {
+    pub name: String,               // MOD 目录名
+    pub priority: u8,               // 优先级（0–100，默认 50，数值大的覆盖数值小的）
+    pub enabled: bool,              // ModLoader 是否加载该 MOD
+    pub ignored_by: Option<String>, // 不加载的原因（IgnoreAllMods、ExcludeAllMods、IgnoreMods、ExclusiveMods 或 Priority）
+    pub mod_id: Option<u32>,        // 对应的已安装 MOD（不是由本工具安装的为空）
+}
+
+// modloader.ini 的方案和 MOD 状态
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModLoaderState {
+    pub profile: String,                 // 当前方案
+    pub profiles: Vec<String>,           // 所有方案（modloader.ini 和 .profiles/*.ini 中定义的）
+    pub mods: Vec<ModLoaderModEntry>,    // modloader 目录中的 MOD（按名称排序）
+}
@@ -1,6 +1,7 @@
+use crate::game::descriptors::{find_game_descriptor_by_exe, game_descriptors};
 use crate::game::types::{G2MGameConfig, G2MGameInfo, G2MModInfo, G2MModsList};
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::Read;
 use std::path::{Path, PathBuf};
 
 #[cfg(target_os = "windows")]
@@ -13,49 +14,75 @@ use windows_sys::Win32::Storage::FileSystem::{
 
 /// 根据exe文件名识别游戏类型
 pub fn detect_game_type_from_exe(exe_name: &str) -> Option<String> {
-    match exe_name.to_lowercase().as_str() {
-        "gta3.exe" => Some("gta3".to_string()),
-        "gta-vc.exe" => Some("gtavc".to_string()),
-        "gtasa.exe" | "gta-sa.exe" | "gta_sa.exe" => Some("gtasa".to_string()),
-        _ => None,
-    }
+    find_game_descriptor_by_exe(exe_name).map(|descriptor| descriptor.id.clone())
 }
 
-/// 计算文件的MD5值
-pub fn calculate_file_md5(file_path: &Path) -> Result<String, String> {
-    let mut file = std::fs::File::open(file_path).map_err(|e| format!("无法打开文件: {}", e))?;
+/// 读取 PE 文件（exe/dll/asi）版本资源中的文件版本，如 "4.4.4.0"
+/// 直接查找 VS_FIXEDFILEINFO 的签名，没有版本资源时返回 None
+pub fn read_pe_file_version(file_path: &Path) -> Option<String> {
+    const SIGNATURE: [u8; 4] = 0xFEEF04BDu32.to_le_bytes();
 
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)
-        .map_err(|e| format!("读取文件失败: {}", e))?;
+    let data = fs::read(file_path).ok()?;
+    if !data.starts_with(b"MZ") {
+        return None;
+    }
 
-    let digest = md5::compute(&buffer);
-    Ok(format!("{:x}", digest))
+    // 签名之后依次是 dwStrucVersion、dwFileVersionMS、dwFileVersionLS
+    let offset = data.windows(4).position(|w| w == SIGNATURE)?;
+    let read_u32 = |at: usize| -> Option<u32> {
+        data.get(at..at + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let ms = read_u32(offset + 8)?;
+    let ls = read_u32(offset + 12)?;
+
+    Some(format!(
+        "{}.{}.{}.{}",
+        ms >> 16,
+        ms & 0xFFFF,
+        ls >> 16,
+        ls & 0xFFFF
+    ))
 }
 
-/// 游戏版本数据库：MD5到版本的映射
-/// TODO: 等获取到真实MD5值后再启用版本识别功能
-/// 当前暂时返回None，保留MD5计算功能以便后续扩展
-pub fn get_game_version_from_md5(_game_type: &str, _md5: &str) -> Option<String> {
-    // 暂时不进行版本识别，等获取到真实MD5值后再实现
-    // 可以在这里添加MD5到版本的映射关系
-    //
-    // 示例：
-    // let md5_lower = md5.to_lowercase();
-    // if game_type == "gta3" {
-    //     match md5_lower.as_str() {
-    //         "真实的MD5值1" => Some("1.0".to_string()),
-    //         "真实的MD5值2" => Some("1.1".to_string()),
-    //         "真实的MD5值3" => Some("steam".to_string()),
-    //         _ => Some("diy".to_string()),
-    //     }
-    // }
+/// 获取随程序提供的 G2M 资源目录（G2M/Module、G2M/Data 等）
+/// 开发环境中位于项目的 src-tauri/G2M 下，发布后位于程序所在目录的 G2M 下
+pub fn get_bundled_dir(name: &str) -> Result<PathBuf, String> {
+    let g2m_dir = if cfg!(debug_assertions) {
+        // 在开发环境中，使用项目根目录
+        let current_dir =
+            std::env::current_dir().map_err(|e| format!("获取当前目录失败: {}", e))?;
+
+        // 如果当前目录是 src-tauri，则向上找到项目根目录
+        let project_root = if current_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|name| name == "src-tauri")
+            .unwrap_or(false)
+        {
+            current_dir
+                .parent()
+                .ok_or("无法获取项目根目录")?
+                .to_path_buf()
+        } else {
+            current_dir
+        };
 
-    None
+        project_root.join("src-tauri").join("G2M")
+    } else {
+        // 在生产环境中，使用exe文件所在目录
+        let exe_dir = std::env::current_exe()
+            .map_err(|e| format!("获取程序路径失败: {}", e))?
+            .parent()
+            .ok_or("无法获取程序目录")?
+            .to_path_buf();
+        exe_dir.join("G2M")
+    };
+    Ok(g2m_dir.join(name))
 }
 
 /// 获取 .gtamodx 目录路径
-fn get_g2m_dir_path(game_dir: &str) -> PathBuf {
+pub(crate) fn get_g2m_dir_path(game_dir: &str) -> PathBuf {
     Path::new(game_dir).join(".gtamodx")
 }
 
@@ -133,9 +160,7 @@ fn migrate_old_g2m_json(game_dir: &str) -> Option<G2MGameConfig> {
     }
 
     // 写入 mods.json
-    let mods_list = G2MModsList {
-        mods: old_config.mods.clone(),
-    };
+    let mods_list = mods_list_with_next_id(game_dir, old_config.mods.clone());
     let mods_path = g2m_dir.join("mods.json");
     if let Ok(mods_json) = serde_json::to_string_pretty(&mods_list) {
         if let Err(e) = fs::write(&mods_path, mods_json) {
@@ -251,9 +276,7 @@ pub fn write_g2m_json(
     }
 
     // 写入 mods.json
-    let mods_list = G2MModsList {
-        mods: existing_mods,
-    };
+    let mods_list = mods_list_with_next_id(game_dir, existing_mods);
     let mods_path = g2m_dir.join("mods.json");
     match serde_json::to_string_pretty(&mods_list) {
         Ok(json_content) => {
@@ -279,20 +302,15 @@ fn auto_detect_game_info(game_dir: &str) -> G2MGameConfig {
     };
 
     // 尝试查找游戏可执行文件
-    let possible_exes = ["gta3.exe", "gta-vc.exe", "gta_sa.exe", "gta-sa.exe"];
-    for exe_name in &possible_exes {
-        let exe_path = game_path.join(exe_name);
-        if exe_path.exists() {
-            config.exe = exe_name.to_string();
-            config.r#type = detect_game_type_from_exe(exe_name);
-            // 根据游戏类型设置默认名称
-            config.name = match config.r#type.as_deref() {
-                Some("gta3") => "GTA III".to_string(),
-                Some("gtavc") => "GTA Vice City".to_string(),
-                Some("gtasa") => "GTA San Andreas".to_string(),
-                _ => exe_name.replace(".exe", "").to_uppercase(),
-            };
-            break;
+    'detect: for descriptor in game_descriptors() {
+        for exe_name in &descriptor.executables {
+            if game_path.join(exe_name).exists() {
+                config.exe = exe_name.clone();
+                config.r#type = Some(descriptor.id.clone());
+                // 使用游戏描述中的名称作为默认名称
+                config.name = descriptor.name.clone();
+                break 'detect;
+            }
         }
     }
 
@@ -385,62 +403,18 @@ fn sanitize_mod_entries(mods: &mut Vec<G2MModInfo>) {
     }
 }
 
-pub fn add_mod_to_g2m_json(
-    game_dir: &str,
-    mod_name: String,
-    mod_author: Option<String>,
-    r#type: Option<String>,
-    install_path: Option<String>,
-) -> Result<(), String> {
-    use crate::game::types::G2MModInfo;
-    
+/// 保存 MOD 列表到 .gtamodx/mods.json（目录不存在时自动创建）
+pub fn save_mods_list(game_dir: &str, mods: Vec<G2MModInfo>) -> Result<(), String> {
     let g2m_dir = get_g2m_dir_path(game_dir);
     let mods_path = g2m_dir.join("mods.json");
 
-    // 读取现有的配置，如果不存在则自动识别游戏信息
-    let mut config = read_g2m_json(game_dir).unwrap_or_else(|| auto_detect_game_info(game_dir));
-    sanitize_mod_entries(&mut config.mods);
-
-    let normalized_install_path = normalize_install_path(install_path);
-    let normalized_type = normalize_type(r#type).or_else(|| {
-        normalized_install_path
-            .as_ref()
-            .and_then(|path| detect_type_from_path(path))
-    });
-
-    // 生成唯一ID（基于现有MOD数量 + 1，类似游戏列表）
-    let mod_id = if config.mods.is_empty() {
-        1
-    } else {
-        config.mods.iter().map(|m| m.id).max().unwrap_or(0) + 1
-    };
-
-    // 检查是否已存在相同的 MOD（同名且安装路径一致）
-    if config.mods.iter().any(|m| {
-        m.name == mod_name
-            && (m.install_path.is_none() && normalized_install_path.is_none()
-                || m.install_path == normalized_install_path)
-    }) {
-        return Err("MOD 已存在于列表中".to_string());
-    }
-
-    // 添加新的 MOD（每次安装都生成新的唯一数字ID）
-    config.mods.push(G2MModInfo {
-        id: mod_id,
-        name: mod_name,
-        author: mod_author,
-        r#type: normalized_type,
-        install_path: normalized_install_path,
-    });
-
     // 创建 .gtamodx 目录（如果不存在）
     if let Err(e) = fs::create_dir_all(&g2m_dir) {
         return Err(format!("创建 .gtamodx 目录失败: {}", e));
     }
     ensure_hidden_attribute(&g2m_dir);
 
-    // 保存更新后的 mods.json
-    let mods_list = G2MModsList { mods: config.mods };
+    let mods_list = mods_list_with_next_id(game_dir, mods);
     match serde_json::to_string_pretty(&mods_list) {
         Ok(json_content) => {
             fs::write(&mods_path, json_content)
@@ -451,49 +425,107 @@ pub fn add_mod_to_g2m_json(
     }
 }
 
-/// 从 .gtamodx/mods.json 的 mods 列表中移除 MOD
-#[allow(dead_code)]
-pub fn remove_mod_from_g2m_json(
-    game_dir: &str,
-    mod_name: &str,
-    install_path: Option<&str>,
-) -> Result<(), String> {
-    let g2m_dir = get_g2m_dir_path(game_dir);
-    let mods_path = g2m_dir.join("mods.json");
+/// 读取 mods.json 中记录的下一个 MOD ID（没有记录时为 0）
+fn stored_next_mod_id(game_dir: &str) -> u32 {
+    fs::read_to_string(get_g2m_dir_path(game_dir).join("mods.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<G2MModsList>(&content).ok())
+        .map_or(0, |list| list.next_id)
+}
 
-    // 读取现有的配置
+/// 生成要写入 mods.json 的列表，下一个 MOD ID 不小于已记录的值，也大于列表中所有的 ID
+fn mods_list_with_next_id(game_dir: &str, mods: Vec<G2MModInfo>) -> G2MModsList {
+    let max_id = mods.iter().map(|m| m.id).max().unwrap_or(0);
+    G2MModsList {
+        next_id: stored_next_mod_id(game_dir).max(max_id + 1),
+        mods,
+    }
+}
+
+/// 获取下一个可用的 MOD ID（安装前预留，用于备份目录 .gtamodx/backup/<id>/）
+/// 卸载的 MOD 的 ID 不会被复用，配置方案中记录的 ID 不会指向新安装的 MOD
+pub fn next_mod_id(game_dir: &str) -> u32 {
+    let max_id = read_g2m_json(game_dir)
+        .map(|mut config| {
+            sanitize_mod_entries(&mut config.mods);
+            config.mods.iter().map(|m| m.id).max().unwrap_or(0)
+        })
+        .unwrap_or(0);
+    stored_next_mod_id(game_dir).max(max_id + 1)
+}
+
+/// 规范化新 MOD 记录的安装路径和类型
+fn normalize_mod_info(mod_info: &mut G2MModInfo) {
+    mod_info.install_path = normalize_install_path(mod_info.install_path.take());
+    mod_info.r#type = normalize_type(mod_info.r#type.take()).or_else(|| {
+        mod_info
+            .install_path
+            .as_ref()
+            .and_then(|path| detect_type_from_path(path))
+    });
+}
+
+/// 检查新 MOD 记录能否加入列表：预留的 ID 未被占用，且没有同名、安装路径一致的 MOD
+fn check_new_mod(mods: &[G2MModInfo], mod_info: &G2MModInfo) -> Result<(), String> {
+    // 安装前预留的 ID 必须仍然可用（备份目录以该 ID 命名）
+    if mods.iter().any(|m| m.id == mod_info.id) {
+        return Err(format!("MOD ID {} 已被占用", mod_info.id));
+    }
+
+    // 检查是否已存在相同的 MOD（同名且安装路径一致）
+    if mods.iter().any(|m| {
+        m.name == mod_info.name
+            && (m.install_path.is_none() && mod_info.install_path.is_none()
+                || m.install_path == mod_info.install_path)
+    }) {
+        return Err("MOD 已存在于列表中".to_string());
+    }
+    Ok(())
+}
+
+/// 安装前检查 MOD 记录能否写入 .gtamodx/mods.json（与 add_mod_to_g2m_json 的检查相同）
+pub fn check_mod_record(game_dir: &str, mod_info: &G2MModInfo) -> Result<(), String> {
+    let mut mods = read_g2m_json(game_dir)
+        .map(|config| config.mods)
+        .unwrap_or_default();
+    sanitize_mod_entries(&mut mods);
+    let mut mod_info = mod_info.clone();
+    normalize_mod_info(&mut mod_info);
+    check_new_mod(&mods, &mod_info)
+}
+
+/// 添加 MOD 到 .gtamodx/mods.json
+/// mod_info 中的安装清单用于卸载时删除文件并恢复备份
+pub fn add_mod_to_g2m_json(game_dir: &str, mut mod_info: G2MModInfo) -> Result<(), String> {
+    // 读取现有的配置，如果不存在则自动识别游戏信息
+    let mut config = read_g2m_json(game_dir).unwrap_or_else(|| auto_detect_game_info(game_dir));
+    sanitize_mod_entries(&mut config.mods);
+    normalize_mod_info(&mut mod_info);
+    check_new_mod(&config.mods, &mod_info)?;
+
+    // 添加新的 MOD（每次安装都生成新的唯一数字ID）
+    config.mods.push(mod_info);
+
+    // 保存更新后的 mods.json
+    save_mods_list(game_dir, config.mods)
+}
+
+/// 更新 .gtamodx/mods.json 中指定 MOD 的记录（按 ID 匹配）
+pub fn update_mod_in_g2m_json(game_dir: &str, mod_info: &G2MModInfo) -> Result<(), String> {
     let mut config = match read_g2m_json(game_dir) {
         Some(c) => c,
         None => return Err(".gtamodx 目录或配置文件不存在".to_string()),
     };
     sanitize_mod_entries(&mut config.mods);
 
-    // 移除指定的 MOD
-    let initial_len = config.mods.len();
-    config.mods.retain(|m| {
-        let same_name = m.name == mod_name;
-        let same_path = match (m.install_path.as_deref(), install_path) {
-            (None, None) => true,
-            (Some(existing), Some(target)) => existing == target,
-            _ => false,
-        };
-        !(same_name && same_path)
-    });
-
-    if config.mods.len() == initial_len {
-        return Err("未找到指定的 MOD".to_string());
-    }
+    let entry = config
+        .mods
+        .iter_mut()
+        .find(|m| m.id == mod_info.id)
+        .ok_or_else(|| "未找到指定的 MOD".to_string())?;
+    *entry = mod_info.clone();
 
-    // 保存更新后的 mods.json
-    let mods_list = G2MModsList { mods: config.mods };
-    match serde_json::to_string_pretty(&mods_list) {
-        Ok(json_content) => {
-            fs::write(&mods_path, json_content)
-                .map_err(|e| format!("写入 mods.json 失败: {}", e))?;
-            Ok(())
-        }
-        Err(e) => Err(format!("序列化 mods.json 失败: {}", e)),
-    }
+    save_mods_list(game_dir, config.mods)
 }
 
 /// 扫描游戏目录，自动识别已安装的 MOD
@@ -531,8 +563,18 @@ pub fn scan_installed_mods(game_dir: &str) -> Vec<G2MModInfo> {
                                 id: mod_id,
                                 name: mod_name.clone(),
                                 author: None,
+                                version: None,
                                 r#type: detect_type_from_path(&install_path),
                                 install_path: Some(install_path),
+                                installed_files: Vec::new(),
+                                created_directories: Vec::new(),
+                                backed_up_files: Vec::new(),
+                                dat_entries: Vec::new(),
+                                text_changes: Vec::new(),
+                                modloader_priority: None,
+                                modloader_ignored: false,
+                                enabled: true,
+                                selected_options: BTreeMap::new(),
                             });
                         }
                     }
@@ -572,8 +614,18 @@ pub fn scan_installed_mods(game_dir: &str) -> Vec<G2MModInfo> {
                                     id: mod_id,
                                     name: mod_name.clone(),
                                     author: None,
+                                    version: None,
                                     r#type: detect_type_from_path(&install_path),
                                     install_path: Some(install_path),
+                                    installed_files: Vec::new(),
+                                    created_directories: Vec::new(),
+                                    backed_up_files: Vec::new(),
+                                    dat_entries: Vec::new(),
+                                    text_changes: Vec::new(),
+                                    modloader_priority: None,
+                                    modloader_ignored: false,
+                                    enabled: true,
+                                    selected_options: BTreeMap::new(),
                                 });
                             }
                         }
@@ -722,3 +774,68 @@ pub fn find_file_case_insensitive(dir: &Path, target_name: &str) -> Option<(Path
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_game_dir(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("g2m-utils-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let g2m_dir = dir.join(".gtamodx");
+        fs::create_dir_all(&g2m_dir).unwrap();
+        fs::write(
+            g2m_dir.join("info.json"),
+            r#"{"name": "GTA San Andreas", "exe": "gta_sa.exe"}"#,
+        )
+        .unwrap();
+        dir.to_string_lossy().to_string()
+    }
+
+    fn mod_info(id: u32, name: &str) -> G2MModInfo {
+        serde_json::from_str(&format!(r#"{{"id": {}, "name": "{}"}}"#, id, name)).unwrap()
+    }
+
+    fn installed_ids(game_dir: &str) -> Vec<u32> {
+        read_g2m_json(game_dir)
+            .unwrap()
+            .mods
+            .iter()
+            .map(|m| m.id)
+            .collect()
+    }
+
+    #[test]
+    fn uninstalled_mod_ids_are_not_reused() {
+        let game_dir = temp_game_dir("next-id");
+        assert_eq!(next_mod_id(&game_dir), 1);
+        add_mod_to_g2m_json(&game_dir, mod_info(next_mod_id(&game_dir), "A")).unwrap();
+        add_mod_to_g2m_json(&game_dir, mod_info(next_mod_id(&game_dir), "B")).unwrap();
+        assert_eq!(installed_ids(&game_dir), vec![1, 2]);
+
+        // 卸载 ID 最大的 MOD 后再安装，新 MOD 不能拿到配置方案中可能仍记录着的 ID 2
+        let mods = read_g2m_json(&game_dir).unwrap().mods;
+        save_mods_list(&game_dir, mods.into_iter().filter(|m| m.id != 2).collect()).unwrap();
+        assert_eq!(next_mod_id(&game_dir), 3);
+        add_mod_to_g2m_json(&game_dir, mod_info(next_mod_id(&game_dir), "C")).unwrap();
+        assert_eq!(installed_ids(&game_dir), vec![1, 3]);
+
+        // 已占用的 ID 和重复的 MOD 都不能写入
+        assert!(check_mod_record(&game_dir, &mod_info(3, "D")).is_err());
+        assert!(check_mod_record(&game_dir, &mod_info(4, "C")).is_err());
+        assert!(check_mod_record(&game_dir, &mod_info(4, "D")).is_ok());
+        fs::remove_dir_all(&game_dir).unwrap();
+    }
+
+    #[test]
+    fn next_id_falls_back_to_max_id_for_old_lists() {
+        let game_dir = temp_game_dir("old-list");
+        fs::write(
+            get_g2m_dir_path(&game_dir).join("mods.json"),
+            r#"{"mods": [{"id": 4, "name": "A"}, {"id": 9, "name": "B"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(next_mod_id(&game_dir), 10);
+        fs::remove_dir_all(&game_dir).unwrap();
+    }
+}
@@ -0,0 +1,223 @@
+use crate::game::descriptors::game_engine;
+use crate::game::utils::get_bundled_dir;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// 版本数据库中的一个主程序（G2M/Data/game_versions.json）
+/// 按 sha256、md5 识别；没有哈希时按主程序文件名和大小识别
+#[derive(Debug, Deserialize)]
+struct GameVersionEntry {
+    game: String,
+    version: String,
+    #[serde(default)]
+    exe: Option<String>,
+    #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
+    md5: Option<String>,
+    #[serde(default)]
+    sha256: Option<String>,
+    #[serde(default)]
+    cleo: bool,
+    #[serde(default)]
+    modloader: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct GameVersionDatabase {
+    #[serde(default)]
+    executables: Vec<GameVersionEntry>,
+}
+
+/// 主程序的指纹和识别出的版本
+#[derive(Debug, Clone)]
+pub struct GameExeInfo {
+    pub md5: String,
+    pub sha256: String,
+    pub version: Option<String>,
+    pub loader_compatible: Option<bool>, // 是否可以使用 CLEO 和 ModLoader，未识别版本时为 None
+}
+
+/// 读取版本数据库，随程序提供，可以直接替换文件更新而无需重新编译
+fn load_database() -> Result<GameVersionDatabase, String> {
+    let path = get_bundled_dir("Data")?.join("game_versions.json");
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("读取游戏版本数据库失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析游戏版本数据库失败: {}", e))
+}
+
+/// 流式计算文件的 MD5 和 SHA-256，返回（大小, MD5, SHA-256）
+fn hash_executable(path: &Path) -> Result<(u64, String, String), String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("打开文件失败: {}", e))?;
+    let mut md5_context = md5::Context::new();
+    let mut sha256 = Sha256::new();
+    let mut size = 0u64;
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("读取文件失败: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        md5_context.consume(&buffer[..read]);
+        sha256.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((
+        size,
+        format!("{:x}", md5_context.compute()),
+        format!("{:x}", sha256.finalize()),
+    ))
+}
+
+/// 在数据库中查找主程序：哈希相同的条目优先，其次是文件名和大小相同且没有记录哈希的条目
+/// 匹配到多个条目时，只采用它们一致的版本和兼容性
+fn identify(
+    database: &GameVersionDatabase,
+    game_type: Option<&str>,
+    exe_name: &str,
+    size: u64,
+    md5: &str,
+    sha256: &str,
+) -> (Option<String>, Option<bool>) {
+    let entries: Vec<&GameVersionEntry> = database
+        .executables
+        .iter()
+        .filter(|e| game_type.is_none_or(|t| e.game.eq_ignore_ascii_case(t)))
+        .collect();
+
+    let by_hash: Vec<&GameVersionEntry> = entries
+        .iter()
+        .copied()
+        .filter(|e| {
+            e.sha256
+                .as_deref()
+                .is_some_and(|h| h.eq_ignore_ascii_case(sha256))
+                || e.md5
+                    .as_deref()
+                    .is_some_and(|h| h.eq_ignore_ascii_case(md5))
+        })
+        .collect();
+    let matched = if by_hash.is_empty() {
+        entries
+            .into_iter()
+            .filter(|e| e.md5.is_none() && e.sha256.is_none())
+            .filter(|e| e.size == Some(size))
+            .filter(|e| {
+                e.exe
+                    .as_deref()
+                    .is_none_or(|x| x.eq_ignore_ascii_case(exe_name))
+            })
+            .collect()
+    } else {
+        by_hash
+    };
+
+    let Some(first) = matched.first() else {
+        return (None, None);
+    };
+    let version = matched
+        .iter()
+        .all(|e| e.version == first.version)
+        .then(|| first.version.clone());
+    let compatible = first.cleo && first.modloader;
+    let loader_compatible = matched
+        .iter()
+        .all(|e| (e.cleo && e.modloader) == compatible)
+        .then_some(compatible);
+    (version, loader_compatible)
+}
+
+/// 计算主程序的指纹并识别游戏版本；版本数据库不可用时只返回哈希
+/// 自定义游戏按其基于的引擎（游戏描述中的 engine）查找版本
+pub fn identify_game_exe(game_type: Option<&str>, exe_path: &Path) -> Result<GameExeInfo, String> {
+    let game_type = game_type.map(|t| game_engine(t).unwrap_or(t));
+    let (size, md5, sha256) = hash_executable(exe_path)?;
+    let exe_name = exe_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (version, loader_compatible) = match load_database() {
+        Ok(database) => identify(&database, game_type, &exe_name, size, &md5, &sha256),
+        Err(e) => {
+            eprintln!("警告: {}", e);
+            (None, None)
+        }
+    };
+    Ok(GameExeInfo {
+        md5,
+        sha256,
+        version,
+        loader_compatible,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shipped_database() -> GameVersionDatabase {
+        serde_json::from_str(include_str!("../../G2M/Data/game_versions.json")).unwrap()
+    }
+
+    /// 数据库中的每个主程序都必须能唯一识别：用条目自身的哈希或大小查找时，
+    /// 不能与其他版本（或兼容性不同的条目）冲突
+    #[test]
+    fn shipped_entries_identify_one_version() {
+        let database = shipped_database();
+        assert!(!database.executables.is_empty());
+        for entry in &database.executables {
+            assert!(
+                entry.md5.is_some() || entry.sha256.is_some() || entry.size.is_some(),
+                "{} {} 没有哈希也没有大小",
+                entry.game,
+                entry.version
+            );
+            let exe_name = entry.exe.as_deref().unwrap_or_default();
+            let size = entry.size.unwrap_or_default();
+            let md5 = entry.md5.as_deref().unwrap_or_default();
+            let sha256 = entry.sha256.as_deref().unwrap_or_default();
+            for game_type in [Some(entry.game.as_str()), None] {
+                let (version, loader_compatible) =
+                    identify(&database, game_type, exe_name, size, md5, sha256);
+                assert_eq!(
+                    version.as_deref(),
+                    Some(entry.version.as_str()),
+                    "{} {} (size {:?}) 无法唯一识别",
+                    entry.game,
+                    entry.version,
+                    entry.size
+                );
+                assert_eq!(loader_compatible, Some(entry.cleo && entry.modloader));
+            }
+        }
+    }
+
+    #[test]
+    fn conflicting_matches_are_not_identified() {
+        let database: GameVersionDatabase = serde_json::from_str(
+            r#"{"executables": [
+                {"game": "gtasa", "version": "1.0", "exe": "gta_sa.exe", "size": 100, "cleo": true, "modloader": true},
+                {"game": "gtasa", "version": "1.01", "exe": "gta_sa.exe", "size": 100, "cleo": true},
+                {"game": "gtasa", "version": "steam", "size": 100, "md5": "ABCDEF"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            identify(&database, Some("gtasa"), "gta_sa.exe", 100, "", ""),
+            (None, None)
+        );
+        assert_eq!(
+            identify(&database, Some("gtasa"), "gta_sa.exe", 100, "abcdef", ""),
+            (Some("steam".to_string()), Some(false))
+        );
+        assert_eq!(
+            identify(&database, Some("gta3"), "gta_sa.exe", 100, "abcdef", ""),
+            (None, None)
+        );
+    }
+}
@@ -4,6 +4,9 @@ mod game;
 #[path = "mod/mod.rs"]
 mod mod_core;
 
+#[path = "formats/mod.rs"]
+mod formats;
+
 #[path = "utils/log.rs"]
 mod log;
 
@@ -11,12 +14,14 @@ mod log;
 mod app_info;
 
 // 从 game 模块导入所有函数
+use game::baseline::{restore_vanilla_files, verify_game_files};
 use game::core::{
     check_duplicate_directory, copy_game_image, copy_image_to_custom_dir, delete_game,
     get_game_by_id, get_game_mods, get_games, install_mod_prerequisites, launch_game,
-    open_game_folder, process_image_upload, save_base64_image, save_game, select_image_file,
-    update_game,
+    open_game_folder, open_user_files_folder, process_image_upload, save_base64_image, save_game,
+    select_image_file, update_game,
 };
+use game::descriptors::get_game_descriptors;
 use game::detection::{detect_game, select_game_folder};
 use game::download::{
     cancel_download, download_game, extract_game, get_download_records, get_extract_records,
@@ -27,11 +32,23 @@ use game::prerequisites::{
     get_custom_prerequisites, install_custom_prerequisite, mark_mod_loader_manual,
     select_custom_prerequisite_files, select_mod_loader_file, unmark_mod_loader_manual,
 };
+use game::snapshot::{create_game_snapshot, delete_game_snapshot, restore_game_snapshot};
 
 use app_info::get_app_info;
+use formats::{
+    add_dat_entries, analyze_cleo_script, delete_img_entries, diff_text_tables, dump_text_table,
+    extract_img_entries, import_img_entries, inspect_game_asset, list_dat_entries,
+    list_img_entries, rebuild_img_archive, remove_dat_entries, restore_img_entries,
+};
 use mod_core::{
-    get_mod_file_tree, install_user_mod, read_g2m_mod_config, save_g2m_mod_config,
+    check_model_collisions, clone_mod_profile, create_mod_profile, create_modloader_profile,
+    delete_mod_profile, get_g2m_mod_config_schema, get_mod_file_tree, get_mod_install_options,
+    get_mod_profiles, get_modloader_state, install_user_mod, merge_mod_text,
+    plan_user_mod_install, read_g2m_mod_config, save_g2m_mod_config,
     select_game_install_directory, select_mod_directory, select_mod_files,
+    select_modloader_profile, set_mod_enabled, set_modloader_mod_included,
+    set_modloader_priority, switch_mod_profile, uninstall_user_mod, update_user_mod,
+    validate_g2m_mod_config,
 };
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -53,12 +70,18 @@ pub fn run() -> () {
             greet,
             select_game_folder,
             detect_game,
+            get_game_descriptors,
             save_game,
             get_games,
             get_game_by_id,
             get_game_mods,
             update_game,
             delete_game,
+            create_game_snapshot,
+            restore_game_snapshot,
+            delete_game_snapshot,
+            verify_game_files,
+            restore_vanilla_files,
             check_duplicate_directory,
             copy_game_image,
             select_image_file,
@@ -67,6 +90,7 @@ pub fn run() -> () {
             copy_image_to_custom_dir,
             launch_game,
             open_game_folder,
+            open_user_files_folder,
             check_mod_loaders,
             install_mod_prerequisites,
             select_mod_loader_file,
@@ -84,8 +108,40 @@ pub fn run() -> () {
             get_extract_records,
             select_extract_folder,
             install_user_mod,
+            plan_user_mod_install,
+            update_user_mod,
+            get_mod_install_options,
+            uninstall_user_mod,
+            set_mod_enabled,
+            check_model_collisions,
+            get_mod_profiles,
+            create_mod_profile,
+            clone_mod_profile,
+            switch_mod_profile,
+            delete_mod_profile,
             save_g2m_mod_config,
             read_g2m_mod_config,
+            validate_g2m_mod_config,
+            get_g2m_mod_config_schema,
+            list_img_entries,
+            extract_img_entries,
+            import_img_entries,
+            delete_img_entries,
+            restore_img_entries,
+            rebuild_img_archive,
+            list_dat_entries,
+            add_dat_entries,
+            remove_dat_entries,
+            inspect_game_asset,
+            analyze_cleo_script,
+            dump_text_table,
+            diff_text_tables,
+            merge_mod_text,
+            get_modloader_state,
+            set_modloader_priority,
+            set_modloader_mod_included,
+            create_modloader_profile,
+            select_modloader_profile,
             get_mod_file_tree,
             select_mod_directory,
             select_mod_files,
@@ -0,0 +1,391 @@
+use flate2::read::GzDecoder;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use zip::ZipArchive;
+
+/// 支持直接安装的压缩包格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArchiveKind {
+    Zip,
+    SevenZip,
+    Rar,
+    TarGz,
+    Tar,
+}
+
+/// 根据文件名识别压缩包格式
+fn detect_archive_kind(path: &Path) -> Option<ArchiveKind> {
+    if !path.is_file() {
+        return None;
+    }
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else if name.ends_with(".7z") {
+        Some(ArchiveKind::SevenZip)
+    } else if name.ends_with(".rar") {
+        Some(ArchiveKind::Rar)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else {
+        None
+    }
+}
+
+/// 检查路径是否为支持的压缩包
+pub fn is_archive_path(path: &Path) -> bool {
+    detect_archive_kind(path).is_some()
+}
+
+/// 去掉压缩包扩展名后的文件名（用作解压目录名）
+fn archive_stem(path: &Path) -> String {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let lower = name.to_lowercase();
+    for ext in [".tar.gz", ".tgz", ".zip", ".7z", ".rar", ".tar"] {
+        if lower.ends_with(ext) {
+            return name[..name.len() - ext.len()].to_string();
+        }
+    }
+    name
+}
+
+/// 已解压到临时目录的压缩包，离开作用域时自动删除临时目录
+pub struct ExtractedArchive {
+    staging_dir: PathBuf,
+    mod_root: PathBuf,
+}
+
+impl ExtractedArchive {
+    /// MOD 实际的根目录（已跳过外层包装目录）
+    pub fn mod_root(&self) -> &Path {
+        &self.mod_root
+    }
+}
+
+impl Drop for ExtractedArchive {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.staging_dir);
+    }
+}
+
+/// 解压 MOD 压缩包到临时目录，并定位 MOD 根目录
+pub fn extract_mod_archive(archive_path: &Path) -> Result<ExtractedArchive, String> {
+    let kind = detect_archive_kind(archive_path)
+        .ok_or_else(|| format!("不支持的压缩包格式: {}", archive_path.display()))?;
+
+    let staging_dir = std::env::temp_dir().join(format!("g2m_mod_{}", uuid::Uuid::new_v4()));
+    // 以压缩包名称命名解压目录，没有外层目录的压缩包安装后仍使用该名称
+    let extract_dir = staging_dir.join(archive_stem(archive_path));
+    fs::create_dir_all(&extract_dir).map_err(|e| format!("创建临时解压目录失败: {}", e))?;
+
+    // 先创建守卫，解压失败时同样会清理临时目录
+    let mut extracted = ExtractedArchive {
+        staging_dir,
+        mod_root: extract_dir.clone(),
+    };
+
+    let result = match kind {
+        ArchiveKind::Zip => extract_zip(archive_path, &extract_dir),
+        ArchiveKind::SevenZip => extract_7z(archive_path, &extract_dir),
+        ArchiveKind::Rar => extract_rar(archive_path, &extract_dir),
+        ArchiveKind::TarGz => File::open(archive_path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| extract_tar(GzDecoder::new(file), &extract_dir)),
+        ArchiveKind::Tar => File::open(archive_path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| extract_tar(file, &extract_dir)),
+    };
+    result.map_err(|e| format!("解压失败: {}\n压缩包: {}", e, archive_path.display()))?;
+
+    extracted.mod_root = find_mod_root(&extract_dir);
+    Ok(extracted)
+}
+
+/// 将压缩包内的条目名称转换为解压目录下的路径
+/// 绝对路径、盘符和 .. 等会逃出解压目录的条目返回 None
+fn safe_entry_path(dest: &Path, entry_name: &str) -> Option<PathBuf> {
+    let normalized = entry_name.replace('\\', "/");
+    let mut path = dest.to_path_buf();
+    for component in Path::new(&normalized).components() {
+        match component {
+            Component::Normal(part) => {
+                if part.to_string_lossy().contains(':') {
+                    return None;
+                }
+                path.push(part);
+            }
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    Some(path)
+}
+
+/// 解压 ZIP 压缩包
+fn extract_zip(archive_path: &Path, dest: &Path) -> Result<(), String> {
+    let file = File::open(archive_path).map_err(|e| e.to_string())?;
+    let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i).map_err(|e| e.to_string())?;
+        let outpath = match entry.enclosed_name() {
+            Some(path) => dest.join(path),
+            None => continue,
+        };
+
+        if entry.is_dir() {
+            fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
+        } else {
+            if let Some(parent) = outpath.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            let mut outfile = File::create(&outpath).map_err(|e| e.to_string())?;
+            io::copy(&mut entry, &mut outfile).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+/// 解压 tar 压缩包
+/// 符号链接和硬链接条目可能指向解压目录之外，包含这类条目的压缩包直接拒绝
+fn extract_tar<R: io::Read>(reader: R, dest: &Path) -> Result<(), String> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            return Err(format!(
+                "压缩包包含符号链接或硬链接，无法安装: {}",
+                String::from_utf8_lossy(&entry.path_bytes())
+            ));
+        }
+        // unpack_in 会跳过绝对路径和包含 .. 的条目
+        entry.unpack_in(dest).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// 解压 7z 压缩包
+fn extract_7z(archive_path: &Path, dest: &Path) -> Result<(), String> {
+    sevenz_rust::decompress_file_with_extract_fn(archive_path, dest, |entry, reader, _| {
+        let Some(outpath) = safe_entry_path(dest, entry.name()) else {
+            // 跳过不安全的条目（仍需读完数据流）
+            io::copy(reader, &mut io::sink()).map_err(sevenz_rust::Error::io)?;
+            return Ok(true);
+        };
+
+        if entry.is_directory() {
+            fs::create_dir_all(&outpath).map_err(sevenz_rust::Error::io)?;
+        } else {
+            if let Some(parent) = outpath.parent() {
+                fs::create_dir_all(parent).map_err(sevenz_rust::Error::io)?;
+            }
+            let mut outfile = File::create(&outpath).map_err(sevenz_rust::Error::io)?;
+            io::copy(reader, &mut outfile).map_err(sevenz_rust::Error::io)?;
+        }
+        Ok(true)
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// 解压 RAR 压缩包
+/// RAR 格式没有可用的纯 Rust 解码器，调用系统中已安装的 UnRAR 或 7-Zip
+fn extract_rar(archive_path: &Path, dest: &Path) -> Result<(), String> {
+    let unrar_args = |tool: &str| -> (String, Vec<String>) {
+        (
+            tool.to_string(),
+            vec![
+                "x".to_string(),
+                "-o+".to_string(),
+                "-idq".to_string(),
+                archive_path.to_string_lossy().to_string(),
+                format!("{}{}", dest.to_string_lossy(), std::path::MAIN_SEPARATOR),
+            ],
+        )
+    };
+    let seven_zip_args = |tool: &str| -> (String, Vec<String>) {
+        (
+            tool.to_string(),
+            vec![
+                "x".to_string(),
+                "-y".to_string(),
+                format!("-o{}", dest.to_string_lossy()),
+                archive_path.to_string_lossy().to_string(),
+            ],
+        )
+    };
+
+    let candidates = [
+        unrar_args("unrar"),
+        unrar_args("UnRAR.exe"),
+        unrar_args(r"C:\Program Files\WinRAR\UnRAR.exe"),
+        seven_zip_args("7z"),
+        seven_zip_args(r"C:\Program Files\7-Zip\7z.exe"),
+    ];
+
+    // 依次尝试每个工具，全部失败时返回最后一个错误
+    let mut last_error = None;
+    for (tool, args) in candidates {
+        let mut command = Command::new(&tool);
+        command.args(&args);
+        hide_console_window(&mut command);
+        match command.output() {
+            Ok(output) if output.status.success() => return Ok(()),
+            Ok(output) => {
+                last_error = Some(format!(
+                    "{} 解压失败: {}",
+                    tool,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ))
+            }
+            // 工具不存在，尝试下一个
+            Err(_) => continue,
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        "未找到可用的 RAR 解压工具，请安装 WinRAR 或 7-Zip，或先手动解压后再安装".to_string()
+    }))
+}
+
+/// 调用命令行工具时不弹出控制台窗口
+#[cfg(target_os = "windows")]
+fn hide_console_window(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    command.creation_flags(CREATE_NO_WINDOW);
+}
+
+#[cfg(not(target_os = "windows"))]
+fn hide_console_window(_command: &mut Command) {}
+
+/// 定位 MOD 根目录
+/// 优先使用包含 g2m.json 的最浅目录；否则跳过只包含单个子目录的外层包装目录
+fn find_mod_root(extract_dir: &Path) -> PathBuf {
+    if let Some(config_dir) = find_config_dir(extract_dir) {
+        return config_dir;
+    }
+
+    let mut root = extract_dir.to_path_buf();
+    loop {
+        let entries: Vec<PathBuf> = match fs::read_dir(&root) {
+            Ok(entries) => entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| !is_archive_junk(p))
+                .collect(),
+            Err(_) => break,
+        };
+        match entries.as_slice() {
+            [only] if only.is_dir() => root = only.clone(),
+            _ => break,
+        }
+    }
+    root
+}
+
+/// 按层级查找包含 g2m.json 的最浅目录
+fn find_config_dir(extract_dir: &Path) -> Option<PathBuf> {
+    let mut level = vec![extract_dir.to_path_buf()];
+    while !level.is_empty() {
+        let mut next = Vec::new();
+        for dir in level {
+            if dir.join("g2m.json").is_file() {
+                return Some(dir);
+            }
+            if let Ok(entries) = fs::read_dir(&dir) {
+                let mut children: Vec<PathBuf> = entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.is_dir() && !is_archive_junk(p))
+                    .collect();
+                children.sort();
+                next.extend(children);
+            }
+        }
+        level = next;
+    }
+    None
+}
+
+/// 压缩工具附带的无关条目（macOS 资源目录等）
+fn is_archive_junk(path: &Path) -> bool {
+    matches!(
+        path.file_name().and_then(|n| n.to_str()),
+        Some("__MACOSX") | Some(".DS_Store") | Some("Thumbs.db")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("g2m-archive-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn file_header(name: &[u8], size: u64) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name);
+        header.set_size(size);
+        header.set_mode(0o644);
+        header.set_cksum();
+        header
+    }
+
+    fn link_header(name: &str, entry_type: tar::EntryType) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_path(name).unwrap();
+        header.set_link_name("/etc/passwd").unwrap();
+        header.set_size(0);
+        header.set_cksum();
+        header
+    }
+
+    #[test]
+    fn tar_extracts_files_and_skips_parent_paths() {
+        let dir = temp_dir("tar");
+        let mut builder = tar::Builder::new(Vec::new());
+        builder
+            .append(&file_header(b"mod/a.cs", 3), &b"abc"[..])
+            .unwrap();
+        builder
+            .append(&file_header(b"../evil.txt", 4), &b"evil"[..])
+            .unwrap();
+        let data = builder.into_inner().unwrap();
+
+        let dest = dir.join("out");
+        fs::create_dir_all(&dest).unwrap();
+        extract_tar(data.as_slice(), &dest).unwrap();
+        assert_eq!(fs::read(dest.join("mod/a.cs")).unwrap(), b"abc");
+        assert!(!dir.join("evil.txt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tar_rejects_links() {
+        let dir = temp_dir("tar-links");
+        for entry_type in [tar::EntryType::Symlink, tar::EntryType::Link] {
+            let mut builder = tar::Builder::new(Vec::new());
+            builder
+                .append(&link_header("mod/link", entry_type), io::empty())
+                .unwrap();
+            let data = builder.into_inner().unwrap();
+            let error = extract_tar(data.as_slice(), &dir).unwrap_err();
+            assert!(error.contains("mod/link"), "{}", error);
+            assert!(fs::symlink_metadata(dir.join("mod/link")).is_err());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
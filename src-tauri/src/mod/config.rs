@@ -1,40 +1,135 @@
-use crate::game::types::{ApiResponse, G2MModConfig};
+use crate::formats::dat::validate_dat_entry;
+use crate::game::types::{
+    ApiResponse, G2MConfigValidationError, G2MConfigValidationResult, G2MModConfig, ModFileEntry,
+};
+use crate::mod_core::schema::{
+    g2m_mod_config_schema, parse_g2m_mod_config, G2M_CONFIG_SCHEMA_VERSION,
+};
+use crate::mod_core::img_target::{is_img_target, normalize_img_target};
+use crate::mod_core::utils::check_relative_path;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tauri::AppHandle;
 
-/// 读取 g2m.json 配置文件
-pub fn load_g2m_mod_config(mod_source_path: &Path) -> Option<G2MModConfig> {
-    let config_path = mod_source_path.join("g2m.json");
-
-    // 如果 mod_source_path 是文件，则检查其所在目录
-    let config_path = if mod_source_path.is_file() {
+/// 获取 g2m.json 路径（mod_source_path 是文件时使用其所在目录）
+fn get_g2m_config_path(mod_source_path: &Path) -> PathBuf {
+    if mod_source_path.is_file() {
         mod_source_path
             .parent()
-            .and_then(|p| Some(p.join("g2m.json")))
-            .unwrap_or(config_path)
+            .map(|p| p.join("g2m.json"))
+            .unwrap_or_else(|| mod_source_path.join("g2m.json"))
     } else {
-        config_path
-    };
+        mod_source_path.join("g2m.json")
+    }
+}
 
+/// 读取 g2m.json 配置文件（旧版本配置会自动迁移）
+/// 没有配置文件时返回 Ok(None)；配置无效时返回全部错误及其位置，而不是忽略配置
+pub fn load_g2m_mod_config(mod_source_path: &Path) -> Result<Option<G2MModConfig>, String> {
+    let config_path = get_g2m_config_path(mod_source_path);
     if !config_path.exists() {
-        return None;
+        return Ok(None);
     }
 
-    match fs::read_to_string(&config_path) {
-        Ok(content) => match serde_json::from_str::<G2MModConfig>(&content) {
-            Ok(config) => Some(config),
-            Err(e) => {
-                eprintln!("解析 g2m.json 失败: {}", e);
-                None
+    let content =
+        fs::read_to_string(&config_path).map_err(|e| format!("读取 g2m.json 失败: {}", e))?;
+    parse_g2m_mod_config(&content)
+        .map(|parsed| Some(parsed.config))
+        .map_err(|(_, errors)| format_validation_errors(&errors))
+}
+
+/// 将校验错误整理为文本
+fn format_validation_errors(errors: &[G2MConfigValidationError]) -> String {
+    let lines: Vec<String> = errors
+        .iter()
+        .map(|e| format!("{}: {}", e.path, e.message))
+        .collect();
+    format!("g2m.json 无效:\n{}", lines.join("\n"))
+}
+
+/// 检查 g2m.json 中所有文件条目的路径
+/// source 必须是 MOD 根目录内的相对路径；target 必须是游戏目录内的相对路径（可使用 ${cleo} 等变量），
+/// 或 img://<档案路径>/<条目名> 形式的 IMG 档案条目（只能是单个文件）
+/// 包括可选组件中的文件条目，以及 dat_entries 中要写入 .dat 文件的行
+pub fn mod_file_path_errors(config: &G2MModConfig) -> Vec<G2MConfigValidationError> {
+    let mut errors = entry_path_errors("$.modfile", &config.modfile);
+    for (index, entry) in config.dat_entries.iter().enumerate() {
+        if let Err(reason) = validate_dat_entry(entry) {
+            errors.push(G2MConfigValidationError {
+                path: format!("$.dat_entries[{}]", index),
+                message: reason,
+            });
+        }
+    }
+    for (g, group) in config.options.iter().enumerate() {
+        for (o, option) in group.options.iter().enumerate() {
+            errors.extend(entry_path_errors(
+                &format!("$.options[{}].options[{}].modfile", g, o),
+                &option.modfile,
+            ));
+        }
+    }
+    errors
+}
+
+fn entry_path_errors(prefix: &str, entries: &[ModFileEntry]) -> Vec<G2MConfigValidationError> {
+    let mut errors = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        if let Err(reason) = check_relative_path(&entry.source) {
+            errors.push(G2MConfigValidationError {
+                path: format!("{}[{}].source", prefix, index),
+                message: format!("\"{}\" {}", entry.source, reason),
+            });
+        }
+        if is_img_target(&entry.target) {
+            let problem = match normalize_img_target(&entry.target) {
+                Err(reason) => Some(reason),
+                Ok(_) if entry.is_directory => Some("IMG 档案条目不能是目录".to_string()),
+                Ok(_) => None,
+            };
+            if let Some(reason) = problem {
+                errors.push(G2MConfigValidationError {
+                    path: format!("{}[{}].target", prefix, index),
+                    message: format!("\"{}\" {}", entry.target, reason),
+                });
             }
-        },
-        Err(e) => {
-            eprintln!("读取 g2m.json 失败: {}", e);
-            None
+        } else if let Err(reason) = check_relative_path(&strip_path_variables(&entry.target)) {
+            errors.push(G2MConfigValidationError {
+                path: format!("{}[{}].target", prefix, index),
+                message: format!("\"{}\" {}", entry.target, reason),
+            });
         }
     }
+
+    errors
+}
+
+/// 校验 g2m.json 中所有文件条目的路径，不合法时列出全部有问题的条目
+pub fn validate_mod_file_paths(config: &G2MModConfig) -> Result<(), String> {
+    let errors = mod_file_path_errors(config);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format_validation_errors(&errors))
+    }
+}
+
+/// 将 ${...} 路径变量替换为普通目录名，便于对其余部分做路径检查
+fn strip_path_variables(target: &str) -> String {
+    let mut result = String::new();
+    let mut rest = target;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}').map(|offset| start + offset) else {
+            break;
+        };
+        result.push_str(&rest[..start]);
+        result.push_str("var");
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
 }
 
 /// 选择 MOD 目录
@@ -162,8 +257,10 @@ pub async fn read_g2m_mod_config(
         return Ok(ApiResponse::error("MOD 目录不存在".to_string()));
     }
 
-    let config = load_g2m_mod_config(mod_path);
-    Ok(ApiResponse::success(config))
+    match load_g2m_mod_config(mod_path) {
+        Ok(config) => Ok(ApiResponse::success(config)),
+        Err(e) => Ok(ApiResponse::error(e)),
+    }
 }
 
 /// 文件树节点
@@ -266,7 +363,7 @@ fn build_file_tree(
 #[tauri::command]
 pub async fn save_g2m_mod_config(
     mod_dir: String,
-    config: G2MModConfig,
+    mut config: G2MModConfig,
 ) -> Result<ApiResponse<()>, String> {
     let mod_path = Path::new(&mod_dir);
     
@@ -274,6 +371,12 @@ pub async fn save_g2m_mod_config(
         return Ok(ApiResponse::error("MOD 目录不存在".to_string()));
     }
 
+    if let Err(e) = validate_mod_file_paths(&config) {
+        return Ok(ApiResponse::error(e));
+    }
+    // 保存时总是写入当前版本
+    config.schema_version = G2M_CONFIG_SCHEMA_VERSION;
+
     let config_path = mod_path.join("g2m.json");
 
     // 序列化配置为 JSON
@@ -288,3 +391,43 @@ pub async fn save_g2m_mod_config(
     }
 }
 
+/// 校验 g2m.json（Tauri 命令）
+/// 返回每个错误的 JSON 路径，便于 MOD 作者定位配置为何无法使用
+#[tauri::command]
+pub async fn validate_g2m_mod_config(
+    mod_dir: String,
+) -> Result<ApiResponse<G2MConfigValidationResult>, String> {
+    let config_path = get_g2m_config_path(Path::new(&mod_dir));
+    let content = match fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        Err(e) => {
+            return Ok(ApiResponse::error(format!(
+                "读取 g2m.json 失败: {}\n路径: {}",
+                e,
+                config_path.display()
+            )))
+        }
+    };
+
+    let result = match parse_g2m_mod_config(&content) {
+        Ok(parsed) => G2MConfigValidationResult {
+            valid: true,
+            schema_version: Some(parsed.original_version),
+            migrated: parsed.original_version < G2M_CONFIG_SCHEMA_VERSION,
+            errors: Vec::new(),
+        },
+        Err((version, errors)) => G2MConfigValidationResult {
+            valid: false,
+            schema_version: version,
+            migrated: version.is_some_and(|v| v < G2M_CONFIG_SCHEMA_VERSION),
+            errors,
+        },
+    };
+    Ok(ApiResponse::success(result))
+}
+
+/// 获取 g2m.json 的 JSON Schema（Tauri 命令）
+#[tauri::command]
+pub async fn get_g2m_mod_config_schema() -> Result<ApiResponse<serde_json::Value>, String> {
+    Ok(ApiResponse::success(g2m_mod_config_schema()))
+}
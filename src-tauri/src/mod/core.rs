@@ -1,57 +1,68 @@
-use crate::game::types::{ApiResponse, G2MModConfig, UserModInstallRequest, UserModInstallResult};
-use crate::game::utils::{add_mod_to_g2m_json, detect_game_type_from_exe, read_g2m_json};
+use crate::formats::dat::{apply_mod_dat_entries, remove_mod_dat_entries, resolve_dat_entries};
+use crate::formats::cleo::check_cleo_plugins;
+use crate::formats::rw::check_asset_games;
+use crate::game::types::{
+    ApiResponse, G2MModConfig, G2MModInfo, ModInstallOptions, RequirementIssue,
+    UserModInstallPlan, UserModInstallRequest, UserModInstallResult, UserModUninstallResult,
+    UserModUpdateResult,
+};
+use crate::game::lock::GameDirLock;
+use crate::game::prerequisites::check_mod_loaders;
+use crate::game::utils::{
+    add_mod_to_g2m_json, check_mod_record, next_mod_id, read_g2m_json, save_mods_list,
+    update_mod_in_g2m_json,
+};
+use crate::mod_core::archive::{extract_mod_archive, is_archive_path, ExtractedArchive};
 use crate::mod_core::config::load_g2m_mod_config;
+use crate::mod_core::manifest::remove_installed_paths;
+use crate::mod_core::model_collisions::check_install_collisions;
+use crate::mod_core::ownership::{
+    detect_conflicts, format_conflict_report, load_ownership_index, requires_overwrite,
+    save_ownership_index,
+};
+use crate::mod_core::img_target::{
+    is_img_target, normalize_img_target, split_img_target, work_path, TargetLocation,
+};
+use crate::mod_core::options::{
+    build_option_states, carry_over_selection, resolve_option_selection, OptionSelection,
+};
+use crate::mod_core::profiles::sync_active_profile;
+use crate::mod_core::requirements::{
+    check_mod_requirements, format_requirement_report, InstallEnvironment,
+};
+use crate::mod_core::toggle::{disable_mod_files, enable_mod_files};
+use crate::mod_core::transaction::{get_mod_backup_dir, InstallTransaction};
 use crate::mod_core::utils::{
-    copy_dir_all, is_texture_or_model_directory, is_texture_or_model_file,
+    find_escaping_entry, is_texture_or_model_directory, is_texture_or_model_file, resolves_within,
+};
+use crate::mod_core::variables::{
+    detect_game_type, resolve_cleo_dir, resolve_path_variables, resolve_plugin_dir,
 };
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
-/// 检查文件冲突
-#[allow(dead_code)]
-fn check_file_conflict(dest_path: &Path) -> bool {
-    dest_path.exists()
-}
-
-/// 检查目录冲突（检查目录是否存在且非空）
-fn check_dir_conflict(dest_path: &Path) -> bool {
-    if !dest_path.exists() {
-        return false;
-    }
-    // 检查目录是否非空
-    if let Ok(entries) = fs::read_dir(dest_path) {
-        entries.count() > 0
-    } else {
-        false
-    }
-}
-
 /// 安装 MOD 到指定目录
-/// 将 MOD 文件/目录复制到用户指定的游戏目录子目录
+/// 将 MOD 文件/目录复制到用户指定的游戏目录子目录（登记到安装事务中）
 fn install_mod_to_directory(
     mod_source_path: &Path,
     game_dir: &Path,
     target_directory: &str,
     _mod_name: &str,
-    overwrite: bool,
-) -> Result<UserModInstallResult, String> {
-    let mut installed_files = Vec::new();
-    let mut created_directories = Vec::new();
+    transaction: &mut InstallTransaction,
+) -> Result<(), String> {
 
     // 构建目标目录路径（相对于游戏目录）
     let target_dir = game_dir.join(target_directory);
 
     // 确保目标目录存在
-    if !target_dir.exists() {
-        fs::create_dir_all(&target_dir).map_err(|e| {
-            format!(
-                "创建目标目录失败: {}\n目标路径: {}",
-                e,
-                target_dir.display()
-            )
-        })?;
-        created_directories.push(target_directory.to_string());
-    }
+    transaction.create_dir_all(&target_dir).map_err(|e| {
+        format!(
+            "创建目标目录失败: {}\n目标路径: {}",
+            e,
+            target_dir.display()
+        )
+    })?;
 
     // 处理源路径
     if mod_source_path.is_file() {
@@ -63,18 +74,9 @@ fn install_mod_to_directory(
 
         let dest = target_dir.join(file_name);
 
-        // 检查冲突
-        if dest.exists() && !overwrite {
-            return Err(format!(
-                "文件冲突: {}/{} 已存在，请选择是否覆盖",
-                target_directory, file_name
-            ));
-        }
-
-        fs::copy(mod_source_path, &dest)
+        transaction
+            .copy_file(mod_source_path, &dest)
             .map_err(|e| format!("复制文件失败: {}\n目标路径: {}", e, dest.display()))?;
-
-        installed_files.push(format!("{}/{}", target_directory, file_name));
     } else if mod_source_path.is_dir() {
         // 处理目录
         let dir_name = mod_source_path
@@ -84,92 +86,33 @@ fn install_mod_to_directory(
 
         let dest = target_dir.join(dir_name);
 
-        // 检查冲突（对于目录，检查是否存在且非空）
-        if check_dir_conflict(&dest) && !overwrite {
-            return Err(format!(
-                "目录冲突: {}/{} 已存在且非空，请选择是否覆盖",
-                target_directory, dir_name
-            ));
-        }
-
-        copy_dir_all(mod_source_path, &dest)
+        transaction
+            .copy_dir(mod_source_path, &dest)
             .map_err(|e| format!("复制目录失败: {}\n目标路径: {}", e, dest.display()))?;
-
-        installed_files.push(format!("{}/{}", target_directory, dir_name));
     } else {
         return Err("源路径既不是文件也不是目录".to_string());
     }
 
-    Ok(UserModInstallResult {
-        installed_files,
-        created_directories,
-    })
+    Ok(())
 }
 
 /// 自动识别并安装 MOD 文件
-/// 根据游戏目录结构和文件类型自动选择安装位置
+/// 根据游戏目录结构和文件类型自动选择安装位置（登记到安装事务中）
 fn auto_install_mod(
     mod_source_path: &Path,
     game_dir: &Path,
     mod_name: &str,
     overwrite: bool, // 是否覆盖冲突文件
-) -> Result<UserModInstallResult, String> {
-    let mut installed_files = Vec::new();
-    let mut created_directories = Vec::new();
+    transaction: &mut InstallTransaction,
+) -> Result<(), String> {
 
     // 检查游戏目录结构，确定安装位置
     let plugins_dir = game_dir.join("plugins");
-    let scripts_dir = game_dir.join("scripts");
-    let cleo_dir = game_dir.join("CLEO");
-    let cleo_lower_dir = game_dir.join("cleo"); // GTA SA 使用小写
     let cleo_plugins_dir = plugins_dir.join("CLEO"); // CLEO Redux 目录
     let modloader_dir = game_dir.join("modloader");
 
-    // 辅助函数：确定 ASI/DLL 文件的安装目录（优先级：plugins > scripts > 根目录）
-    let determine_asi_install_dir = || -> std::path::PathBuf {
-        if plugins_dir.exists() {
-            plugins_dir.clone()
-        } else if scripts_dir.exists() {
-            scripts_dir.clone()
-        } else {
-            // 默认创建并使用 plugins 目录
-            game_dir.join("plugins")
-        }
-    };
-
     // 确定 CLEO 目录位置（优先使用已存在的目录）
-    let cleo_target_dir = if cleo_dir.exists() {
-        cleo_dir.clone()
-    } else if cleo_lower_dir.exists() {
-        cleo_lower_dir.clone()
-    } else {
-        // 如果 CLEO 目录不存在，默认创建根目录下的 CLEO（GTA SA 使用小写）
-        // 检查是否有 .gtamodx 配置来确定游戏类型
-        let game_type = read_g2m_json(game_dir.to_str().unwrap_or(""))
-            .and_then(|c| c.r#type)
-            .or_else(|| {
-                // 尝试从 exe 文件名识别
-                if let Ok(entries) = fs::read_dir(game_dir) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if path.is_file() {
-                            if let Some(exe_name) = path.file_name().and_then(|n| n.to_str()) {
-                                if let Some(gt) = detect_game_type_from_exe(exe_name) {
-                                    return Some(gt);
-                                }
-                            }
-                        }
-                    }
-                }
-                None
-            });
-
-        // 根据游戏类型决定 CLEO 目录名称
-        match game_type.as_deref() {
-            Some("gtasa") => cleo_lower_dir.clone(),
-            _ => cleo_dir.clone(),
-        }
-    };
+    let cleo_target_dir = resolve_cleo_dir(game_dir);
 
     // 确定 modloader 目录位置
     let modloader_target_dir = if modloader_dir.exists() {
@@ -179,35 +122,19 @@ fn auto_install_mod(
     };
 
     // 创建必要的目录
-    if !cleo_target_dir.exists() {
-        fs::create_dir_all(&cleo_target_dir).map_err(|e| format!("创建 CLEO 目录失败: {}", e))?;
-        // 根据目录名称决定记录格式
-        let cleo_dir_name = if cleo_target_dir
-            .file_name()
-            .and_then(|n| n.to_str())
-            .map(|n| n == "cleo")
-            .unwrap_or(false)
-        {
-            "cleo"
-        } else {
-            "CLEO"
-        };
-        created_directories.push(cleo_dir_name.to_string());
-    }
+    transaction
+        .create_dir_all(&cleo_target_dir)
+        .map_err(|e| format!("创建 CLEO 目录失败: {}", e))?;
 
-    if !modloader_target_dir.exists() {
-        fs::create_dir_all(&modloader_target_dir)
-            .map_err(|e| format!("创建 modloader 目录失败: {}", e))?;
-        created_directories.push("modloader".to_string());
-    }
+    transaction
+        .create_dir_all(&modloader_target_dir)
+        .map_err(|e| format!("创建 modloader 目录失败: {}", e))?;
 
     // 在 modloader 下创建以 MOD 名称命名的目录
     let mod_modloader_dir = modloader_target_dir.join(format!("[{}]", mod_name));
-    if !mod_modloader_dir.exists() {
-        fs::create_dir_all(&mod_modloader_dir)
-            .map_err(|e| format!("创建 MOD modloader 目录失败: {}", e))?;
-        created_directories.push(format!("modloader/[{}]", mod_name));
-    }
+    transaction
+        .create_dir_all(&mod_modloader_dir)
+        .map_err(|e| format!("创建 MOD modloader 目录失败: {}", e))?;
 
     // 处理源路径
     if mod_source_path.is_file() {
@@ -237,110 +164,50 @@ fn auto_install_mod(
                     }
                 }
 
-                // 检查目标文件冲突
-                if dest.exists() && !overwrite {
-                    return Err(format!(
-                        "文件冲突: {} 已存在，请选择是否覆盖",
-                        new_file_name
-                    ));
-                }
-
-                fs::copy(mod_source_path, &dest)
+                transaction
+                    .copy_file(mod_source_path, &dest)
                     .map_err(|e| format!("复制 .cs 文件失败: {}", e))?;
-
-                let cleo_path = if cleo_target_dir
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .map(|n| n == "cleo")
-                    .unwrap_or(false)
-                {
-                    format!("cleo/{}", new_file_name)
-                } else {
-                    format!("CLEO/{}", new_file_name)
-                };
-                installed_files.push(cleo_path);
             } else if ext_lower == "js" || ext_lower == "ts" {
                 // .js/.ts 文件（CLEO Redux）复制到 plugins/CLEO 目录
                 let dest = cleo_plugins_dir.join(&new_file_name);
 
-                // 检查冲突
-                if dest.exists() && !overwrite {
-                    return Err(format!(
-                        "文件冲突: plugins/CLEO/{} 已存在，请选择是否覆盖",
-                        new_file_name
-                    ));
-                }
-
-                fs::copy(mod_source_path, &dest)
+                transaction
+                    .copy_file(mod_source_path, &dest)
                     .map_err(|e| format!("复制 .js/.ts 文件失败: {}", e))?;
-                installed_files.push(format!("plugins/CLEO/{}", new_file_name));
             } else if ext_lower == "asi" || ext_lower == "dll" {
-                // ASI/DLL 文件：优先安装到 plugins，没有则使用 scripts，都没有则安装到根目录
-                let asi_dest_dir = determine_asi_install_dir();
+                // ASI/DLL 文件：优先安装到 plugins，没有则使用 scripts，都没有则创建 plugins
+                let asi_dest_dir = resolve_plugin_dir(game_dir);
 
                 // 确保目标目录存在
-                if !asi_dest_dir.exists() {
-                    fs::create_dir_all(&asi_dest_dir)
-                        .map_err(|e| format!("创建 ASI/DLL 安装目录失败: {}", e))?;
-                    let dir_name = if asi_dest_dir == plugins_dir {
-                        "plugins"
-                    } else if asi_dest_dir == scripts_dir {
-                        "scripts"
-                    } else {
-                        "" // 根目录
-                    };
-                    if !dir_name.is_empty() && !created_directories.contains(&dir_name.to_string())
-                    {
-                        created_directories.push(dir_name.to_string());
-                    }
-                }
+                transaction
+                    .create_dir_all(&asi_dest_dir)
+                    .map_err(|e| format!("创建 ASI/DLL 安装目录失败: {}", e))?;
 
                 // ASI/DLL 文件不使用 [MOD名称] 前缀，直接使用原始文件名
-                let dest = if asi_dest_dir == plugins_dir || asi_dest_dir == scripts_dir {
-                    // 安装到 plugins 或 scripts 目录
-                    asi_dest_dir.join(file_name)
-                } else {
-                    // 安装到根目录
-                    game_dir.join(file_name)
-                };
-
-                // 检查冲突
-                if dest.exists() && !overwrite {
-                    return Err(format!(
-                        "文件冲突: {} 已存在，请选择是否覆盖",
-                        dest.display()
-                    ));
-                }
+                let dest = asi_dest_dir.join(file_name);
 
-                fs::copy(mod_source_path, &dest)
+                transaction
+                    .copy_file(mod_source_path, &dest)
                     .map_err(|e| format!("复制 .asi/.dll 文件失败: {}", e))?;
-
-                // 记录安装路径（使用原始文件名，不带 [MOD名称] 前缀）
-                if asi_dest_dir == plugins_dir {
-                    installed_files.push(format!("plugins/{}", file_name));
-                } else if asi_dest_dir == scripts_dir {
-                    installed_files.push(format!("scripts/{}", file_name));
-                } else {
-                    // 根目录，直接使用文件名
-                    installed_files.push(file_name.to_string());
-                }
             } else if is_texture_or_model_file(mod_source_path) {
                 // 贴图/模型文件复制到 modloader 目录
                 let dest = mod_modloader_dir.join(&new_file_name);
-                fs::copy(mod_source_path, &dest)
+                transaction
+                    .copy_file(mod_source_path, &dest)
                     .map_err(|e| format!("复制贴图/模型文件失败: {}", e))?;
-                installed_files.push(format!("modloader/[{}]/{}", mod_name, new_file_name));
             } else {
                 // 其他文件也复制到 modloader 目录
                 let dest = mod_modloader_dir.join(&new_file_name);
-                fs::copy(mod_source_path, &dest).map_err(|e| format!("复制文件失败: {}", e))?;
-                installed_files.push(format!("modloader/[{}]/{}", mod_name, new_file_name));
+                transaction
+                    .copy_file(mod_source_path, &dest)
+                    .map_err(|e| format!("复制文件失败: {}", e))?;
             }
         } else {
             // 没有扩展名的文件，复制到 modloader 目录
             let dest = mod_modloader_dir.join(&new_file_name);
-            fs::copy(mod_source_path, &dest).map_err(|e| format!("复制文件失败: {}", e))?;
-            installed_files.push(format!("modloader/[{}]/{}", mod_name, new_file_name));
+            transaction
+                .copy_file(mod_source_path, &dest)
+                .map_err(|e| format!("复制文件失败: {}", e))?;
         }
     } else if mod_source_path.is_dir() {
         // 处理目录
@@ -355,9 +222,9 @@ fn auto_install_mod(
         if is_texture_or_model_directory(mod_source_path) {
             // 复制到 modloader 目录
             let dest = mod_modloader_dir.join(&new_dir_name);
-            copy_dir_all(mod_source_path, &dest)
+            transaction
+                .copy_dir(mod_source_path, &dest)
                 .map_err(|e| format!("复制贴图/模型目录失败: {}", e))?;
-            installed_files.push(format!("modloader/[{}]/{}", mod_name, new_dir_name));
         } else {
             // 检查目录中是否有 .cs 文件
             let mut has_cs_files = false;
@@ -386,69 +253,106 @@ fn auto_install_mod(
                 // 包含 .cs 文件，复制到 CLEO 目录
                 let dest = cleo_target_dir.join(&new_dir_name);
 
-                // 检查冲突（对于目录，检查是否存在且非空）
-                if check_dir_conflict(&dest) && !overwrite {
-                    return Err(format!(
-                        "目录冲突: CLEO/{} 已存在且非空，请选择是否覆盖",
-                        new_dir_name
-                    ));
-                }
-
-                copy_dir_all(mod_source_path, &dest)
+                transaction
+                    .copy_dir(mod_source_path, &dest)
                     .map_err(|e| format!("复制 CLEO 目录失败: {}", e))?;
-
-                let cleo_path = if cleo_target_dir
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .map(|n| n == "cleo")
-                    .unwrap_or(false)
-                {
-                    format!("cleo/{}", new_dir_name)
-                } else {
-                    format!("CLEO/{}", new_dir_name)
-                };
-                installed_files.push(cleo_path);
             } else if has_js_ts_files {
                 // 包含 .js/.ts 文件（CLEO Redux），复制到 plugins/CLEO 目录
                 let dest = cleo_plugins_dir.join(&new_dir_name);
 
-                // 检查冲突（对于目录，检查是否存在且非空）
-                if check_dir_conflict(&dest) && !overwrite {
-                    return Err(format!(
-                        "目录冲突: plugins/CLEO/{} 已存在且非空，请选择是否覆盖或重命名",
-                        new_dir_name
-                    ));
-                }
-
-                copy_dir_all(mod_source_path, &dest)
+                transaction
+                    .copy_dir(mod_source_path, &dest)
                     .map_err(|e| format!("复制 CLEO Redux 目录失败: {}", e))?;
-                installed_files.push(format!("plugins/CLEO/{}", new_dir_name));
             } else {
                 // 其他情况，复制到 modloader 目录
                 let dest = mod_modloader_dir.join(&new_dir_name);
-                copy_dir_all(mod_source_path, &dest).map_err(|e| format!("复制目录失败: {}", e))?;
-                installed_files.push(format!("modloader/[{}]/{}", mod_name, new_dir_name));
+                transaction
+                    .copy_dir(mod_source_path, &dest)
+                    .map_err(|e| format!("复制目录失败: {}", e))?;
             }
         }
     } else {
         return Err("源路径既不是文件也不是目录".to_string());
     }
 
-    Ok(UserModInstallResult {
-        installed_files,
-        created_directories,
-    })
+    Ok(())
+}
+
+/// 检查 g2m.json 条目在磁盘上解析（含符号链接）后是否仍在 MOD 根目录和游戏目录内
+fn check_mod_file_entries_on_disk(
+    mod_root: &Path,
+    game_dir: &Path,
+    config: &G2MModConfig,
+) -> Result<(), String> {
+    let mut problems = Vec::new();
+
+    for (index, entry) in config.modfile.iter().enumerate() {
+        let source_path = mod_root.join(&entry.source);
+        if source_path.exists() {
+            if !resolves_within(&source_path, mod_root) {
+                problems.push(format!(
+                    "第 {} 项 source \"{}\": 指向 MOD 目录之外",
+                    index + 1,
+                    entry.source
+                ));
+            } else if let Some(escaping) = find_escaping_entry(&source_path, mod_root) {
+                problems.push(format!(
+                    "第 {} 项 source \"{}\": 包含指向 MOD 目录之外的链接 {}",
+                    index + 1,
+                    entry.source,
+                    escaping.display()
+                ));
+            }
+        }
+
+        if is_img_target(&entry.target) {
+            let (archive, _) = split_img_target(&entry.target);
+            let archive_path = game_dir.join(&archive);
+            if !archive_path.is_file() {
+                problems.push(format!(
+                    "第 {} 项 target \"{}\": 游戏目录中没有 IMG 档案 {}",
+                    index + 1,
+                    entry.target,
+                    archive
+                ));
+            } else if !resolves_within(&archive_path, game_dir) {
+                problems.push(format!(
+                    "第 {} 项 target \"{}\": 指向游戏目录之外",
+                    index + 1,
+                    entry.target
+                ));
+            }
+            continue;
+        }
+
+        match resolve_path_variables(game_dir, &entry.target) {
+            Ok(target) if !resolves_within(&game_dir.join(&target), game_dir) => {
+                problems.push(format!(
+                    "第 {} 项 target \"{}\": 指向游戏目录之外",
+                    index + 1,
+                    entry.target
+                ));
+            }
+            Ok(_) => {}
+            Err(e) => problems.push(format!("第 {} 项 target: {}", index + 1, e)),
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("g2m.json 包含不安全的路径:\n{}", problems.join("\n")))
+    }
 }
 
 /// 根据 g2m.json 配置安装 MOD
-/// 严格按照配置文件中的 modfile 列表，将指定文件复制到指定目录
+/// 严格按照配置文件中的 modfile 列表，将指定文件复制到指定目录（登记到安装事务中）
 fn install_mod_with_config(
     mod_source_path: &Path,
     game_dir: &Path,
     config: &G2MModConfig,
-) -> Result<UserModInstallResult, String> {
-    let mut installed_files = Vec::new();
-    let mut created_directories = Vec::new();
+    transaction: &mut InstallTransaction,
+) -> Result<(), String> {
 
     // 确定 MOD 根目录
     let mod_root = if mod_source_path.is_file() {
@@ -457,6 +361,10 @@ fn install_mod_with_config(
         mod_source_path
     };
 
+    // 先检查所有条目，避免 g2m.json 通过符号链接读取 MOD 之外的文件或写到游戏目录之外
+    // （路径格式已在读取配置时校验）
+    check_mod_file_entries_on_disk(mod_root, game_dir, config)?;
+
     println!("开始安装 MOD: {}", config.name);
     println!("MOD 根目录: {}", mod_root.display());
     println!("游戏目录: {}", game_dir.display());
@@ -479,8 +387,30 @@ fn install_mod_with_config(
             return Err(error_msg);
         }
 
-        // 构建目标路径（相对于游戏目录）
-        let target_path = game_dir.join(&file_entry.target);
+        // 写入 IMG 档案的条目
+        if is_img_target(&file_entry.target) {
+            let target = normalize_img_target(&file_entry.target)
+                .map_err(|e| format!("目标路径无效: {}\n目标路径: {}", e, file_entry.target))?;
+            println!(
+                "[{}/{}] 写入 IMG: {} -> {}",
+                index + 1,
+                config.modfile.len(),
+                source_path.display(),
+                target
+            );
+            transaction.copy_into_img(&source_path, &target).map_err(|e| {
+                format!(
+                    "复制文件失败\n源路径: {}\n目标路径: {}\n错误: {}",
+                    source_path.display(),
+                    target,
+                    e
+                )
+            })?;
+            continue;
+        }
+
+        // 构建目标路径（相对于游戏目录，解析 ${cleo} 等路径变量）
+        let target_path = game_dir.join(resolve_path_variables(game_dir, &file_entry.target)?);
 
         println!(
             "[{}/{}] 复制: {} -> {}",
@@ -492,23 +422,15 @@ fn install_mod_with_config(
 
         // 确保目标目录存在
         if let Some(parent) = target_path.parent() {
-            if !parent.exists() {
-                fs::create_dir_all(parent).map_err(|e| {
-                    format!("创建目标目录失败: {}\n目标路径: {}", e, parent.display())
-                })?;
-                if let Ok(parent_str) = parent.strip_prefix(game_dir) {
-                    let dir_str = parent_str.to_string_lossy().replace('\\', "/");
-                    if !created_directories.contains(&dir_str) {
-                        created_directories.push(dir_str);
-                    }
-                }
-            }
+            transaction.create_dir_all(parent).map_err(|e| {
+                format!("创建目标目录失败: {}\n目标路径: {}", e, parent.display())
+            })?;
         }
 
         // 根据文件类型复制
         if file_entry.is_directory {
             // 复制目录
-            copy_dir_all(&source_path, &target_path).map_err(|e| {
+            transaction.copy_dir(&source_path, &target_path).map_err(|e| {
                 format!(
                     "复制目录失败\n源路径: {}\n目标路径: {}\n错误: {}",
                     source_path.display(),
@@ -516,10 +438,9 @@ fn install_mod_with_config(
                     e
                 )
             })?;
-            println!("  ✓ 目录复制成功");
         } else {
             // 复制文件
-            fs::copy(&source_path, &target_path).map_err(|e| {
+            transaction.copy_file(&source_path, &target_path).map_err(|e| {
                 format!(
                     "复制文件失败\n源路径: {}\n目标路径: {}\n错误: {}",
                     source_path.display(),
@@ -527,18 +448,10 @@ fn install_mod_with_config(
                     e
                 )
             })?;
-            println!("  ✓ 文件复制成功");
         }
-
-        installed_files.push(file_entry.target.clone());
     }
 
-    println!("MOD 安装完成，共安装 {} 个文件/目录", installed_files.len());
-
-    Ok(UserModInstallResult {
-        installed_files,
-        created_directories,
-    })
+    Ok(())
 }
 
 fn classify_install_type(path: &str) -> Option<String> {
@@ -696,12 +609,14 @@ fn convert_path_to_variable_format(path: &str, r#type: Option<&String>) -> Optio
     }
 }
 
-fn summarize_install_metadata(result: &UserModInstallResult) -> (Option<String>, Option<String>) {
-    let primary = result
-        .installed_files
+fn summarize_install_metadata(
+    installed_files: &[String],
+    created_directories: &[String],
+) -> (Option<String>, Option<String>) {
+    let primary = installed_files
         .first()
         .cloned()
-        .or_else(|| result.created_directories.first().cloned());
+        .or_else(|| created_directories.first().cloned());
     let normalized = primary.as_ref().map(|path| path.replace('\\', "/"));
     let r#type = normalized
         .as_ref()
@@ -715,11 +630,18 @@ fn summarize_install_metadata(result: &UserModInstallResult) -> (Option<String>,
     (r#type, variable_path)
 }
 
-/// 安装用户 MOD
-#[tauri::command]
-pub async fn install_user_mod(
-    request: UserModInstallRequest,
-) -> Result<ApiResponse<UserModInstallResult>, String> {
+/// 已登记全部文件操作、尚未提交的安装
+struct PreparedInstall {
+    transaction: InstallTransaction,
+    mod_name: String,
+    mod_author: Option<String>,
+    config: Option<G2MModConfig>,        // 按 g2m.json 安装时的配置
+    selected_options: BTreeMap<String, Vec<String>>, // 实际安装的可选组件
+    _archive: Option<ExtractedArchive>, // 压缩包的临时解压目录，提交前需保持存在
+}
+
+/// 验证安装请求中的 MOD 源路径和游戏目录
+fn validate_install_request(request: &UserModInstallRequest) -> Result<(), String> {
     let mod_source_path = Path::new(&request.mod_source_path);
     let game_dir = Path::new(&request.game_dir);
     let mod_source_path_str = mod_source_path.to_string_lossy().to_string();
@@ -727,17 +649,17 @@ pub async fn install_user_mod(
 
     // 验证路径
     if !mod_source_path.exists() {
-        return Ok(ApiResponse::error(format!(
+        return Err(format!(
             "MOD 源路径不存在\n路径: {}\nMOD名称: {}",
             mod_source_path_str, request.mod_name
-        )));
+        ));
     }
 
     if !game_dir.exists() || !game_dir.is_dir() {
-        return Ok(ApiResponse::error(format!(
+        return Err(format!(
             "游戏目录不存在或不是有效目录\n游戏目录: {}\nMOD名称: {}",
             game_dir_str, request.mod_name
-        )));
+        ));
     }
 
     // 检查 MOD 源路径是否在游戏目录内（会导致无限递归）
@@ -755,46 +677,79 @@ pub async fn install_user_mod(
         };
 
     if is_inside_game_dir {
-        return Ok(ApiResponse::error(format!(
+        return Err(format!(
             "MOD 源路径不能位于游戏目录内，这会导致无限递归\nMOD源路径: {}\n游戏目录: {}\n\n请将 MOD 文件移动到游戏目录外的位置后再安装",
             mod_source_path_str, game_dir_str
-        )));
+        ));
     }
 
+    Ok(())
+}
+
+/// 生成安装事务：按 g2m.json、指定目录或自动识别登记文件操作，不修改游戏目录
+/// 压缩包会先解压到临时目录，从其中的 MOD 根目录安装
+/// previous_selection 为更新时沿用的可选组件选择（此时忽略 request.selected_options）
+fn prepare_install(
+    request: &UserModInstallRequest,
+    mod_id: u32,
+    env: &InstallEnvironment,
+    previous_selection: Option<&OptionSelection>,
+) -> Result<PreparedInstall, String> {
+    let game_dir = Path::new(&request.game_dir);
+    let mod_source_path_str = request.mod_source_path.clone();
+    let game_dir_str = game_dir.to_string_lossy().to_string();
+
+    let archive = if is_archive_path(Path::new(&request.mod_source_path)) {
+        Some(extract_mod_archive(Path::new(&request.mod_source_path))?)
+    } else {
+        None
+    };
+    let mod_source_path = archive
+        .as_ref()
+        .map_or(Path::new(&request.mod_source_path), |a| a.mod_root());
+
     // 检查是否有 g2m.json 配置文件
-    let config = load_g2m_mod_config(mod_source_path);
+    // 配置文件无效时直接报错，不再退回到自动安装
+    let config = load_g2m_mod_config(mod_source_path).map_err(|e| {
+        format!(
+            "{}\nMOD名称: {}\n源路径: {}",
+            e, request.mod_name, mod_source_path_str
+        )
+    })?;
+    let mut transaction = InstallTransaction::new(game_dir, mod_id);
+    let mut selected_options = BTreeMap::new();
+
+    let (mod_name, mod_author) = if let Some(ref config) = config {
+        // 有 g2m.json：按选择的可选组件确定文件列表，再执行文件复制操作
+        let carried = previous_selection.map(|previous| carry_over_selection(config, previous));
+        let requested = carried.as_ref().or(request.selected_options.as_ref());
+        let (entries, selection) = resolve_option_selection(config, requested, env).map_err(
+            |e| format!("选择的可选组件无效\nMOD名称: {}\n错误详情: {}", config.name, e),
+        )?;
+        selected_options = selection;
+        let effective = G2MModConfig {
+            modfile: entries,
+            ..config.clone()
+        };
 
-    let result = if let Some(config) = config {
-        // 有 g2m.json：直接读取配置并执行文件复制操作
-        let install_result =
-            install_mod_with_config(mod_source_path, game_dir, &config).map_err(|e| {
+        install_mod_with_config(mod_source_path, game_dir, &effective, &mut transaction).map_err(
+            |e| {
                 format!(
                     "使用配置安装 MOD 失败\nMOD名称: {}\n源路径: {}\n游戏目录: {}\n错误详情: {}",
                     config.name, mod_source_path_str, game_dir_str, e
                 )
-            })?;
+            },
+        )?;
 
-        // 记录 MOD 到 .gtamodx/mods.json
-        let (r#type, install_hint) = summarize_install_metadata(&install_result);
-        if let Err(e) = add_mod_to_g2m_json(
-            &request.game_dir,
-            config.name.clone(),
-            config.author.clone(),
-            r#type,
-            install_hint,
-        ) {
-            eprintln!("警告: 无法将 MOD 记录到 .gtamodx/mods.json: {}", e);
-        }
-
-        install_result
+        (config.name.clone(), config.author.clone())
     } else if let Some(ref target_dir) = request.target_directory {
         // 没有 g2m.json，但用户指定了目标目录：安装到指定目录
-        let install_result = install_mod_to_directory(
+        install_mod_to_directory(
             mod_source_path,
             game_dir,
             target_dir,
             &request.mod_name,
-            request.overwrite,
+            &mut transaction,
         )
         .map_err(|e| {
             format!(
@@ -803,26 +758,16 @@ pub async fn install_user_mod(
             )
         })?;
 
-        // 记录 MOD 到 .gtamodx/mods.json（没有 author 信息）
-        let (r#type, install_hint) = summarize_install_metadata(&install_result);
-        if let Err(e) = add_mod_to_g2m_json(
-            &request.game_dir,
-            request.mod_name.clone(),
-            None,
-            r#type,
-            install_hint,
-        ) {
-            eprintln!("警告: 无法将 MOD 记录到 .gtamodx/mods.json: {}", e);
-        }
-
-        install_result
+        // 没有 author 信息
+        (request.mod_name.clone(), None)
     } else {
         // 没有 g2m.json，也没有指定目标目录：自动检测文件后缀
-        let install_result = auto_install_mod(
+        auto_install_mod(
             mod_source_path,
             game_dir,
             &request.mod_name,
             request.overwrite,
+            &mut transaction,
         )
         .map_err(|e| {
             format!(
@@ -831,20 +776,680 @@ pub async fn install_user_mod(
             )
         })?;
 
-        // 记录 MOD 到 .gtamodx/mods.json（没有 author 信息）
-        let (r#type, install_hint) = summarize_install_metadata(&install_result);
-        if let Err(e) = add_mod_to_g2m_json(
-            &request.game_dir,
-            request.mod_name.clone(),
-            None,
-            r#type,
-            install_hint,
-        ) {
-            eprintln!("警告: 无法将 MOD 记录到 .gtamodx/mods.json: {}", e);
+        // 没有 author 信息
+        (request.mod_name.clone(), None)
+    };
+
+    Ok(PreparedInstall {
+        transaction,
+        mod_name,
+        mod_author,
+        config,
+        selected_options,
+        _archive: archive,
+    })
+}
+
+/// 检测安装环境：游戏类型和已安装的加载器
+async fn load_install_environment(game_dir: &str) -> InstallEnvironment {
+    InstallEnvironment {
+        game_type: detect_game_type(Path::new(game_dir)),
+        loader_status: check_mod_loaders(game_dir.to_string(), None)
+            .await
+            .ok()
+            .and_then(|response| response.data),
+    }
+}
+
+/// 检查 g2m.json 的依赖声明（没有配置文件时没有任何限制）
+fn check_requirements(
+    config: Option<&G2MModConfig>,
+    mods: &[G2MModInfo],
+    env: &InstallEnvironment,
+) -> Vec<RequirementIssue> {
+    config.map_or_else(Vec::new, |config| check_mod_requirements(config, mods, env))
+}
+
+/// 安装用户 MOD
+#[tauri::command]
+pub async fn install_user_mod(
+    request: UserModInstallRequest,
+) -> Result<ApiResponse<UserModInstallResult>, String> {
+    if let Err(e) = validate_install_request(&request) {
+        return Ok(ApiResponse::error(e));
+    }
+
+    let game_dir = Path::new(&request.game_dir);
+    let _lock = match GameDirLock::acquire(game_dir, "安装 MOD") {
+        Ok(lock) => lock,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let mod_source_path_str = request.mod_source_path.clone();
+    let game_dir_str = request.game_dir.clone();
+
+    // 预留 MOD ID，安装时被覆盖的原文件备份到 .gtamodx/backup/<id>/
+    let mod_id = next_mod_id(&request.game_dir);
+    let env = load_install_environment(&request.game_dir).await;
+    let PreparedInstall {
+        transaction,
+        mod_name,
+        mod_author,
+        config,
+        selected_options,
+        _archive,
+    } = prepare_install(&request, mod_id, &env, None)?;
+    let has_config = config.is_some();
+
+    let mods = read_g2m_json(&request.game_dir)
+        .map(|c| c.mods)
+        .unwrap_or_default();
+
+    // 复制前检查 g2m.json 的依赖声明：游戏类型、前置加载器、依赖和冲突的 MOD
+    let requirement_issues = check_requirements(config.as_ref(), &mods, &env);
+    if requirement_issues.iter().any(|i| i.blocking) && !request.ignore_requirements {
+        return Err(format!(
+            "MOD 的依赖条件不满足\nMOD名称: {}\n{}",
+            mod_name,
+            format_requirement_report(&requirement_issues)
+        ));
+    }
+
+    // 复制前检查冲突：目标文件已被其他 MOD 占用或是原版文件
+    // g2m.json 中的目标由 MOD 作者指定，可以覆盖原版文件；
+    // 自动安装或覆盖其他 MOD 的文件时需要用户确认
+    let mut ownership = load_ownership_index(game_dir);
+    let conflicts = detect_conflicts(game_dir, &ownership, &mods, &transaction.planned_files());
+    if requires_overwrite(&conflicts, has_config, request.overwrite, mod_id) {
+        return Err(format!(
+            "文件冲突，请选择是否覆盖\nMOD名称: {}\n冲突文件:\n{}",
+            mod_name,
+            format_conflict_report(&conflicts)
+        ));
+    }
+
+    // 新安装的 IDE 文件与已有定义的模型 ID 重复时只提示，不阻止安装
+    let model_collisions = check_install_collisions(
+        game_dir,
+        &mods,
+        mod_id,
+        &mod_name,
+        &transaction.planned_copies(),
+    );
+
+    // TXD/DFF 的 RenderWare 版本与当前游戏不符时只提示，不阻止安装
+    let asset_mismatches =
+        check_asset_games(env.game_type.as_deref(), &transaction.planned_copies());
+
+    // CLEO 脚本需要的插件未安装时只提示，不阻止安装
+    let missing_cleo_plugins = check_cleo_plugins(
+        game_dir,
+        env.game_type.as_deref(),
+        &transaction.planned_copies(),
+    );
+
+    // MOD 记录在文件就位后写入 mods.json；先检查记录能否写入，避免文件写入后才发现 ID 或名称冲突
+    let (r#type, install_hint) = summarize_install_metadata(
+        &transaction.planned_files(),
+        &transaction.planned_directories(),
+    );
+    let mut mod_info = G2MModInfo {
+        id: mod_id,
+        name: mod_name.clone(),
+        author: mod_author,
+        version: config.as_ref().and_then(|c| c.version.clone()),
+        r#type,
+        install_path: install_hint,
+        installed_files: Vec::new(),
+        created_directories: Vec::new(),
+        backed_up_files: Vec::new(),
+        dat_entries: Vec::new(),
+        text_changes: Vec::new(),
+        modloader_priority: None,
+        modloader_ignored: false,
+        enabled: true,
+        selected_options: selected_options.clone(),
+    };
+    if let Err(e) = check_mod_record(&request.game_dir, &mod_info) {
+        return Err(format!(
+            "无法记录 MOD\nMOD名称: {}\n错误详情: {}",
+            mod_name, e
+        ));
+    }
+
+    // 写入 g2m.json 声明的 .dat 行（带 MOD 标记），文件安装失败时一并删除
+    let dat_entries = apply_mod_dat_entries(
+        game_dir,
+        mod_id,
+        config.as_ref().map_or(&[], |c| c.dat_entries.as_slice()),
+    )
+    .map_err(|e| format!("写入 .dat 文件失败\nMOD名称: {}\n错误详情: {}", mod_name, e))?;
+
+    // 提交安装事务：任一文件失败或 MOD 记录写入失败都会回滚，游戏目录恢复到安装前的状态
+    mod_info.dat_entries = dat_entries.clone();
+    let record = |result: &UserModInstallResult| {
+        let mod_info = G2MModInfo {
+            installed_files: result.installed_files.clone(),
+            created_directories: result.created_directories.clone(),
+            backed_up_files: result.backed_up_files.clone(),
+            ..mod_info
+        };
+        add_mod_to_g2m_json(&request.game_dir, mod_info)
+            .map_err(|e| format!("无法将 MOD 记录到 .gtamodx/mods.json: {}", e))
+    };
+    let mut result = transaction.commit_with(record).map_err(|e| {
+        let _ = remove_mod_dat_entries(game_dir, mod_id, &dat_entries);
+        format!(
+            "安装 MOD 失败\nMOD名称: {}\n源路径: {}\n游戏目录: {}\n错误详情: {}",
+            mod_name, mod_source_path_str, game_dir_str, e
+        )
+    })?;
+    println!("MOD 安装完成，共安装 {} 个文件", result.installed_files.len());
+    result.conflicts = conflicts;
+    result.requirement_issues = requirement_issues;
+    result.selected_options = selected_options;
+    if !model_collisions.is_empty() {
+        eprintln!(
+            "警告: MOD \"{}\" 的 IDE 文件与已有定义重复的模型 ID: {:?}",
+            mod_name,
+            model_collisions.iter().map(|c| c.id).collect::<Vec<_>>()
+        );
+    }
+    result.model_collisions = model_collisions;
+    for mismatch in &asset_mismatches {
+        eprintln!(
+            "警告: MOD \"{}\" 的 {} 为 RenderWare {}（适用于 {}），与当前游戏不符",
+            mod_name,
+            mismatch.file,
+            mismatch.rw_version,
+            mismatch.games.join("、")
+        );
+    }
+    result.asset_mismatches = asset_mismatches;
+    for missing in &missing_cleo_plugins {
+        eprintln!(
+            "警告: MOD \"{}\" 的 {} 需要 CLEO 插件 {}（指令 {}），游戏目录中没有该插件",
+            mod_name,
+            missing.file,
+            missing.plugin,
+            missing.opcodes.join("、")
+        );
+    }
+    result.missing_cleo_plugins = missing_cleo_plugins;
+
+    // 新安装的 MOD 成为所写入文件的所有者
+    ownership.claim(mod_id, &result.installed_files);
+    if let Err(e) = save_ownership_index(game_dir, &ownership) {
+        eprintln!("警告: 无法更新 .gtamodx/ownership.json: {}", e);
+    }
+    sync_active_profile(Path::new(&request.game_dir)).await;
+
+    Ok(ApiResponse::success(result))
+}
+
+/// 预览 MOD 安装计划
+/// 与 install_user_mod 使用相同的安装逻辑，但只返回将要执行的操作，不修改游戏目录
+#[tauri::command]
+pub async fn plan_user_mod_install(
+    request: UserModInstallRequest,
+) -> Result<ApiResponse<UserModInstallPlan>, String> {
+    if let Err(e) = validate_install_request(&request) {
+        return Ok(ApiResponse::error(e));
+    }
+
+    let game_dir = Path::new(&request.game_dir);
+    let mod_id = next_mod_id(&request.game_dir);
+    let env = load_install_environment(&request.game_dir).await;
+    let PreparedInstall {
+        transaction,
+        mod_name,
+        mod_author,
+        config,
+        selected_options,
+        _archive,
+    } = match prepare_install(&request, mod_id, &env, None) {
+        Ok(prepared) => prepared,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let has_config = config.is_some();
+
+    let planned_files = transaction.planned_files();
+    let directories_to_create = transaction.planned_directories();
+    let overwritten_files = planned_files
+        .iter()
+        .filter(|relative| TargetLocation::resolve(game_dir, relative).is_file())
+        .cloned()
+        .collect();
+
+    let mods = read_g2m_json(&request.game_dir)
+        .map(|c| c.mods)
+        .unwrap_or_default();
+    let ownership = load_ownership_index(game_dir);
+    let conflicts = detect_conflicts(game_dir, &ownership, &mods, &planned_files);
+    let (r#type, install_path) = summarize_install_metadata(&planned_files, &directories_to_create);
+    let requirement_issues = check_requirements(config.as_ref(), &mods, &env);
+    let copies = transaction.planned_copies();
+    let model_collisions = check_install_collisions(game_dir, &mods, mod_id, &mod_name, &copies);
+    let asset_mismatches = check_asset_games(env.game_type.as_deref(), &copies);
+    let missing_cleo_plugins = check_cleo_plugins(game_dir, env.game_type.as_deref(), &copies);
+    let dat_entries = match resolve_dat_entries(
+        game_dir,
+        config.as_ref().map_or(&[], |c| c.dat_entries.as_slice()),
+    ) {
+        Ok(entries) => entries,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+
+    let overwrite_required = requires_overwrite(&conflicts, has_config, request.overwrite, mod_id);
+    Ok(ApiResponse::success(UserModInstallPlan {
+        mod_name,
+        mod_author,
+        r#type,
+        install_path,
+        uses_config: has_config,
+        copies,
+        directories_to_create,
+        overwritten_files,
+        requires_overwrite: overwrite_required,
+        conflicts,
+        requirement_issues,
+        selected_options,
+        dat_entries,
+        model_collisions,
+        asset_mismatches,
+        missing_cleo_plugins,
+    }))
+}
+
+/// 获取 MOD 的可选组件列表（按当前游戏计算可用性和默认选择）
+/// 界面据此让用户选择组件，再把选择传给 install_user_mod
+#[tauri::command]
+pub async fn get_mod_install_options(
+    game_dir: String,
+    mod_source_path: String,
+) -> Result<ApiResponse<ModInstallOptions>, String> {
+    let source = Path::new(&mod_source_path);
+    if !source.exists() {
+        return Ok(ApiResponse::error(format!(
+            "MOD 源路径不存在\n路径: {}",
+            mod_source_path
+        )));
+    }
+
+    let archive = if is_archive_path(source) {
+        match extract_mod_archive(source) {
+            Ok(archive) => Some(archive),
+            Err(e) => return Ok(ApiResponse::error(e)),
         }
+    } else {
+        None
+    };
+    let mod_root = archive.as_ref().map_or(source, |a| a.mod_root());
+
+    let config = match load_g2m_mod_config(mod_root) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            return Ok(ApiResponse::success(ModInstallOptions {
+                mod_name: mod_root
+                    .file_stem()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                groups: Vec::new(),
+            }))
+        }
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
 
-        install_result
+    let env = load_install_environment(&game_dir).await;
+    Ok(ApiResponse::success(ModInstallOptions {
+        groups: build_option_states(&config, &env),
+        mod_name: config.name,
+    }))
+}
+
+/// 更新已安装的 MOD
+/// 保留 MOD ID 和之前选择的可选组件：替换内容有变化的文件，删除新版本不再包含的文件并恢复其原文件
+#[tauri::command]
+pub async fn update_user_mod(
+    game_dir: String,
+    mod_id: u32,
+    new_source: String,
+) -> Result<ApiResponse<UserModUpdateResult>, String> {
+    let game_path = Path::new(&game_dir);
+    let _lock = match GameDirLock::acquire(game_path, "更新 MOD") {
+        Ok(lock) => lock,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let mut mods = read_g2m_json(&game_dir)
+        .map(|config| config.mods)
+        .unwrap_or_default();
+    let Some(mod_info) = mods.iter().find(|m| m.id == mod_id).cloned() else {
+        return Ok(ApiResponse::error("未找到指定的 MOD".to_string()));
+    };
+    if !mod_info.enabled {
+        return Ok(ApiResponse::error(format!(
+            "MOD \"{}\" 已禁用，请先启用后再更新",
+            mod_info.name
+        )));
+    }
+    if mod_info.installed_files.is_empty() {
+        return Ok(ApiResponse::error(format!(
+            "MOD \"{}\" 没有安装清单，无法更新，请卸载后重新安装",
+            mod_info.name
+        )));
+    }
+
+    let request = UserModInstallRequest {
+        game_dir: game_dir.clone(),
+        mod_source_path: new_source.clone(),
+        mod_name: mod_info.name.clone(),
+        overwrite: true,
+        target_directory: None,
+        ignore_requirements: false,
+        selected_options: None,
     };
+    if let Err(e) = validate_install_request(&request) {
+        return Ok(ApiResponse::error(e));
+    }
+
+    let env = load_install_environment(&game_dir).await;
+    let PreparedInstall {
+        mut transaction,
+        mod_name,
+        mod_author,
+        config,
+        selected_options,
+        _archive,
+    } = match prepare_install(&request, mod_id, &env, Some(&mod_info.selected_options)) {
+        Ok(prepared) => prepared,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+
+    let other_mods: Vec<G2MModInfo> = mods.iter().filter(|m| m.id != mod_id).cloned().collect();
+    let requirement_issues = check_requirements(config.as_ref(), &other_mods, &env);
+    if requirement_issues.iter().any(|i| i.blocking) {
+        return Ok(ApiResponse::error(format!(
+            "MOD 新版本的依赖条件不满足\nMOD名称: {}\n{}",
+            mod_name,
+            format_requirement_report(&requirement_issues)
+        )));
+    }
+
+    // 对比新旧文件：已被其他 MOD 覆盖的文件保持不变，内容相同的文件不再复制
+    let mut ownership = load_ownership_index(game_path);
+    let planned_files = transaction.planned_files();
+    let planned_keys: Vec<String> = planned_files.iter().map(|f| f.to_lowercase()).collect();
+    let old_keys: Vec<String> = mod_info
+        .installed_files
+        .iter()
+        .map(|f| f.to_lowercase())
+        .collect();
+
+    let kept_files: Vec<String> = planned_files
+        .iter()
+        .filter(|f| old_keys.contains(&f.to_lowercase()) && !ownership.is_owned_by(f, mod_id))
+        .cloned()
+        .collect();
+    let mut added_files = Vec::new();
+    let mut updated_files = Vec::new();
+    let mut unchanged_files = Vec::new();
+    for copy in transaction.planned_copies() {
+        if kept_files.contains(&copy.target) {
+            continue;
+        }
+        if !old_keys.contains(&copy.target.to_lowercase()) {
+            added_files.push(copy.target);
+        } else if TargetLocation::resolve(game_path, &copy.target)
+            .same_content_as(Path::new(&copy.source))
+        {
+            unchanged_files.push(copy.target);
+        } else {
+            updated_files.push(copy.target);
+        }
+    }
+    let removed_files: Vec<String> = mod_info
+        .installed_files
+        .iter()
+        .filter(|f| !planned_keys.contains(&f.to_lowercase()))
+        .filter(|f| ownership.is_owned_by(f, mod_id))
+        .cloned()
+        .collect();
+    let backup_dir = get_mod_backup_dir(game_path, mod_id);
+    let restored_files: Vec<String> = removed_files
+        .iter()
+        .filter(|f| {
+            mod_info.backed_up_files.contains(f) && backup_dir.join(work_path(f)).is_file()
+        })
+        .cloned()
+        .collect();
+
+    let previous_files: Vec<String> = mod_info
+        .installed_files
+        .iter()
+        .filter(|f| ownership.is_owned_by(f, mod_id))
+        .cloned()
+        .collect();
+    transaction.exclude_files(&kept_files);
+    transaction.exclude_files(&unchanged_files);
+    transaction.replace_previous_install(
+        &previous_files,
+        &removed_files,
+        &mod_info.backed_up_files,
+    );
+
+    // 旧版本写入的 .dat 行换成新版本声明的，更新失败时恢复
+    let new_dat_entries = config.as_ref().map_or(&[][..], |c| c.dat_entries.as_slice());
+    let dat_entries = match remove_mod_dat_entries(game_path, mod_id, &mod_info.dat_entries)
+        .and_then(|_| apply_mod_dat_entries(game_path, mod_id, new_dat_entries))
+    {
+        Ok(entries) => entries,
+        Err(e) => {
+            let _ = apply_mod_dat_entries(game_path, mod_id, &mod_info.dat_entries);
+            return Ok(ApiResponse::error(format!(
+                "更新 MOD 失败\nMOD名称: {}\n写入 .dat 文件失败: {}",
+                mod_name, e
+            )));
+        }
+    };
+
+    let result = match transaction.commit() {
+        Ok(result) => result,
+        Err(e) => {
+            let _ = remove_mod_dat_entries(game_path, mod_id, &dat_entries);
+            let _ = apply_mod_dat_entries(game_path, mod_id, &mod_info.dat_entries);
+            return Ok(ApiResponse::error(format!(
+                "更新 MOD 失败\nMOD名称: {}\n源路径: {}\n错误详情: {}",
+                mod_name, new_source, e
+            )));
+        }
+    };
+    println!(
+        "MOD 更新完成：新增 {} 个，替换 {} 个，删除 {} 个文件",
+        added_files.len(),
+        updated_files.len(),
+        removed_files.len()
+    );
+
+    // 旧版本创建、现已为空的目录一并清理（从最深的目录开始）
+    let mut created_directories = result.created_directories.clone();
+    let mut old_directories = mod_info.created_directories.clone();
+    old_directories.sort_by_key(|d| std::cmp::Reverse(d.matches('/').count()));
+    for relative in old_directories {
+        let path = game_path.join(&relative);
+        let is_empty = fs::read_dir(&path)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        if is_empty {
+            let _ = fs::remove_dir(&path);
+        }
+        if path.is_dir() && !created_directories.contains(&relative) {
+            created_directories.push(relative);
+        }
+    }
+
+    ownership.release_files(mod_id, &removed_files);
+    ownership.claim(mod_id, &result.installed_files);
+    if let Err(e) = save_ownership_index(game_path, &ownership) {
+        eprintln!("警告: 无法更新 .gtamodx/ownership.json: {}", e);
+    }
+
+    let (r#type, install_path) = summarize_install_metadata(&planned_files, &created_directories);
+    let version = config.and_then(|c| c.version);
+    let updated = G2MModInfo {
+        id: mod_id,
+        name: mod_name,
+        author: mod_author.or(mod_info.author.clone()),
+        version: version.clone(),
+        r#type: r#type.or(mod_info.r#type.clone()),
+        install_path: install_path.or(mod_info.install_path.clone()),
+        installed_files: planned_files,
+        created_directories,
+        backed_up_files: result.backed_up_files,
+        dat_entries,
+        text_changes: mod_info.text_changes.clone(),
+        modloader_priority: mod_info.modloader_priority,
+        modloader_ignored: mod_info.modloader_ignored,
+        enabled: true,
+        selected_options: selected_options.clone(),
+    };
+    if let Some(entry) = mods.iter_mut().find(|m| m.id == mod_id) {
+        *entry = updated;
+    }
+    if let Err(e) = save_mods_list(&game_dir, mods) {
+        return Ok(ApiResponse::error(format!(
+            "MOD 文件已更新，但更新 mods.json 失败: {}",
+            e
+        )));
+    }
+    sync_active_profile(game_path).await;
+
+    Ok(ApiResponse::success(UserModUpdateResult {
+        mod_id,
+        previous_version: mod_info.version,
+        version,
+        added_files,
+        updated_files,
+        unchanged_files,
+        removed_files,
+        restored_files,
+        kept_files,
+        selected_options,
+        requirement_issues,
+    }))
+}
+
+/// 卸载用户 MOD
+/// 按 .gtamodx/mods.json 中记录的安装清单删除文件，清理空目录并移除 MOD 记录
+#[tauri::command]
+pub async fn uninstall_user_mod(
+    game_dir: String,
+    mod_id: u32,
+) -> Result<ApiResponse<UserModUninstallResult>, String> {
+    let game_path = Path::new(&game_dir);
+    if !game_path.exists() || !game_path.is_dir() {
+        return Ok(ApiResponse::error("游戏目录不存在".to_string()));
+    }
+    let _lock = match GameDirLock::acquire(game_path, "卸载 MOD") {
+        Ok(lock) => lock,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+
+    let mut mods = read_g2m_json(&game_dir)
+        .map(|config| config.mods)
+        .unwrap_or_default();
+    let mod_info = match mods.iter().position(|m| m.id == mod_id) {
+        Some(index) => mods.remove(index),
+        None => return Ok(ApiResponse::error("未找到指定的 MOD".to_string())),
+    };
+    let mut ownership = load_ownership_index(game_path);
+
+    // 已禁用的 MOD 先恢复到启用状态，使文件回到清单记录的位置
+    if !mod_info.enabled {
+        if let Err(e) = enable_mod_files(game_path, &mod_info, &ownership) {
+            return Ok(ApiResponse::error(format!(
+                "卸载 MOD 失败\nMOD名称: {}\n错误详情: {}",
+                mod_info.name, e
+            )));
+        }
+    }
+
+    let result = match remove_installed_paths(game_path, &mod_info, &ownership, &mut mods) {
+        Ok(result) => result,
+        Err(e) => {
+            return Ok(ApiResponse::error(format!(
+                "卸载 MOD 失败\nMOD名称: {}\n错误详情: {}",
+                mod_info.name, e
+            )))
+        }
+    };
+
+    if let Err(e) = save_mods_list(&game_dir, mods) {
+        return Ok(ApiResponse::error(format!(
+            "MOD 文件已删除，但更新 mods.json 失败: {}",
+            e
+        )));
+    }
+
+    // 被保留的文件归还给之前写入它的 MOD
+    ownership.release(mod_id);
+    if let Err(e) = save_ownership_index(game_path, &ownership) {
+        eprintln!("警告: 无法更新 .gtamodx/ownership.json: {}", e);
+    }
+    sync_active_profile(game_path).await;
 
     Ok(ApiResponse::success(result))
 }
+
+/// 启用或禁用用户 MOD
+/// 禁用时将文件移出生效位置（不删除），启用时放回，并更新 mods.json 中的 enabled 标记
+#[tauri::command]
+pub async fn set_mod_enabled(
+    game_dir: String,
+    mod_id: u32,
+    enabled: bool,
+) -> Result<ApiResponse<G2MModInfo>, String> {
+    let game_path = Path::new(&game_dir);
+    if !game_path.exists() || !game_path.is_dir() {
+        return Ok(ApiResponse::error("游戏目录不存在".to_string()));
+    }
+    let _lock = match GameDirLock::acquire(game_path, "启用或禁用 MOD") {
+        Ok(lock) => lock,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+
+    let mut mod_info = match read_g2m_json(&game_dir)
+        .and_then(|config| config.mods.into_iter().find(|m| m.id == mod_id))
+    {
+        Some(info) => info,
+        None => return Ok(ApiResponse::error("未找到指定的 MOD".to_string())),
+    };
+
+    if mod_info.enabled == enabled {
+        return Ok(ApiResponse::success(mod_info));
+    }
+
+    let ownership = load_ownership_index(game_path);
+    let result = if enabled {
+        enable_mod_files(game_path, &mod_info, &ownership)
+    } else {
+        disable_mod_files(game_path, &mod_info, &ownership)
+    };
+    if let Err(e) = result {
+        return Ok(ApiResponse::error(format!(
+            "{} MOD 失败\nMOD名称: {}\n错误详情: {}",
+            if enabled { "启用" } else { "禁用" },
+            mod_info.name,
+            e
+        )));
+    }
+
+    mod_info.enabled = enabled;
+    if let Err(e) = update_mod_in_g2m_json(&game_dir, &mod_info) {
+        return Ok(ApiResponse::error(format!(
+            "MOD 文件已移动，但更新 mods.json 失败: {}",
+            e
+        )));
+    }
+    sync_active_profile(game_path).await;
+
+    Ok(ApiResponse::success(mod_info))
+}
@@ -0,0 +1,189 @@
+use crate::formats::img::{validate_entry_name, ImgArchive, SECTOR_SIZE};
+use crate::mod_core::transaction::move_file;
+use crate::mod_core::utils::{check_relative_path, files_identical};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 安装到 IMG 档案内的目标前缀，如 img://models/gta3.img/infernus.dff
+pub const IMG_TARGET_PREFIX: &str = "img://";
+
+/// 是否为 img:// 目标（前缀不区分大小写）
+pub fn is_img_target(target: &str) -> bool {
+    target
+        .trim()
+        .get(..IMG_TARGET_PREFIX.len())
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(IMG_TARGET_PREFIX))
+}
+
+/// 拆分 img:// 目标为（档案路径, 条目名），不做校验
+pub fn split_img_target(target: &str) -> (String, String) {
+    let rest = target.trim()[IMG_TARGET_PREFIX.len()..].replace('\\', "/");
+    match rest.rsplit_once('/') {
+        Some((archive, entry)) => (archive.to_string(), entry.to_string()),
+        None => (String::new(), rest),
+    }
+}
+
+/// 校验 img:// 目标并返回统一格式（img://<档案路径>/<条目名>）
+pub fn normalize_img_target(target: &str) -> Result<String, String> {
+    let (archive, entry) = split_img_target(target);
+    if archive.is_empty() {
+        return Err("格式应为 img://<档案路径>/<条目名>".to_string());
+    }
+    check_relative_path(&archive).map_err(|reason| format!("档案路径{}", reason))?;
+    if !archive.to_lowercase().ends_with(".img") {
+        return Err("档案路径必须是 .img 文件".to_string());
+    }
+    validate_entry_name(&entry)?;
+    Ok(format!("{}{}/{}", IMG_TARGET_PREFIX, archive, entry))
+}
+
+/// 安装记录中的路径在 .gtamodx 工作目录（暂存、备份、禁用区）中的相对位置
+/// 普通文件保持不变，IMG 条目存放在 img/<档案路径>/<条目名>
+pub fn work_path(relative: &str) -> String {
+    if is_img_target(relative) {
+        let (archive, entry) = split_img_target(relative);
+        format!("img/{}/{}", archive, entry)
+    } else {
+        relative.to_string()
+    }
+}
+
+/// 安装记录中的路径在游戏中的实际位置：磁盘上的文件或 IMG 档案中的条目
+#[derive(Debug, Clone, PartialEq)]
+pub enum TargetLocation {
+    File(PathBuf),
+    ImgEntry { archive: PathBuf, entry: String },
+}
+
+impl TargetLocation {
+    /// 解析相对游戏目录的路径（可以是 img:// 目标）
+    pub fn resolve(game_dir: &Path, relative: &str) -> Self {
+        if is_img_target(relative) {
+            let (archive, entry) = split_img_target(relative);
+            Self::ImgEntry {
+                archive: game_dir.join(archive),
+                entry,
+            }
+        } else {
+            Self::File(game_dir.join(relative))
+        }
+    }
+
+    fn open_archive(&self) -> Option<(ImgArchive, usize)> {
+        match self {
+            Self::File(_) => None,
+            Self::ImgEntry { archive, entry } => {
+                let img = ImgArchive::open(archive).ok()?;
+                let index = img.find(entry)?;
+                Some((img, index))
+            }
+        }
+    }
+
+    /// 是否存在（IMG 条目需要档案中有该条目）
+    pub fn exists(&self) -> bool {
+        match self {
+            Self::File(path) => path.exists(),
+            Self::ImgEntry { .. } => self.open_archive().is_some(),
+        }
+    }
+
+    /// 是否为已存在的文件或 IMG 条目
+    pub fn is_file(&self) -> bool {
+        match self {
+            Self::File(path) => path.is_file(),
+            Self::ImgEntry { .. } => self.exists(),
+        }
+    }
+
+    /// 是否为已存在的目录
+    pub fn is_dir(&self) -> bool {
+        matches!(self, Self::File(path) if path.is_dir())
+    }
+
+    /// 目标所在的上级目录（IMG 条目不需要创建目录）
+    pub fn parent_dir(&self) -> Option<&Path> {
+        match self {
+            Self::File(path) => path.parent(),
+            Self::ImgEntry { .. } => None,
+        }
+    }
+
+    /// 读取内容（IMG 条目按扇区大小补齐）
+    fn read(&self) -> io::Result<Vec<u8>> {
+        match self {
+            Self::File(path) => fs::read(path),
+            Self::ImgEntry { .. } => {
+                let (img, index) = self
+                    .open_archive()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, self.to_string()))?;
+                img.read_entry(index).map_err(io::Error::other)
+            }
+        }
+    }
+
+    /// 写入内容，IMG 条目已存在时替换
+    fn write(&self, data: &[u8]) -> io::Result<()> {
+        match self {
+            Self::File(path) => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(path, data)
+            }
+            Self::ImgEntry { archive, entry } => ImgArchive::open(archive)
+                .and_then(|mut img| img.put_entry(entry, data))
+                .map(|_| ())
+                .map_err(io::Error::other),
+        }
+    }
+
+    /// 删除文件或 IMG 条目
+    pub fn remove(&self) -> io::Result<()> {
+        match self {
+            Self::File(path) => fs::remove_file(path),
+            Self::ImgEntry { archive, entry } => ImgArchive::open(archive)
+                .and_then(|mut img| img.remove_entry(entry))
+                .map(|_| ())
+                .map_err(io::Error::other),
+        }
+    }
+
+    /// 内容是否与指定文件相同（IMG 条目忽略扇区补齐的零字节）
+    pub fn same_content_as(&self, file: &Path) -> bool {
+        if let Self::File(path) = self {
+            return files_identical(path, file);
+        }
+        let (Ok(current), Ok(expected)) = (self.read(), fs::read(file)) else {
+            return false;
+        };
+        current.len() as u64 == (expected.len() as u64).div_ceil(SECTOR_SIZE) * SECTOR_SIZE
+            && current.starts_with(&expected)
+            && current[expected.len()..].iter().all(|&b| b == 0)
+    }
+}
+
+impl fmt::Display for TargetLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::ImgEntry { archive, entry } => write!(f, "{} 中的 {}", archive.display(), entry),
+        }
+    }
+}
+
+/// 在文件和 IMG 条目之间移动内容，目标的上级目录不存在时自动创建
+pub fn move_location(from: &TargetLocation, to: &TargetLocation) -> io::Result<()> {
+    if let (TargetLocation::File(from), TargetLocation::File(to)) = (from, to) {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        return move_file(from, to);
+    }
+    let data = from.read()?;
+    to.write(&data)?;
+    from.remove()
+}
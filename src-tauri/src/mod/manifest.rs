@@ -0,0 +1,231 @@
+use crate::formats::dat::{apply_mod_dat_entries, remove_mod_dat_entries};
+use crate::game::types::{FileOwnershipIndex, G2MModInfo, UserModUninstallResult};
+use crate::mod_core::img_target::{move_location, work_path, TargetLocation};
+use crate::mod_core::text_merge::revert_mod_text_changes;
+use crate::mod_core::transaction::{get_mod_backup_dir, move_file, remove_mod_work_dir};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// 按安装清单删除 MOD 的文件和写入 .dat 文件的行，恢复安装时被覆盖的原文件和合并到文本表的条目，并清理安装时创建、现已为空的目录
+/// 已被其他 MOD 覆盖的文件和文本条目会保留，备份和原内容转交给当前所有者（会修改 other_mods 中的记录）
+/// 没有安装清单的旧记录会退回到 install_path（仅限真实的相对路径）
+pub fn remove_installed_paths(
+    game_dir: &Path,
+    mod_info: &G2MModInfo,
+    ownership: &FileOwnershipIndex,
+    other_mods: &mut [G2MModInfo],
+) -> Result<UserModUninstallResult, String> {
+    let mut removed_files = Vec::new();
+    let mut removed_directories = Vec::new();
+    let mut missing_files = Vec::new();
+    let mut restored_files = Vec::new();
+    let mut kept_files = Vec::new();
+    let mut failed = Vec::new();
+
+    if mod_info.installed_files.is_empty() {
+        // 旧版本记录：只有 install_path 提示
+        let legacy_path = mod_info
+            .install_path
+            .as_deref()
+            .filter(|p| !p.contains("${"))
+            .ok_or_else(|| {
+                format!(
+                    "MOD \"{}\" 没有安装清单，无法自动卸载，请手动删除相关文件",
+                    mod_info.name
+                )
+            })?;
+
+        let path = game_dir.join(legacy_path);
+        if path.is_dir() {
+            fs::remove_dir_all(&path)
+                .map_err(|e| format!("删除目录失败: {}\n路径: {}", e, path.display()))?;
+            removed_directories.push(legacy_path.to_string());
+        } else if path.exists() {
+            fs::remove_file(&path)
+                .map_err(|e| format!("删除文件失败: {}\n路径: {}", e, path.display()))?;
+            removed_files.push(legacy_path.to_string());
+        } else {
+            missing_files.push(legacy_path.to_string());
+        }
+
+        return Ok(UserModUninstallResult {
+            removed_files,
+            removed_directories,
+            missing_files,
+            restored_files,
+            kept_files,
+        });
+    }
+
+    // 先删除文件：文件被占用等原因删除失败时 MOD 仍保留在列表中，.dat 行和文本条目也应保持不变
+    for relative in &mod_info.installed_files {
+        if !ownership.is_owned_by(relative, mod_info.id) {
+            kept_files.push(relative.clone());
+            continue;
+        }
+
+        let path = TargetLocation::resolve(game_dir, relative);
+        if path.is_file() {
+            match path.remove() {
+                Ok(_) => removed_files.push(relative.clone()),
+                Err(e) => failed.push(format!("{} ({})", relative, e)),
+            }
+        } else if path.exists() {
+            // 清单中的路径已被替换为目录，不做处理
+            failed.push(format!("{} (不是文件)", relative));
+        } else {
+            missing_files.push(relative.clone());
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(format!("以下文件删除失败:\n{}", failed.join("\n")));
+    }
+
+    remove_mod_dat_entries(game_dir, mod_info.id, &mod_info.dat_entries)?;
+    if let Err(e) = revert_mod_text_changes(game_dir, mod_info, other_mods) {
+        let _ = apply_mod_dat_entries(game_dir, mod_info.id, &mod_info.dat_entries);
+        return Err(e);
+    }
+
+    // 被其他 MOD 覆盖的文件：备份转交给当前所有者
+    for relative in &kept_files {
+        if let Some(owner) = ownership
+            .owner_of(relative)
+            .and_then(|id| other_mods.iter_mut().find(|m| m.id == id))
+        {
+            if let Err(e) = hand_over_backup(game_dir, mod_info, owner, relative) {
+                eprintln!("警告: 转交备份文件失败: {} ({})", relative, e);
+            }
+        }
+    }
+
+    // 恢复安装时备份的原文件
+    let backup_dir = get_mod_backup_dir(game_dir, mod_info.id);
+    for relative in &mod_info.backed_up_files {
+        if kept_files.contains(relative) {
+            continue;
+        }
+        let backup_path = TargetLocation::File(backup_dir.join(work_path(relative)));
+        if !backup_path.is_file() {
+            continue;
+        }
+        let target = TargetLocation::resolve(game_dir, relative);
+        match move_location(&backup_path, &target) {
+            Ok(_) => restored_files.push(relative.clone()),
+            Err(e) => failed.push(format!("{} ({})", relative, e)),
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(format!(
+            "以下原文件恢复失败，备份保留在 {}:\n{}",
+            backup_dir.display(),
+            failed.join("\n")
+        ));
+    }
+    remove_mod_work_dir(&backup_dir);
+
+    // 从最深的目录开始清理，只删除空目录
+    let mut directories = mod_info.created_directories.clone();
+    directories.sort_by_key(|d| std::cmp::Reverse(d.matches('/').count()));
+    for relative in directories {
+        let path = game_dir.join(&relative);
+        if is_empty_dir(&path) && fs::remove_dir(&path).is_ok() {
+            removed_directories.push(relative);
+        }
+    }
+
+    Ok(UserModUninstallResult {
+        removed_files,
+        removed_directories,
+        missing_files,
+        restored_files,
+        kept_files,
+    })
+}
+
+/// 文件在本 MOD 之后又被 owner 覆盖：owner 的备份是本 MOD 的版本，已经过期
+/// 本 MOD 有备份时（更早的原文件）转交给 owner；没有备份说明文件由本 MOD 新建，owner 卸载时应直接删除
+fn hand_over_backup(
+    game_dir: &Path,
+    mod_info: &G2MModInfo,
+    owner: &mut G2MModInfo,
+    relative: &str,
+) -> io::Result<()> {
+    let own_backup = get_mod_backup_dir(game_dir, mod_info.id).join(work_path(relative));
+    let owner_backup = get_mod_backup_dir(game_dir, owner.id).join(work_path(relative));
+
+    if mod_info.backed_up_files.iter().any(|f| f == relative) && own_backup.is_file() {
+        if let Some(parent) = owner_backup.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        move_file(&own_backup, &owner_backup)?;
+        if !owner.backed_up_files.iter().any(|f| f == relative) {
+            owner.backed_up_files.push(relative.to_string());
+        }
+    } else {
+        if owner_backup.is_file() {
+            fs::remove_file(&owner_backup)?;
+        }
+        owner.backed_up_files.retain(|f| f != relative);
+    }
+    Ok(())
+}
+
+/// 检查目录是否存在且为空
+fn is_empty_dir(path: &Path) -> bool {
+    path.is_dir()
+        && fs::read_dir(path)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::types::DatEntry;
+
+    #[test]
+    fn failed_delete_keeps_dat_entries() {
+        let game_dir = std::env::temp_dir().join(format!("g2m-manifest-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&game_dir);
+        fs::create_dir_all(game_dir.join("data")).unwrap();
+        let original = "IDE DATA\\VANILLA.IDE\r\n";
+        fs::write(game_dir.join("data/gta.dat"), original).unwrap();
+        fs::write(game_dir.join("mod.ide"), "objs").unwrap();
+        // 清单中的路径已被替换为目录，删除会失败
+        fs::create_dir_all(game_dir.join("blocker")).unwrap();
+
+        let entries = [DatEntry {
+            file: Some("data/gta.dat".to_string()),
+            keyword: "IDE".to_string(),
+            path: "MOD.IDE".to_string(),
+        }];
+        let dat_entries = apply_mod_dat_entries(&game_dir, 5, &entries).unwrap();
+        let mut mod_info: G2MModInfo =
+            serde_json::from_str(r#"{"id": 5, "name": "Test"}"#).unwrap();
+        mod_info.installed_files = vec!["mod.ide".to_string(), "blocker".to_string()];
+        mod_info.dat_entries = dat_entries;
+        let applied = fs::read_to_string(game_dir.join("data/gta.dat")).unwrap();
+        assert!(applied.contains("MOD.IDE"));
+
+        let ownership = FileOwnershipIndex::default();
+        assert!(remove_installed_paths(&game_dir, &mod_info, &ownership, &mut []).is_err());
+        assert_eq!(
+            fs::read_to_string(game_dir.join("data/gta.dat")).unwrap(),
+            applied
+        );
+
+        // 重试时已删除的文件记为缺失，.dat 行在文件全部删除后才移除
+        fs::remove_dir(game_dir.join("blocker")).unwrap();
+        let result = remove_installed_paths(&game_dir, &mod_info, &ownership, &mut []).unwrap();
+        assert_eq!(result.missing_files, vec!["mod.ide", "blocker"]);
+        assert_eq!(
+            fs::read_to_string(game_dir.join("data/gta.dat")).unwrap(),
+            original
+        );
+        fs::remove_dir_all(&game_dir).unwrap();
+    }
+}
@@ -1,10 +1,32 @@
+pub mod archive;
 pub mod config;
 pub mod core;
+pub mod img_target;
+pub mod manifest;
+pub mod model_collisions;
+pub mod modloader;
+pub mod options;
+pub mod ownership;
+pub mod profiles;
+pub mod requirements;
+pub mod schema;
+pub mod text_merge;
+pub mod toggle;
+pub mod transaction;
 pub mod utils;
+pub mod variables;
 
-pub use config::{get_mod_file_tree, read_g2m_mod_config, save_g2m_mod_config, select_game_install_directory, select_mod_directory, select_mod_files};
+pub use config::{get_g2m_mod_config_schema, get_mod_file_tree, read_g2m_mod_config, save_g2m_mod_config, select_game_install_directory, select_mod_directory, select_mod_files, validate_g2m_mod_config};
+
+pub use model_collisions::check_model_collisions;
+
+pub use modloader::{create_modloader_profile, get_modloader_state, select_modloader_profile, set_modloader_mod_included, set_modloader_priority};
+
+pub use text_merge::merge_mod_text;
+
+pub use profiles::{clone_mod_profile, create_mod_profile, delete_mod_profile, get_mod_profiles, switch_mod_profile};
 
 // 只重新导出核心安装函数，其他工具函数不导出（避免冲突）
-pub use core::install_user_mod;
+pub use core::{get_mod_install_options, install_user_mod, plan_user_mod_install, set_mod_enabled, uninstall_user_mod, update_user_mod};
 // utils 中的函数是内部使用的，不需要导出
 
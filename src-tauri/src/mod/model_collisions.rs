@@ -0,0 +1,334 @@
+use crate::formats::dat::{find_main_dat_file, DatFile};
+use crate::formats::ide::parse_ide;
+use crate::formats::ipl::{parse_ipl, IplInstance};
+use crate::game::baseline::file_owners;
+use crate::game::types::{
+    ApiResponse, G2MModInfo, ModelCollisionReport, ModelDefinition, ModelIdCollision,
+    ModelNameCollision, PlannedFileCopy,
+};
+use crate::game::utils::read_g2m_json;
+use crate::mod_core::img_target::is_img_target;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 参与检查的 IDE 或 IPL 文件
+struct ModelSource {
+    relative: String,         // 相对游戏目录的路径（统一使用 /）
+    path: PathBuf,            // 实际读取的文件（安装前检查时为 MOD 中的源文件）
+    mod_id: Option<u32>,      // 引入该文件的 MOD
+    mod_name: Option<String>, // MOD 名称
+}
+
+fn has_extension(name: &str, extension: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+}
+
+fn file_name_lower(relative: &str) -> String {
+    relative
+        .rsplit('/')
+        .next()
+        .unwrap_or(relative)
+        .to_lowercase()
+}
+
+fn is_modloader_path(relative: &str) -> bool {
+    relative.to_lowercase().starts_with("modloader/")
+}
+
+/// 在游戏目录中查找文件，不区分大小写（gta.dat 中的路径通常为大写）
+fn find_game_file(game_dir: &Path, relative: &str) -> Option<PathBuf> {
+    let direct = game_dir.join(relative);
+    if direct.is_file() {
+        return Some(direct);
+    }
+
+    let mut current = game_dir.to_path_buf();
+    for part in relative.split('/').filter(|p| !p.is_empty()) {
+        let exact = current.join(part);
+        current = if exact.exists() {
+            exact
+        } else {
+            fs::read_dir(&current)
+                .ok()?
+                .flatten()
+                .map(|entry| entry.path())
+                .find(|path| {
+                    path.file_name()
+                        .is_some_and(|name| name.to_string_lossy().eq_ignore_ascii_case(part))
+                })?
+        };
+    }
+    current.is_file().then_some(current)
+}
+
+/// 递归收集 modloader 目录中的 IDE 和 IPL 文件（ModLoader 不加载 . 开头的目录）
+fn collect_modloader_files(dir: &Path, game_dir: &Path, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !name.starts_with('.') {
+                collect_modloader_files(&path, game_dir, files);
+            }
+        } else if has_extension(&name, "ide") || has_extension(&name, "ipl") {
+            if let Ok(relative) = path.strip_prefix(game_dir) {
+                files.push((relative.to_string_lossy().replace('\\', "/"), path));
+            }
+        }
+    }
+}
+
+/// 收集游戏会加载的 IDE 和 IPL 文件，返回（IDE 文件, IPL 文件）
+/// - 主配置文件和 data/default.dat 中 IDE、IPL 行引用的文件
+/// - modloader 目录中的文件，与原版同名时替换原版文件
+/// - planned 为即将安装的文件（相对路径, 源文件），替换相同路径的已有文件
+fn collect_sources(
+    game_dir: &Path,
+    mods: &[G2MModInfo],
+    planned: &[(String, PathBuf)],
+    new_mod: Option<(u32, &str)>,
+) -> (Vec<ModelSource>, Vec<ModelSource>) {
+    // (相对路径, 文件, 写入 .dat 行的 MOD)
+    let mut files: Vec<(String, PathBuf, Option<u32>)> = Vec::new();
+
+    let dat_files = [find_main_dat_file(game_dir), Some("data/default.dat")];
+    for dat in dat_files.into_iter().flatten() {
+        let Some(dat) = find_game_file(game_dir, dat).and_then(|p| DatFile::load(&p).ok()) else {
+            continue;
+        };
+        for entry in dat.entries() {
+            if entry.keyword != "IDE" && entry.keyword != "IPL" {
+                continue;
+            }
+            let relative = entry.path.replace('\\', "/");
+            if let Some(path) = find_game_file(game_dir, &relative) {
+                files.push((relative, path, entry.owner_mod_id));
+            }
+        }
+    }
+
+    let mut modloader_files = Vec::new();
+    collect_modloader_files(&game_dir.join("modloader"), game_dir, &mut modloader_files);
+    files.extend(modloader_files.into_iter().map(|(r, p)| (r, p, None)));
+
+    for (relative, source) in planned {
+        let key = relative.to_lowercase();
+        files.retain(|(r, _, _)| r.to_lowercase() != key);
+        files.push((relative.clone(), source.clone(), new_mod.map(|(id, _)| id)));
+    }
+
+    // modloader 中的同名文件替换原版文件；同一文件被引用多次时只检查一次
+    let overridden: Vec<String> = files
+        .iter()
+        .filter(|(r, _, _)| is_modloader_path(r))
+        .map(|(r, _, _)| file_name_lower(r))
+        .collect();
+    files.retain(|(r, _, _)| is_modloader_path(r) || !overridden.contains(&file_name_lower(r)));
+    let mut seen = Vec::new();
+    files.retain(|(r, _, _)| {
+        let key = r.to_lowercase();
+        let first = !seen.contains(&key);
+        seen.push(key);
+        first
+    });
+
+    let owners = file_owners(game_dir, mods);
+    let mut ide = Vec::new();
+    let mut ipl = Vec::new();
+    for (relative, path, dat_owner) in files {
+        let mod_id = dat_owner.or_else(|| owners(&relative));
+        let mod_name = match mod_id {
+            Some(id) => mods
+                .iter()
+                .find(|m| m.id == id)
+                .map(|m| m.name.clone())
+                .or_else(|| {
+                    new_mod
+                        .filter(|(new, _)| *new == id)
+                        .map(|(_, n)| n.to_string())
+                }),
+            // 不是由本工具安装的 modloader 子目录，以目录名作为 MOD 名称
+            None if is_modloader_path(&relative) => relative
+                .split('/')
+                .nth(1)
+                .filter(|_| relative.matches('/').count() > 1)
+                .map(str::to_string),
+            None => None,
+        };
+        let source = ModelSource {
+            relative,
+            path,
+            mod_id,
+            mod_name,
+        };
+        if has_extension(&source.relative, "ide") {
+            ide.push(source);
+        } else {
+            ipl.push(source);
+        }
+    }
+    (ide, ipl)
+}
+
+/// 读取所有 IDE 文件中的模型定义
+fn load_definitions(sources: &[ModelSource]) -> Vec<ModelDefinition> {
+    let mut definitions = Vec::new();
+    for source in sources {
+        let Ok(content) = fs::read(&source.path) else {
+            continue;
+        };
+        for definition in parse_ide(&String::from_utf8_lossy(&content)) {
+            definitions.push(ModelDefinition {
+                id: definition.id,
+                model: definition.model,
+                section: definition.section,
+                file: source.relative.clone(),
+                line: definition.line,
+                mod_id: source.mod_id,
+                mod_name: source.mod_name.clone(),
+            });
+        }
+    }
+    definitions
+}
+
+/// 读取所有 IPL 文件中放置的模型，无法解析的文件跳过
+fn load_placements(sources: &[ModelSource]) -> Vec<(String, Vec<IplInstance>)> {
+    sources
+        .iter()
+        .filter_map(|source| {
+            let data = fs::read(&source.path).ok()?;
+            let instances = parse_ipl(&data).ok()?;
+            Some((source.relative.clone(), instances))
+        })
+        .collect()
+}
+
+/// 找出重复的模型 ID 和模型名称
+fn find_collisions(
+    definitions: &[ModelDefinition],
+    placements: &[(String, Vec<IplInstance>)],
+) -> (Vec<ModelIdCollision>, Vec<ModelNameCollision>) {
+    let mut by_id: BTreeMap<u32, Vec<ModelDefinition>> = BTreeMap::new();
+    let mut by_name: BTreeMap<String, Vec<ModelDefinition>> = BTreeMap::new();
+    for definition in definitions {
+        by_id
+            .entry(definition.id)
+            .or_default()
+            .push(definition.clone());
+        by_name
+            .entry(definition.model.to_lowercase())
+            .or_default()
+            .push(definition.clone());
+    }
+
+    let id_collisions = by_id
+        .into_iter()
+        .filter(|(_, definitions)| definitions.len() > 1)
+        .map(|(id, definitions)| ModelIdCollision {
+            id,
+            ipl_files: placements
+                .iter()
+                .filter(|(_, instances)| instances.iter().any(|i| i.id == id as i32))
+                .map(|(file, _)| file.clone())
+                .collect(),
+            definitions,
+        })
+        .collect();
+    let name_collisions = by_name
+        .into_values()
+        .filter(|definitions| definitions.len() > 1)
+        .map(|definitions| {
+            let model = definitions[0].model.clone();
+            ModelNameCollision {
+                ipl_files: placements
+                    .iter()
+                    .filter(|(_, instances)| {
+                        instances.iter().any(|i| {
+                            i.model
+                                .as_deref()
+                                .is_some_and(|m| m.eq_ignore_ascii_case(&model))
+                        })
+                    })
+                    .map(|(file, _)| file.clone())
+                    .collect(),
+                model,
+                definitions,
+            }
+        })
+        .collect();
+    (id_collisions, name_collisions)
+}
+
+/// 检查即将安装的 IDE 文件是否与已有的模型 ID 重复，只返回涉及新文件的冲突
+/// 同一 MOD 之前安装的文件（更新时）不参与比较
+pub fn check_install_collisions(
+    game_dir: &Path,
+    mods: &[G2MModInfo],
+    mod_id: u32,
+    mod_name: &str,
+    copies: &[PlannedFileCopy],
+) -> Vec<ModelIdCollision> {
+    let planned: Vec<(String, PathBuf)> = copies
+        .iter()
+        .filter(|c| !is_img_target(&c.target))
+        .filter(|c| has_extension(&c.target, "ide") || has_extension(&c.target, "ipl"))
+        .map(|c| (c.target.replace('\\', "/"), PathBuf::from(&c.source)))
+        .collect();
+    if !planned
+        .iter()
+        .any(|(target, _)| has_extension(target, "ide"))
+    {
+        return Vec::new();
+    }
+
+    let (ide, ipl) = collect_sources(game_dir, mods, &planned, Some((mod_id, mod_name)));
+    let is_new = |d: &ModelDefinition| {
+        planned
+            .iter()
+            .any(|(target, _)| target.eq_ignore_ascii_case(&d.file))
+    };
+    let definitions: Vec<ModelDefinition> = load_definitions(&ide)
+        .into_iter()
+        .filter(|d| d.mod_id != Some(mod_id) || is_new(d))
+        .collect();
+
+    let (id_collisions, _) = find_collisions(&definitions, &load_placements(&ipl));
+    id_collisions
+        .into_iter()
+        .filter(|c| c.definitions.iter().any(is_new) && !c.definitions.iter().all(is_new))
+        .collect()
+}
+
+/// 检查游戏加载的所有 IDE 文件中重复的模型 ID 和模型名称（Tauri 命令）
+/// 包括 gta.dat、default.dat 引用的文件和 modloader 中的文件，并标出引入每个定义的 MOD
+#[tauri::command]
+pub async fn check_model_collisions(
+    game_dir: String,
+) -> Result<ApiResponse<ModelCollisionReport>, String> {
+    let game_path = Path::new(&game_dir);
+    if !game_path.is_dir() {
+        return Ok(ApiResponse::error("游戏目录不存在".to_string()));
+    }
+    let mods = read_g2m_json(&game_dir)
+        .map(|config| config.mods)
+        .unwrap_or_default();
+
+    let (ide, ipl) = collect_sources(game_path, &mods, &[], None);
+    let definitions = load_definitions(&ide);
+    let (id_collisions, name_collisions) = find_collisions(&definitions, &load_placements(&ipl));
+
+    Ok(ApiResponse::success(ModelCollisionReport {
+        ide_files: ide.into_iter().map(|s| s.relative).collect(),
+        ipl_files: ipl.into_iter().map(|s| s.relative).collect(),
+        definition_count: definitions.len(),
+        id_collisions,
+        name_collisions,
+    }))
+}
@@ -0,0 +1,549 @@
+use crate::formats::ini::IniFile;
+use crate::game::types::{ApiResponse, G2MModInfo, ModLoaderModEntry, ModLoaderState};
+use crate::game::utils::{read_g2m_json, save_mods_list};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const FOLDER_CONFIG: &str = "Folder.Config";
+const DEFAULT_PROFILE: &str = "Default";
+const DEFAULT_PRIORITY: u8 = 50;
+
+/// 新建方案时写入的分段（与 ModLoader 自带的 modloader.ini 相同）
+const PROFILE_SECTIONS: &[&str] = &[
+    "Config",
+    "Priority",
+    "IgnoreFiles",
+    "IgnoreMods",
+    "IncludeMods",
+    "ExclusiveMods",
+];
+
+fn get_modloader_dir(game_dir: &Path) -> PathBuf {
+    game_dir.join("modloader")
+}
+
+fn profile_section(profile: &str, kind: &str) -> String {
+    format!("Profiles.{}.{}", profile, kind)
+}
+
+/// 分段名对应的方案名称：Profiles.<方案>.<类型>
+fn section_profile(section: &str) -> Option<&str> {
+    let rest = section.strip_prefix("Profiles.")?;
+    let (profile, _) = rest.rsplit_once('.')?;
+    Some(profile).filter(|p| !p.is_empty())
+}
+
+/// MOD 目录名在 modloader.ini 中的写法
+/// ModLoader 把以 [ 开头的行当作分段名，本工具安装的 "[MOD名称]" 目录写成通配符 "?MOD名称]"
+fn ini_key(name: &str) -> String {
+    match name.strip_prefix('[') {
+        Some(rest) => format!("?{}", rest),
+        None => name.to_string(),
+    }
+}
+
+fn parse_bool(value: Option<String>) -> bool {
+    value.is_some_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+}
+
+/// 通配符匹配（* 和 ?，不区分大小写），用于 IgnoreMods 等列表
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// 当前方案合并父方案后的设置
+struct ResolvedProfile {
+    ignore_all: bool,
+    exclude_all: bool,
+    priorities: Vec<(String, u8)>,
+    ignore_mods: Vec<String>,
+    include_mods: Vec<String>,
+    exclusive_mods: Vec<String>,
+    other_exclusive_mods: Vec<String>, // 其他方案的专属 MOD，当前方案不加载
+}
+
+/// modloader.ini 和 .profiles/*.ini 中的配置；修改只写回定义该方案的文件
+struct ModLoaderConfig {
+    files: Vec<(PathBuf, IniFile)>, // 第一个为 modloader.ini
+}
+
+impl ModLoaderConfig {
+    /// 读取配置；modloader.ini 不存在时（ModLoader 尚未运行过）使用 .data/modloader.ini.0 模板
+    fn load(game_dir: &Path) -> Result<Self, String> {
+        let modloader_dir = get_modloader_dir(game_dir);
+        if !modloader_dir.is_dir() {
+            return Err("游戏目录中没有 modloader 目录，请先安装 ModLoader".to_string());
+        }
+        let main_path = modloader_dir.join("modloader.ini");
+        let template = modloader_dir.join(".data").join("modloader.ini.0");
+        let main = if main_path.is_file() {
+            IniFile::load(&main_path)?
+        } else if template.is_file() {
+            IniFile::load(&template)?
+        } else {
+            let mut ini = IniFile::parse(b"");
+            ini.set(FOLDER_CONFIG, "Profile", DEFAULT_PROFILE);
+            ini
+        };
+
+        let mut files = vec![(main_path, main)];
+        let mut extra: Vec<PathBuf> = fs::read_dir(modloader_dir.join(".profiles"))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|e| e.eq_ignore_ascii_case("ini")))
+                    .collect()
+            })
+            .unwrap_or_default();
+        extra.sort();
+        for path in extra {
+            let ini = IniFile::load(&path)?;
+            files.push((path, ini));
+        }
+        Ok(Self { files })
+    }
+
+    fn main(&mut self) -> &mut IniFile {
+        &mut self.files[0].1
+    }
+
+    fn current_profile(&self) -> String {
+        self.files[0]
+            .1
+            .get(FOLDER_CONFIG, "Profile")
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+    }
+
+    /// 所有方案名称（方案名称不区分大小写，保留第一次出现时的写法）
+    fn profiles(&self) -> Vec<String> {
+        let mut profiles: Vec<String> = Vec::new();
+        for (_, ini) in &self.files {
+            for section in ini.sections() {
+                if let Some(profile) = section_profile(&section) {
+                    if !profiles.iter().any(|p| p.eq_ignore_ascii_case(profile)) {
+                        profiles.push(profile.to_string());
+                    }
+                }
+            }
+        }
+        profiles
+    }
+
+    fn find_profile(&self, name: &str) -> Option<String> {
+        self.profiles()
+            .into_iter()
+            .find(|p| p.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// 定义该方案的文件（没有定义时为 modloader.ini）
+    fn profile_file(&mut self, profile: &str) -> &mut IniFile {
+        let index = self
+            .files
+            .iter()
+            .position(|(_, ini)| {
+                ini.sections()
+                    .iter()
+                    .any(|s| section_profile(s).is_some_and(|p| p.eq_ignore_ascii_case(profile)))
+            })
+            .unwrap_or(0);
+        &mut self.files[index].1
+    }
+
+    fn entries(&self, profile: &str, kind: &str) -> Vec<(String, Option<String>)> {
+        let section = profile_section(profile, kind);
+        self.files
+            .iter()
+            .flat_map(|(_, ini)| ini.entries(&section))
+            .collect()
+    }
+
+    fn get(&self, profile: &str, kind: &str, key: &str) -> Option<String> {
+        self.entries(profile, kind)
+            .into_iter()
+            .rev()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .and_then(|(_, value)| value)
+    }
+
+    fn list(&self, profile: &str, kind: &str) -> Vec<String> {
+        self.entries(profile, kind)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    }
+
+    /// 方案及其继承的父方案（Parents），父方案在前；visiting 用于避免循环继承
+    fn profile_chain(&self, profile: &str, visiting: &mut Vec<String>, chain: &mut Vec<String>) {
+        let seen = |list: &[String]| list.iter().any(|p| p.eq_ignore_ascii_case(profile));
+        if seen(visiting) || seen(chain) {
+            return;
+        }
+        visiting.push(profile.to_string());
+        let parents: Vec<String> = self
+            .get(profile, "Config", "Parents")
+            .unwrap_or_default()
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect();
+        if !parents.iter().any(|p| p.eq_ignore_ascii_case("$None")) {
+            for parent in parents {
+                let parent = if parent.eq_ignore_ascii_case("$Current") {
+                    self.current_profile()
+                } else {
+                    parent
+                };
+                self.profile_chain(&parent, visiting, chain);
+            }
+        }
+        chain.push(profile.to_string());
+    }
+
+    fn resolve(&self) -> ResolvedProfile {
+        let current = self.current_profile();
+        let mut chain = Vec::new();
+        self.profile_chain(&current, &mut Vec::new(), &mut chain);
+
+        let mut resolved = ResolvedProfile {
+            ignore_all: false,
+            exclude_all: false,
+            priorities: Vec::new(),
+            ignore_mods: Vec::new(),
+            include_mods: Vec::new(),
+            exclusive_mods: Vec::new(),
+            other_exclusive_mods: Vec::new(),
+        };
+        for profile in &chain {
+            let ignore_all = self
+                .get(profile, "Config", "IgnoreAllMods")
+                .or_else(|| self.get(profile, "Config", "IgnoreAllFiles"));
+            if ignore_all.is_some() {
+                resolved.ignore_all = parse_bool(ignore_all);
+            }
+            if let Some(value) = self.get(profile, "Config", "ExcludeAllMods") {
+                resolved.exclude_all = parse_bool(Some(value));
+            }
+            for (name, value) in self.entries(profile, "Priority") {
+                let Some(priority) = value.and_then(|v| v.parse::<u8>().ok()) else {
+                    continue;
+                };
+                resolved
+                    .priorities
+                    .retain(|(n, _)| !n.eq_ignore_ascii_case(&name));
+                resolved.priorities.push((name, priority.min(100)));
+            }
+            resolved
+                .ignore_mods
+                .extend(self.list(profile, "IgnoreMods"));
+            resolved
+                .include_mods
+                .extend(self.list(profile, "IncludeMods"));
+            resolved
+                .exclusive_mods
+                .extend(self.list(profile, "ExclusiveMods"));
+        }
+        for profile in self.profiles() {
+            if !chain.iter().any(|p| p.eq_ignore_ascii_case(&profile)) {
+                resolved
+                    .other_exclusive_mods
+                    .extend(self.list(&profile, "ExclusiveMods"));
+            }
+        }
+        resolved
+    }
+
+    fn save(&self) -> Result<(), String> {
+        for (path, ini) in &self.files {
+            ini.save(path)?;
+        }
+        Ok(())
+    }
+}
+
+impl ResolvedProfile {
+    /// 优先级的键也支持通配符，以最后设置的为准
+    fn priority(&self, name: &str) -> u8 {
+        self.priorities
+            .iter()
+            .rev()
+            .find(|(pattern, _)| wildcard_match(pattern, name))
+            .map_or(DEFAULT_PRIORITY, |(_, p)| *p)
+    }
+
+    /// ModLoader 不加载该 MOD 的原因，加载时为 None
+    fn ignored_by(&self, name: &str) -> Option<&'static str> {
+        let matches = |patterns: &[String]| patterns.iter().any(|p| wildcard_match(p, name));
+        if self.ignore_all {
+            Some("IgnoreAllMods")
+        } else if self.priority(name) == 0 {
+            Some("Priority")
+        } else if matches(&self.other_exclusive_mods) && !matches(&self.exclusive_mods) {
+            Some("ExclusiveMods")
+        } else if matches(&self.ignore_mods) {
+            Some("IgnoreMods")
+        } else if self.exclude_all && !matches(&self.include_mods) && !matches(&self.exclusive_mods)
+        {
+            Some("ExcludeAllMods")
+        } else {
+            None
+        }
+    }
+}
+
+/// modloader 目录中的 MOD 目录（. 开头的 .data、.profiles 等不是 MOD）
+fn list_mod_dirs(game_dir: &Path) -> Vec<String> {
+    let mut dirs: Vec<String> = fs::read_dir(get_modloader_dir(game_dir))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|name| !name.starts_with('.'))
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort_by_key(|name| name.to_lowercase());
+    dirs
+}
+
+/// 已安装 MOD 在 modloader 目录下的目录名（按安装清单或安装路径）
+fn modloader_dir_name(mod_info: &G2MModInfo) -> Option<String> {
+    mod_info
+        .installed_files
+        .iter()
+        .chain(mod_info.install_path.iter())
+        .find_map(|path| {
+            let path = path.replace('\\', "/");
+            let mut parts = path.split('/');
+            let root = parts.next()?;
+            let name = parts.next()?;
+            (root.eq_ignore_ascii_case("modloader") && !name.is_empty()).then(|| name.to_string())
+        })
+}
+
+fn find_mod_dir(game_dir: &Path, name: &str) -> Result<String, String> {
+    list_mod_dirs(game_dir)
+        .into_iter()
+        .find(|dir| dir.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| format!("modloader 目录中没有 MOD \"{}\"", name))
+}
+
+/// 计算 modloader 目录中各 MOD 的状态，并同步到已安装 MOD 的记录
+fn build_state(game_dir: &Path, config: &ModLoaderConfig) -> ModLoaderState {
+    let game_dir_str = game_dir.to_string_lossy().to_string();
+    let mut mods = read_g2m_json(&game_dir_str)
+        .map(|c| c.mods)
+        .unwrap_or_default();
+    let resolved = config.resolve();
+
+    let mut entries = Vec::new();
+    let mut changed = false;
+    for name in list_mod_dirs(game_dir) {
+        let priority = resolved.priority(&name);
+        let ignored_by = resolved.ignored_by(&name);
+        let installed = mods
+            .iter_mut()
+            .find(|m| modloader_dir_name(m).is_some_and(|dir| dir.eq_ignore_ascii_case(&name)));
+        let mod_id = installed.map(|mod_info| {
+            if mod_info.modloader_priority != Some(priority)
+                || mod_info.modloader_ignored != ignored_by.is_some()
+            {
+                mod_info.modloader_priority = Some(priority);
+                mod_info.modloader_ignored = ignored_by.is_some();
+                changed = true;
+            }
+            mod_info.id
+        });
+        entries.push(ModLoaderModEntry {
+            name,
+            priority,
+            enabled: ignored_by.is_none(),
+            ignored_by: ignored_by.map(str::to_string),
+            mod_id,
+        });
+    }
+    if changed {
+        if let Err(e) = save_mods_list(&game_dir_str, mods) {
+            eprintln!("警告: 无法更新 .gtamodx/mods.json: {}", e);
+        }
+    }
+
+    ModLoaderState {
+        profile: config.current_profile(),
+        profiles: config.profiles(),
+        mods: entries,
+    }
+}
+
+/// 修改配置并保存，返回修改后的状态
+fn update_config(
+    game_dir: &str,
+    edit: impl FnOnce(&Path, &mut ModLoaderConfig) -> Result<(), String>,
+) -> Result<ApiResponse<ModLoaderState>, String> {
+    let game_path = Path::new(game_dir);
+    let result = ModLoaderConfig::load(game_path).and_then(|mut config| {
+        edit(game_path, &mut config)?;
+        config.save()?;
+        Ok(build_state(game_path, &config))
+    });
+    match result {
+        Ok(state) => Ok(ApiResponse::success(state)),
+        Err(e) => Ok(ApiResponse::error(e)),
+    }
+}
+
+/// 获取 modloader.ini 的当前方案、所有方案和 modloader 目录中各 MOD 的优先级与加载状态
+#[tauri::command]
+pub async fn get_modloader_state(game_dir: String) -> Result<ApiResponse<ModLoaderState>, String> {
+    let game_path = Path::new(&game_dir);
+    match ModLoaderConfig::load(game_path) {
+        Ok(config) => Ok(ApiResponse::success(build_state(game_path, &config))),
+        Err(e) => Ok(ApiResponse::error(e)),
+    }
+}
+
+/// 设置 MOD 在当前方案中的优先级（0–100，0 表示不加载）
+#[tauri::command]
+pub async fn set_modloader_priority(
+    game_dir: String,
+    mod_name: String,
+    priority: u8,
+) -> Result<ApiResponse<ModLoaderState>, String> {
+    if priority > 100 {
+        return Ok(ApiResponse::error("优先级必须在 0 到 100 之间".to_string()));
+    }
+    update_config(&game_dir, |game_path, config| {
+        let name = find_mod_dir(game_path, &mod_name)?;
+        let profile = config.current_profile();
+        config.profile_file(&profile).set(
+            &profile_section(&profile, "Priority"),
+            &ini_key(&name),
+            &priority.to_string(),
+        );
+        Ok(())
+    })
+}
+
+/// 在当前方案中排除（加入 IgnoreMods）或重新包含 MOD
+/// 包含时从 IgnoreMods 中删除该 MOD，方案设置了 ExcludeAllMods 时加入 IncludeMods
+#[tauri::command]
+pub async fn set_modloader_mod_included(
+    game_dir: String,
+    mod_name: String,
+    included: bool,
+) -> Result<ApiResponse<ModLoaderState>, String> {
+    update_config(&game_dir, |game_path, config| {
+        let name = find_mod_dir(game_path, &mod_name)?;
+        let key = ini_key(&name);
+        let profile = config.current_profile();
+        let ignore = profile_section(&profile, "IgnoreMods");
+        let include = profile_section(&profile, "IncludeMods");
+        let priority = profile_section(&profile, "Priority");
+        let file = config.profile_file(&profile);
+        if !included {
+            file.remove(&include, &key);
+            file.add_item(&ignore, &key);
+            return Ok(());
+        }
+
+        file.remove(&ignore, &key);
+        if file.get(&priority, &key).as_deref() == Some("0") {
+            file.remove(&priority, &key);
+        }
+        if config.resolve().ignored_by(&name) == Some("ExcludeAllMods") {
+            config.profile_file(&profile).add_item(&include, &key);
+        }
+        match config.resolve().ignored_by(&name) {
+            None => Ok(()),
+            Some(reason) => Err(format!(
+                "MOD \"{}\" 仍被方案 \"{}\" 的 {} 设置排除，请在 modloader.ini 中修改",
+                name, profile, reason
+            )),
+        }
+    })
+}
+
+/// 检查 ModLoader 方案名称（用作 ini 分段名的一部分）
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("方案名称不能为空".to_string());
+    }
+    if name.starts_with('$')
+        || name
+            .chars()
+            .any(|c| matches!(c, '.' | '[' | ']' | ',' | ';' | '='))
+    {
+        return Err(format!(
+            "方案名称 \"{}\" 不能以 $ 开头，也不能包含 . [ ] , ; =",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// 在 modloader.ini 中新建 ModLoader 方案，可继承已有方案的设置
+#[tauri::command]
+pub async fn create_modloader_profile(
+    game_dir: String,
+    name: String,
+    parent: Option<String>,
+) -> Result<ApiResponse<ModLoaderState>, String> {
+    let name = name.trim().to_string();
+    update_config(&game_dir, |_, config| {
+        validate_profile_name(&name)?;
+        if config.find_profile(&name).is_some() {
+            return Err(format!("方案 \"{}\" 已存在", name));
+        }
+        let parents = match parent.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            Some(parent) => config
+                .find_profile(parent)
+                .ok_or_else(|| format!("方案 \"{}\" 不存在", parent))?,
+            None => "$None".to_string(),
+        };
+        let main = config.main();
+        for kind in PROFILE_SECTIONS {
+            main.add_section(&profile_section(&name, kind));
+        }
+        let section = profile_section(&name, "Config");
+        main.set(&section, "Parents", &parents);
+        main.set(&section, "IgnoreAllMods", "false");
+        main.set(&section, "ExcludeAllMods", "false");
+        Ok(())
+    })
+}
+
+/// 切换 ModLoader 使用的方案（modloader.ini 的 [Folder.Config] Profile）
+#[tauri::command]
+pub async fn select_modloader_profile(
+    game_dir: String,
+    name: String,
+) -> Result<ApiResponse<ModLoaderState>, String> {
+    update_config(&game_dir, |_, config| {
+        let profile = config
+            .find_profile(&name)
+            .ok_or_else(|| format!("方案 \"{}\" 不存在", name.trim()))?;
+        config.main().set(FOLDER_CONFIG, "Profile", &profile);
+        Ok(())
+    })
+}
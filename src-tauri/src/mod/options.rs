@@ -0,0 +1,277 @@
+use crate::game::types::{
+    G2MConfigValidationError, G2MModConfig, ModFileEntry, ModOptionConditions, ModOptionGroup,
+    ModOptionGroupState, ModOptionGroupType, ModOptionState,
+};
+use crate::mod_core::requirements::{
+    is_known_game_type, loader_display_name, InstallEnvironment, KNOWN_LOADERS,
+};
+use std::collections::{BTreeMap, HashSet};
+
+/// 可选组件的选择：分组标识 -> 选中的组件标识
+pub type OptionSelection = BTreeMap<String, Vec<String>>;
+
+/// 检查 options 的写法：标识不能为空或重复，单选分组最多一个默认项，条件取值必须有效
+pub fn option_errors(config: &G2MModConfig) -> Vec<G2MConfigValidationError> {
+    let mut errors = Vec::new();
+    let mut push =
+        |path: String, message: String| errors.push(G2MConfigValidationError { path, message });
+
+    let mut group_ids = HashSet::new();
+    for (g, group) in config.options.iter().enumerate() {
+        let group_path = format!("$.options[{}]", g);
+        if group.id.trim().is_empty() {
+            push(format!("{}.id", group_path), "分组标识不能为空".to_string());
+        } else if !group_ids.insert(group.id.as_str()) {
+            push(
+                format!("{}.id", group_path),
+                format!("分组标识 \"{}\" 重复", group.id),
+            );
+        }
+        if group.options.is_empty() {
+            push(
+                format!("{}.options", group_path),
+                "分组中至少需要一个组件".to_string(),
+            );
+        }
+        if group.r#type == ModOptionGroupType::SelectOne
+            && group.options.iter().filter(|o| o.default).count() > 1
+        {
+            push(
+                format!("{}.options", group_path),
+                "单选分组最多只能有一个默认组件".to_string(),
+            );
+        }
+        for (path, message) in condition_errors(&group.conditions) {
+            push(format!("{}.conditions{}", group_path, path), message);
+        }
+
+        let mut option_ids = HashSet::new();
+        for (o, option) in group.options.iter().enumerate() {
+            let option_path = format!("{}.options[{}]", group_path, o);
+            if option.id.trim().is_empty() {
+                push(
+                    format!("{}.id", option_path),
+                    "组件标识不能为空".to_string(),
+                );
+            } else if !option_ids.insert(option.id.as_str()) {
+                push(
+                    format!("{}.id", option_path),
+                    format!("组件标识 \"{}\" 重复", option.id),
+                );
+            }
+            for (path, message) in condition_errors(&option.conditions) {
+                push(format!("{}.conditions{}", option_path, path), message);
+            }
+        }
+    }
+
+    errors
+}
+
+/// 检查条件中的游戏类型和加载器名称，返回（相对路径, 错误信息）
+fn condition_errors(conditions: &ModOptionConditions) -> Vec<(String, String)> {
+    let mut errors = Vec::new();
+    for (index, game_type) in conditions.game_types.iter().enumerate() {
+        if !is_known_game_type(game_type) {
+            errors.push((
+                format!(".game_types[{}]", index),
+                format!("未知的游戏类型 \"{}\"", game_type),
+            ));
+        }
+    }
+    for (index, loader) in conditions.loaders.iter().enumerate() {
+        if !KNOWN_LOADERS.contains(&loader.to_lowercase().as_str()) {
+            errors.push((
+                format!(".loaders[{}]", index),
+                format!("未知的加载器 \"{}\"", loader),
+            ));
+        }
+    }
+    errors
+}
+
+/// 检查条件是否满足，不满足时返回原因
+fn unmet_condition(conditions: &ModOptionConditions, env: &InstallEnvironment) -> Option<String> {
+    if !conditions.game_types.is_empty() {
+        let matches = env.game_type.as_ref().is_some_and(|game_type| {
+            conditions
+                .game_types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(game_type))
+        });
+        if !matches {
+            return Some(format!("仅适用于 {}", conditions.game_types.join("、")));
+        }
+    }
+
+    for loader in &conditions.loaders {
+        match env.has_loader(loader) {
+            Some(true) => {}
+            Some(false) => {
+                return Some(format!(
+                    "需要先安装 {}",
+                    loader_display_name(&loader.to_lowercase())
+                ))
+            }
+            None => return Some("无法检测加载器".to_string()),
+        }
+    }
+
+    None
+}
+
+/// 分组的默认选择：默认组件中可用的部分；单选分组没有可用的默认组件时选择第一个可用组件
+fn default_selection(group: &ModOptionGroup, env: &InstallEnvironment) -> Vec<String> {
+    let available: Vec<_> = group
+        .options
+        .iter()
+        .filter(|o| unmet_condition(&o.conditions, env).is_none())
+        .collect();
+
+    let mut selected: Vec<String> = available
+        .iter()
+        .filter(|o| o.default)
+        .map(|o| o.id.clone())
+        .collect();
+    if group.r#type == ModOptionGroupType::SelectOne {
+        selected.truncate(1);
+        if selected.is_empty() {
+            selected.extend(available.first().map(|o| o.id.clone()));
+        }
+    }
+    selected
+}
+
+/// 生成界面显示用的可选组件列表（按当前游戏计算可用性和默认选择）
+pub fn build_option_states(
+    config: &G2MModConfig,
+    env: &InstallEnvironment,
+) -> Vec<ModOptionGroupState> {
+    config
+        .options
+        .iter()
+        .map(|group| {
+            let group_available = unmet_condition(&group.conditions, env).is_none();
+            let selected = if group_available {
+                default_selection(group, env)
+            } else {
+                Vec::new()
+            };
+
+            ModOptionGroupState {
+                id: group.id.clone(),
+                name: group.name.clone(),
+                description: group.description.clone(),
+                r#type: group.r#type,
+                available: group_available,
+                options: group
+                    .options
+                    .iter()
+                    .map(|option| {
+                        let unavailable_reason = unmet_condition(&group.conditions, env)
+                            .or_else(|| unmet_condition(&option.conditions, env));
+                        ModOptionState {
+                            id: option.id.clone(),
+                            name: option.name.clone(),
+                            description: option.description.clone(),
+                            available: unavailable_reason.is_none(),
+                            unavailable_reason,
+                            selected: selected.contains(&option.id),
+                        }
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// 根据选择计算要安装的全部文件条目
+/// 未提供选择的分组使用默认选择；返回（文件条目, 实际选择），实际选择会记录到 mods.json
+pub fn resolve_option_selection(
+    config: &G2MModConfig,
+    requested: Option<&OptionSelection>,
+    env: &InstallEnvironment,
+) -> Result<(Vec<ModFileEntry>, OptionSelection), String> {
+    if let Some(requested) = requested {
+        if let Some(unknown) = requested
+            .keys()
+            .find(|id| !config.options.iter().any(|g| &g.id == *id))
+        {
+            return Err(format!("未知的可选组件分组: {}", unknown));
+        }
+    }
+
+    let mut entries = config.modfile.clone();
+    let mut selection = OptionSelection::new();
+
+    for group in &config.options {
+        let chosen = requested.and_then(|r| r.get(&group.id));
+
+        if let Some(reason) = unmet_condition(&group.conditions, env) {
+            if chosen.is_some_and(|ids| !ids.is_empty()) {
+                return Err(format!("分组 \"{}\" 当前不可用: {}", group.name, reason));
+            }
+            continue;
+        }
+
+        let mut ids: Vec<String> = match chosen {
+            Some(ids) => ids.clone(),
+            None => default_selection(group, env),
+        };
+        let mut seen = HashSet::new();
+        ids.retain(|id| seen.insert(id.clone()));
+
+        let mut available_count = 0;
+        for option in &group.options {
+            let unmet = unmet_condition(&option.conditions, env);
+            if unmet.is_none() {
+                available_count += 1;
+            }
+            if !ids.contains(&option.id) {
+                continue;
+            }
+            if let Some(reason) = unmet {
+                return Err(format!(
+                    "组件 \"{}\" ({}) 当前不可用: {}",
+                    option.name, group.name, reason
+                ));
+            }
+            entries.extend(option.modfile.iter().cloned());
+        }
+
+        if let Some(unknown) = ids
+            .iter()
+            .find(|id| !group.options.iter().any(|o| &o.id == *id))
+        {
+            return Err(format!("分组 \"{}\" 中没有组件 {}", group.name, unknown));
+        }
+        if group.r#type == ModOptionGroupType::SelectOne && available_count > 0 && ids.len() != 1 {
+            return Err(format!("分组 \"{}\" 必须且只能选择一个组件", group.name));
+        }
+
+        selection.insert(group.id.clone(), ids);
+    }
+
+    Ok((entries, selection))
+}
+
+/// 更新 MOD 时沿用之前的选择
+/// 新版本中已删除的分组和组件被忽略；单选分组的组件已删除时改用默认选择
+pub fn carry_over_selection(config: &G2MModConfig, previous: &OptionSelection) -> OptionSelection {
+    let mut selection = OptionSelection::new();
+    for group in &config.options {
+        let Some(ids) = previous.get(&group.id) else {
+            continue;
+        };
+        let kept: Vec<String> = ids
+            .iter()
+            .filter(|id| group.options.iter().any(|o| &o.id == *id))
+            .cloned()
+            .collect();
+        if group.r#type == ModOptionGroupType::SelectOne && kept.is_empty() {
+            continue;
+        }
+        selection.insert(group.id.clone(), kept);
+    }
+    selection
+}
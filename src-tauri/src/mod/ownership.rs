@@ -0,0 +1,155 @@
+use crate::game::types::{FileConflict, FileOwnershipIndex, G2MModInfo};
+use crate::game::utils::{get_g2m_dir_path, read_g2m_json};
+use crate::mod_core::img_target::TargetLocation;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 获取文件归属索引路径：.gtamodx/ownership.json
+fn get_ownership_index_path(game_dir: &Path) -> PathBuf {
+    get_g2m_dir_path(&game_dir.to_string_lossy()).join("ownership.json")
+}
+
+/// 索引键：统一使用小写和 / 分隔（游戏运行在不区分大小写的文件系统上）
+fn ownership_key(relative: &str) -> String {
+    relative.replace('\\', "/").to_lowercase()
+}
+
+impl FileOwnershipIndex {
+    /// 获取文件当前的所有者
+    pub fn owner_of(&self, relative: &str) -> Option<u32> {
+        self.files
+            .get(&ownership_key(relative))
+            .and_then(|owners| owners.last().copied())
+    }
+
+    /// 文件是否归指定 MOD 所有（没有归属记录的文件视为归该 MOD）
+    pub fn is_owned_by(&self, relative: &str, mod_id: u32) -> bool {
+        self.owner_of(relative).is_none_or(|owner| owner == mod_id)
+    }
+
+    /// 登记 MOD 写入的文件，该 MOD 成为这些文件的当前所有者
+    pub fn claim(&mut self, mod_id: u32, files: &[String]) {
+        for relative in files {
+            let owners = self.files.entry(ownership_key(relative)).or_default();
+            owners.retain(|id| *id != mod_id);
+            owners.push(mod_id);
+        }
+    }
+
+    /// 移除 MOD 对指定文件的归属记录（更新后不再包含的文件）
+    pub fn release_files(&mut self, mod_id: u32, files: &[String]) {
+        for relative in files {
+            let key = ownership_key(relative);
+            if let Some(owners) = self.files.get_mut(&key) {
+                owners.retain(|id| *id != mod_id);
+                if owners.is_empty() {
+                    self.files.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// 移除 MOD 的所有归属记录，文件归还给之前写入它的 MOD
+    pub fn release(&mut self, mod_id: u32) {
+        for owners in self.files.values_mut() {
+            owners.retain(|id| *id != mod_id);
+        }
+        self.files.retain(|_, owners| !owners.is_empty());
+    }
+}
+
+/// 读取文件归属索引
+/// 索引不存在或损坏时，按 MOD ID 顺序从 mods.json 的安装清单重建
+pub fn load_ownership_index(game_dir: &Path) -> FileOwnershipIndex {
+    let index_path = get_ownership_index_path(game_dir);
+    if let Ok(content) = fs::read_to_string(&index_path) {
+        if let Ok(index) = serde_json::from_str::<FileOwnershipIndex>(&content) {
+            return index;
+        }
+    }
+
+    let mut mods = read_g2m_json(&game_dir.to_string_lossy())
+        .map(|config| config.mods)
+        .unwrap_or_default();
+    mods.sort_by_key(|m| m.id);
+
+    let mut index = FileOwnershipIndex::default();
+    for mod_info in &mods {
+        index.claim(mod_info.id, &mod_info.installed_files);
+    }
+    index
+}
+
+/// 保存文件归属索引
+pub fn save_ownership_index(game_dir: &Path, index: &FileOwnershipIndex) -> Result<(), String> {
+    let index_path = get_ownership_index_path(game_dir);
+    if let Some(parent) = index_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建 .gtamodx 目录失败: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(index)
+        .map_err(|e| format!("序列化 ownership.json 失败: {}", e))?;
+    fs::write(&index_path, content).map_err(|e| format!("写入 ownership.json 失败: {}", e))
+}
+
+/// 检测即将写入的文件与已有文件的冲突
+/// 已被其他 MOD 占用的文件报告所有者，不属于任何 MOD 的已存在文件（含 IMG 档案条目）视为原版文件
+pub fn detect_conflicts(
+    game_dir: &Path,
+    index: &FileOwnershipIndex,
+    mods: &[G2MModInfo],
+    planned_files: &[String],
+) -> Vec<FileConflict> {
+    planned_files
+        .iter()
+        .filter_map(|relative| {
+            if let Some(owner) = index.owner_of(relative) {
+                Some(FileConflict {
+                    path: relative.clone(),
+                    owner_mod_id: Some(owner),
+                    owner_mod_name: mods.iter().find(|m| m.id == owner).map(|m| m.name.clone()),
+                    is_vanilla: false,
+                })
+            } else if TargetLocation::resolve(game_dir, relative).exists() {
+                Some(FileConflict {
+                    path: relative.clone(),
+                    owner_mod_id: None,
+                    owner_mod_name: None,
+                    is_vanilla: true,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// 安装前是否需要用户确认覆盖
+/// 自动安装时任何冲突都需要确认；按 g2m.json 安装时可以直接覆盖原版文件，
+/// 但覆盖其他 MOD 占用的文件仍需确认
+pub fn requires_overwrite(
+    conflicts: &[FileConflict],
+    has_config: bool,
+    overwrite: bool,
+    mod_id: u32,
+) -> bool {
+    if overwrite {
+        return false;
+    }
+    conflicts
+        .iter()
+        .any(|c| !has_config || c.owner_mod_id.is_some_and(|owner| owner != mod_id))
+}
+
+/// 生成冲突报告文本
+pub fn format_conflict_report(conflicts: &[FileConflict]) -> String {
+    conflicts
+        .iter()
+        .map(|conflict| match (&conflict.owner_mod_name, conflict.owner_mod_id) {
+            (Some(name), Some(id)) => format!("{} (已被 MOD \"{}\" [ID {}] 占用)", conflict.path, name, id),
+            (None, Some(id)) => format!("{} (已被 MOD [ID {}] 占用)", conflict.path, id),
+            _ => format!("{} (原版文件)", conflict.path),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
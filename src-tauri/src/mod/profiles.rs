@@ -0,0 +1,443 @@
+use crate::game::lock::GameDirLock;
+use crate::game::prerequisites::check_mod_loaders;
+use crate::game::types::{
+    ApiResponse, FileOwnershipIndex, G2MModInfo, ModProfile, ModProfileList, ModProfileSwitchResult,
+};
+use crate::game::utils::{get_g2m_dir_path, read_g2m_json, save_mods_list};
+use crate::mod_core::ownership::load_ownership_index;
+use crate::mod_core::toggle::{disable_mod_files, enable_mod_files};
+use crate::mod_core::transaction::move_file;
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 获取配置方案目录：.gtamodx/profiles/
+fn get_profiles_dir(game_dir: &Path) -> PathBuf {
+    get_g2m_dir_path(&game_dir.to_string_lossy()).join("profiles")
+}
+
+/// 当前方案名称保存在 .gtamodx/profiles/.active（方案名称不能以 . 开头，不会与方案文件冲突）
+fn get_active_profile_path(game_dir: &Path) -> PathBuf {
+    get_profiles_dir(game_dir).join(".active")
+}
+
+/// 停用的加载器文件存放目录：.gtamodx/disabled/loaders/
+fn get_disabled_loaders_dir(game_dir: &Path) -> PathBuf {
+    get_g2m_dir_path(&game_dir.to_string_lossy())
+        .join("disabled")
+        .join("loaders")
+}
+
+/// 检查方案名称（用作文件名）
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("方案名称不能为空".to_string());
+    }
+    if name.starts_with('.')
+        || name
+            .chars()
+            .any(|c| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+    {
+        return Err(format!(
+            "方案名称 \"{}\" 不能以 . 开头，也不能包含 / \\ : * ? \" < > |",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn get_profile_path(game_dir: &Path, name: &str) -> PathBuf {
+    get_profiles_dir(game_dir).join(format!("{}.json", name.trim()))
+}
+
+/// 读取单个配置方案
+fn load_profile(game_dir: &Path, name: &str) -> Option<ModProfile> {
+    let content = fs::read_to_string(get_profile_path(game_dir, name)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 保存配置方案
+fn save_profile(game_dir: &Path, profile: &ModProfile) -> Result<(), String> {
+    let dir = get_profiles_dir(game_dir);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建 profiles 目录失败: {}", e))?;
+    let content =
+        serde_json::to_string_pretty(profile).map_err(|e| format!("序列化配置方案失败: {}", e))?;
+    fs::write(get_profile_path(game_dir, &profile.name), content)
+        .map_err(|e| format!("写入配置方案失败: {}", e))
+}
+
+/// 读取所有配置方案（按名称排序）
+fn load_profiles(game_dir: &Path) -> Vec<ModProfile> {
+    let mut profiles: Vec<ModProfile> = fs::read_dir(get_profiles_dir(game_dir))
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .filter_map(|p| fs::read_to_string(p).ok())
+                .filter_map(|content| serde_json::from_str(&content).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    profiles
+}
+
+/// 获取当前使用的配置方案名称（方案文件已被删除时返回 None）
+pub fn get_active_profile(game_dir: &Path) -> Option<String> {
+    let name = fs::read_to_string(get_active_profile_path(game_dir)).ok()?;
+    let name = name.trim().to_string();
+    get_profile_path(game_dir, &name).is_file().then_some(name)
+}
+
+fn set_active_profile(game_dir: &Path, name: Option<&str>) -> Result<(), String> {
+    let path = get_active_profile_path(game_dir);
+    match name {
+        Some(name) => {
+            fs::create_dir_all(get_profiles_dir(game_dir))
+                .map_err(|e| format!("创建 profiles 目录失败: {}", e))?;
+            fs::write(&path, name).map_err(|e| format!("保存当前方案失败: {}", e))
+        }
+        None => {
+            let _ = fs::remove_file(&path);
+            Ok(())
+        }
+    }
+}
+
+/// 根据加载器文件名识别加载器类型
+fn classify_loader_file(file_name: &str) -> Option<&'static str> {
+    let lower = file_name.to_lowercase();
+    if lower == "dinput8.dll" {
+        Some("dinput8")
+    } else if lower == "modloader.asi" {
+        Some("modloader")
+    } else if lower == "cleo_redux.asi" {
+        Some("cleo_redux")
+    } else if lower.ends_with(".asi") && lower.contains("cleo") && !lower.contains("redux") {
+        Some("cleo")
+    } else {
+        None
+    }
+}
+
+/// 获取已停用的加载器文件：（加载器类型, 相对游戏目录的原路径）
+fn disabled_loader_files(game_dir: &Path) -> Vec<(String, String)> {
+    let dir = get_disabled_loaders_dir(game_dir);
+    let mut files = Vec::new();
+    let mut pending = vec![dir.clone()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Some(loader) = entry.file_name().to_str().and_then(classify_loader_file) {
+                let relative = path
+                    .strip_prefix(&dir)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                files.push((loader.to_string(), relative));
+            }
+        }
+    }
+    files
+}
+
+/// 获取当前游戏目录中存在的加载器：（加载器类型, 相对游戏目录的路径）
+async fn present_loader_files(game_dir: &Path) -> Vec<(String, String)> {
+    check_mod_loaders(game_dir.to_string_lossy().to_string(), None)
+        .await
+        .ok()
+        .and_then(|response| response.data)
+        .map(|status| status.loader_files.into_iter().collect())
+        .unwrap_or_default()
+}
+
+/// 按当前游戏目录的状态生成方案（启用的 MOD 和存在的加载器）
+async fn snapshot_profile(game_dir: &Path, name: &str) -> ModProfile {
+    let mods = read_g2m_json(&game_dir.to_string_lossy())
+        .map(|config| config.mods)
+        .unwrap_or_default();
+    ModProfile {
+        name: name.trim().to_string(),
+        enabled_mods: mods.iter().filter(|m| m.enabled).map(|m| m.id).collect(),
+        loaders: present_loader_files(game_dir)
+            .await
+            .into_iter()
+            .map(|(loader, _)| loader)
+            .collect(),
+        time: Utc::now().timestamp_millis().to_string(),
+    }
+}
+
+/// 安装、卸载、启用或禁用 MOD 后，把当前状态同步到正在使用的方案
+pub async fn sync_active_profile(game_dir: &Path) {
+    let Some(name) = get_active_profile(game_dir) else {
+        return;
+    };
+    let profile = snapshot_profile(game_dir, &name).await;
+    if let Err(e) = save_profile(game_dir, &profile) {
+        eprintln!("警告: 无法更新配置方案 {}: {}", name, e);
+    }
+}
+
+/// 获取所有配置方案（Tauri 命令）
+#[tauri::command]
+pub async fn get_mod_profiles(game_dir: String) -> Result<ApiResponse<ModProfileList>, String> {
+    let game_path = Path::new(&game_dir);
+    Ok(ApiResponse::success(ModProfileList {
+        active: get_active_profile(game_path),
+        profiles: load_profiles(game_path),
+    }))
+}
+
+/// 以当前启用的 MOD 和加载器创建配置方案，并设为当前方案（Tauri 命令）
+#[tauri::command]
+pub async fn create_mod_profile(
+    game_dir: String,
+    name: String,
+) -> Result<ApiResponse<ModProfile>, String> {
+    let game_path = Path::new(&game_dir);
+    if let Err(e) = validate_profile_name(&name) {
+        return Ok(ApiResponse::error(e));
+    }
+    if get_profile_path(game_path, &name).exists() {
+        return Ok(ApiResponse::error(format!(
+            "配置方案 \"{}\" 已存在",
+            name.trim()
+        )));
+    }
+
+    let profile = snapshot_profile(game_path, &name).await;
+    if let Err(e) = save_profile(game_path, &profile)
+        .and_then(|_| set_active_profile(game_path, Some(&profile.name)))
+    {
+        return Ok(ApiResponse::error(e));
+    }
+    Ok(ApiResponse::success(profile))
+}
+
+/// 复制配置方案（Tauri 命令）
+#[tauri::command]
+pub async fn clone_mod_profile(
+    game_dir: String,
+    source_name: String,
+    new_name: String,
+) -> Result<ApiResponse<ModProfile>, String> {
+    let game_path = Path::new(&game_dir);
+    if let Err(e) = validate_profile_name(&new_name) {
+        return Ok(ApiResponse::error(e));
+    }
+    let Some(source) = load_profile(game_path, &source_name) else {
+        return Ok(ApiResponse::error(format!(
+            "配置方案 \"{}\" 不存在",
+            source_name
+        )));
+    };
+    if get_profile_path(game_path, &new_name).exists() {
+        return Ok(ApiResponse::error(format!(
+            "配置方案 \"{}\" 已存在",
+            new_name.trim()
+        )));
+    }
+
+    let profile = ModProfile {
+        name: new_name.trim().to_string(),
+        time: Utc::now().timestamp_millis().to_string(),
+        ..source
+    };
+    if let Err(e) = save_profile(game_path, &profile) {
+        return Ok(ApiResponse::error(e));
+    }
+    Ok(ApiResponse::success(profile))
+}
+
+/// 删除配置方案（不修改游戏目录中的文件）（Tauri 命令）
+#[tauri::command]
+pub async fn delete_mod_profile(game_dir: String, name: String) -> Result<ApiResponse<()>, String> {
+    let game_path = Path::new(&game_dir);
+    let path = get_profile_path(game_path, &name);
+    if validate_profile_name(&name).is_err() || !path.is_file() {
+        return Ok(ApiResponse::error(format!("配置方案 \"{}\" 不存在", name)));
+    }
+
+    if let Err(e) = fs::remove_file(&path) {
+        return Ok(ApiResponse::error(format!("删除配置方案失败: {}", e)));
+    }
+    if get_active_profile(game_path).is_none() {
+        let _ = set_active_profile(game_path, None);
+    }
+    Ok(ApiResponse::success(()))
+}
+
+/// 切换配置方案（Tauri 命令）
+/// 一次性启用或禁用 MOD、停用或恢复加载器，任一步骤失败时撤销已执行的更改
+#[tauri::command]
+pub async fn switch_mod_profile(
+    game_dir: String,
+    name: String,
+) -> Result<ApiResponse<ModProfileSwitchResult>, String> {
+    let game_path = Path::new(&game_dir);
+    if !game_path.is_dir() {
+        return Ok(ApiResponse::error("游戏目录不存在".to_string()));
+    }
+    let _lock = match GameDirLock::acquire(game_path, "切换配置方案") {
+        Ok(lock) => lock,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+    let Some(profile) = load_profile(game_path, &name) else {
+        return Ok(ApiResponse::error(format!("配置方案 \"{}\" 不存在", name)));
+    };
+
+    let mut mods = read_g2m_json(&game_dir)
+        .map(|config| config.mods)
+        .unwrap_or_default();
+    let mut result = ModProfileSwitchResult {
+        profile: profile.name.clone(),
+        enabled_mods: Vec::new(),
+        disabled_mods: Vec::new(),
+        enabled_loaders: Vec::new(),
+        disabled_loaders: Vec::new(),
+        missing_mods: profile
+            .enabled_mods
+            .iter()
+            .filter(|id| !mods.iter().any(|m| m.id == **id))
+            .copied()
+            .collect(),
+        missing_loaders: Vec::new(),
+        skipped_mods: Vec::new(),
+    };
+
+    // 先禁用再启用，避免两个 MOD 同时占用同一位置
+    let mut changes: Vec<(usize, bool)> = Vec::new();
+    for (index, mod_info) in mods.iter().enumerate() {
+        let target = profile.enabled_mods.contains(&mod_info.id);
+        if mod_info.enabled == target {
+            continue;
+        }
+        if mod_info.installed_files.is_empty() {
+            result.skipped_mods.push(mod_info.id);
+            continue;
+        }
+        changes.push((index, target));
+    }
+    changes.sort_by_key(|(_, enable)| *enable);
+
+    let ownership = load_ownership_index(game_path);
+    let mut applied: Vec<(usize, bool)> = Vec::new();
+    for (index, enable) in &changes {
+        let mod_info = &mods[*index];
+        let outcome = if *enable {
+            enable_mod_files(game_path, mod_info, &ownership)
+        } else {
+            disable_mod_files(game_path, mod_info, &ownership)
+        };
+        if let Err(e) = outcome {
+            revert_mod_changes(game_path, &mods, &applied, &ownership);
+            return Ok(ApiResponse::error(format!(
+                "切换配置方案失败，已撤销更改\nMOD名称: {}\n错误详情: {}",
+                mod_info.name, e
+            )));
+        }
+        applied.push((*index, *enable));
+    }
+
+    // 加载器：方案中没有的移入 .gtamodx/disabled/loaders/，方案中有的从该目录恢复
+    let disabled_dir = get_disabled_loaders_dir(game_path);
+    let mut loader_moves: Vec<(PathBuf, PathBuf)> = Vec::new();
+    for (loader, relative) in present_loader_files(game_path).await {
+        if !profile.loaders.contains(&loader) {
+            loader_moves.push((game_path.join(&relative), disabled_dir.join(&relative)));
+            result.disabled_loaders.push(loader);
+        }
+    }
+    let disabled_loaders = disabled_loader_files(game_path);
+    for loader in &profile.loaders {
+        let mut found = false;
+        for (disabled, relative) in &disabled_loaders {
+            if disabled == loader {
+                loader_moves.push((disabled_dir.join(relative), game_path.join(relative)));
+                found = true;
+            }
+        }
+        if found {
+            result.enabled_loaders.push(loader.clone());
+        }
+    }
+
+    for (done, (from, to)) in loader_moves.iter().enumerate() {
+        let outcome = to
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| move_file(from, to));
+        if let Err(e) = outcome {
+            for (done_from, done_to) in loader_moves[..done].iter().rev() {
+                let _ = move_file(done_to, done_from);
+            }
+            revert_mod_changes(game_path, &mods, &applied, &ownership);
+            return Ok(ApiResponse::error(format!(
+                "切换配置方案失败，已撤销更改\n移动加载器文件失败: {}\n路径: {}",
+                e,
+                from.display()
+            )));
+        }
+    }
+    let _ = fs::remove_dir(&disabled_dir);
+
+    let present: Vec<String> = present_loader_files(game_path)
+        .await
+        .into_iter()
+        .map(|(loader, _)| loader)
+        .collect();
+    result.missing_loaders = profile
+        .loaders
+        .iter()
+        .filter(|loader| !present.contains(loader))
+        .cloned()
+        .collect();
+
+    for (index, enable) in &applied {
+        mods[*index].enabled = *enable;
+        if *enable {
+            result.enabled_mods.push(mods[*index].id);
+        } else {
+            result.disabled_mods.push(mods[*index].id);
+        }
+    }
+    if let Err(e) = save_mods_list(&game_dir, mods) {
+        return Ok(ApiResponse::error(format!(
+            "MOD 文件已切换，但更新 mods.json 失败: {}",
+            e
+        )));
+    }
+    if let Err(e) = set_active_profile(game_path, Some(&profile.name)) {
+        eprintln!("警告: {}", e);
+    }
+
+    Ok(ApiResponse::success(result))
+}
+
+/// 撤销切换方案时已执行的 MOD 启用/禁用
+fn revert_mod_changes(
+    game_dir: &Path,
+    mods: &[G2MModInfo],
+    applied: &[(usize, bool)],
+    ownership: &FileOwnershipIndex,
+) {
+    for (index, enabled) in applied.iter().rev() {
+        let mod_info = &mods[*index];
+        let outcome = if *enabled {
+            disable_mod_files(game_dir, mod_info, ownership)
+        } else {
+            enable_mod_files(game_dir, mod_info, ownership)
+        };
+        if let Err(e) = outcome {
+            eprintln!("警告: 撤销 MOD \"{}\" 的更改失败: {}", mod_info.name, e);
+        }
+    }
+}
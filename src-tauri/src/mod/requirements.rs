@@ -0,0 +1,300 @@
+use crate::game::descriptors::{find_game_descriptor, game_descriptors};
+use crate::game::types::{
+    G2MConfigValidationError, G2MModConfig, G2MModInfo, ModLoaderStatus, RequirementIssue,
+};
+use std::cmp::Ordering;
+
+/// g2m.json 中可声明的加载器
+pub const KNOWN_LOADERS: [&str; 4] = ["cleo", "cleo_redux", "modloader", "dinput8"];
+
+/// g2m.json 中可声明的游戏类型（游戏描述中的所有游戏）
+pub fn is_known_game_type(game_type: &str) -> bool {
+    find_game_descriptor(game_type).is_some()
+}
+
+/// 安装环境：当前游戏类型和已安装的加载器
+pub struct InstallEnvironment {
+    pub game_type: Option<String>,              // 无法识别时为 None
+    pub loader_status: Option<ModLoaderStatus>, // 检测失败时为 None
+}
+
+impl InstallEnvironment {
+    /// 是否已安装指定加载器，无法检测时返回 None
+    pub fn has_loader(&self, loader: &str) -> Option<bool> {
+        self.loader_status
+            .as_ref()
+            .map(|status| has_loader(status, &loader.to_lowercase()))
+    }
+}
+
+/// 检查 requires、conflicts_with、game_types 的写法
+pub fn declaration_errors(config: &G2MModConfig) -> Vec<G2MConfigValidationError> {
+    let mut errors = Vec::new();
+    let mut push =
+        |path: String, message: String| errors.push(G2MConfigValidationError { path, message });
+
+    for (index, requirement) in config.requires.iter().enumerate() {
+        let path = format!("$.requires[{}]", index);
+        match (&requirement.mod_name, &requirement.loader) {
+            (Some(_), Some(_)) | (None, None) => push(
+                path.clone(),
+                "必须且只能指定 mod 或 loader 之一".to_string(),
+            ),
+            (None, Some(loader)) if !KNOWN_LOADERS.contains(&loader.to_lowercase().as_str()) => {
+                push(
+                    format!("{}.loader", path),
+                    format!(
+                        "未知的加载器 \"{}\"，可选值: {}",
+                        loader,
+                        KNOWN_LOADERS.join("、")
+                    ),
+                )
+            }
+            _ => {}
+        }
+        if let Some(version) = &requirement.min_version {
+            if requirement.loader.is_none() {
+                push(
+                    format!("{}.min_version", path),
+                    "min_version 只能用于 loader 依赖".to_string(),
+                );
+            } else if parse_version(version).is_none() {
+                push(
+                    format!("{}.min_version", path),
+                    format!(
+                        "版本号 \"{}\" 格式无效，应为 4.4 或 1.0.2 这样的数字",
+                        version
+                    ),
+                );
+            }
+        }
+    }
+
+    for (index, name) in config.conflicts_with.iter().enumerate() {
+        if name.trim().is_empty() {
+            push(
+                format!("$.conflicts_with[{}]", index),
+                "MOD 名称不能为空".to_string(),
+            );
+        }
+    }
+
+    for (index, game_type) in config.game_types.iter().enumerate() {
+        if !is_known_game_type(game_type) {
+            let known: Vec<&str> = game_descriptors().iter().map(|d| d.id.as_str()).collect();
+            push(
+                format!("$.game_types[{}]", index),
+                format!(
+                    "未知的游戏类型 \"{}\"，可选值: {}",
+                    game_type,
+                    known.join("、")
+                ),
+            );
+        }
+    }
+
+    errors
+}
+
+/// 按 g2m.json 的依赖声明检查游戏目录
+/// 游戏类型不符、缺少依赖、存在冲突 MOD 会阻止安装；无法确认的情况仅作为警告
+pub fn check_mod_requirements(
+    config: &G2MModConfig,
+    mods: &[G2MModInfo],
+    env: &InstallEnvironment,
+) -> Vec<RequirementIssue> {
+    let mut issues = Vec::new();
+
+    if !config.game_types.is_empty() {
+        match &env.game_type {
+            Some(game_type) => {
+                if !config
+                    .game_types
+                    .iter()
+                    .any(|t| t.eq_ignore_ascii_case(game_type))
+                {
+                    issues.push(issue(
+                        "game_type",
+                        format!(
+                            "该 MOD 仅支持 {}，当前游戏为 {}",
+                            config.game_types.join("、"),
+                            game_type
+                        ),
+                        true,
+                    ));
+                }
+            }
+            None => issues.push(issue(
+                "game_type",
+                format!(
+                    "无法识别当前游戏类型，该 MOD 仅支持 {}",
+                    config.game_types.join("、")
+                ),
+                false,
+            )),
+        }
+    }
+
+    for requirement in &config.requires {
+        if let Some(name) = &requirement.mod_name {
+            match find_mod(mods, name) {
+                Some(installed) if installed.enabled => {}
+                Some(_) => issues.push(issue(
+                    "missing_mod",
+                    format!("依赖的 MOD \"{}\" 已安装但已禁用", name),
+                    true,
+                )),
+                None => issues.push(issue(
+                    "missing_mod",
+                    format!("缺少依赖的 MOD \"{}\"", name),
+                    true,
+                )),
+            }
+        }
+
+        if let Some(loader) = &requirement.loader {
+            let loader = loader.to_lowercase();
+            let Some(status) = &env.loader_status else {
+                issues.push(issue(
+                    "missing_loader",
+                    format!(
+                        "无法检测加载器，请确认已安装 {}",
+                        loader_display_name(&loader)
+                    ),
+                    false,
+                ));
+                continue;
+            };
+
+            if !has_loader(status, &loader) {
+                issues.push(issue(
+                    "missing_loader",
+                    format!("缺少前置加载器 {}", loader_display_name(&loader)),
+                    true,
+                ));
+                continue;
+            }
+
+            if let Some(min_version) = &requirement.min_version {
+                match status.loader_versions.get(&loader) {
+                    Some(version) if compare_versions(version, min_version) == Ordering::Less => {
+                        issues.push(issue(
+                            "loader_version",
+                            format!(
+                                "{} 版本过低：需要 {} 或更高，当前为 {}",
+                                loader_display_name(&loader),
+                                min_version,
+                                version
+                            ),
+                            true,
+                        ))
+                    }
+                    Some(_) => {}
+                    None => issues.push(issue(
+                        "loader_version",
+                        format!(
+                            "无法读取 {} 的版本，请确认版本不低于 {}",
+                            loader_display_name(&loader),
+                            min_version
+                        ),
+                        false,
+                    )),
+                }
+            }
+        }
+    }
+
+    for name in &config.conflicts_with {
+        match find_mod(mods, name) {
+            Some(installed) if installed.enabled => issues.push(issue(
+                "conflicting_mod",
+                format!(
+                    "与已安装的 MOD \"{}\" [ID {}] 冲突",
+                    installed.name, installed.id
+                ),
+                true,
+            )),
+            Some(installed) => issues.push(issue(
+                "conflicting_mod",
+                format!(
+                    "与已禁用的 MOD \"{}\" [ID {}] 冲突，启用该 MOD 前请先卸载其中一个",
+                    installed.name, installed.id
+                ),
+                false,
+            )),
+            None => {}
+        }
+    }
+
+    issues
+}
+
+/// 生成阻止安装的问题报告文本
+pub fn format_requirement_report(issues: &[RequirementIssue]) -> String {
+    issues
+        .iter()
+        .filter(|i| i.blocking)
+        .map(|i| format!("- {}", i.message))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn issue(kind: &str, message: String, blocking: bool) -> RequirementIssue {
+    RequirementIssue {
+        kind: kind.to_string(),
+        message,
+        blocking,
+    }
+}
+
+/// 按名称查找已安装的 MOD（不区分大小写）
+fn find_mod<'a>(mods: &'a [G2MModInfo], name: &str) -> Option<&'a G2MModInfo> {
+    mods.iter()
+        .find(|m| m.name.trim().eq_ignore_ascii_case(name.trim()))
+}
+
+fn has_loader(status: &ModLoaderStatus, loader: &str) -> bool {
+    match loader {
+        "cleo" => status.has_cleo,
+        "cleo_redux" => status.has_cleo_redux,
+        "modloader" => status.has_modloader,
+        "dinput8" => status.has_dinput8,
+        _ => false,
+    }
+}
+
+pub fn loader_display_name(loader: &str) -> &str {
+    match loader {
+        "cleo" => "CLEO",
+        "cleo_redux" => "CLEO Redux",
+        "modloader" => "ModLoader",
+        "dinput8" => "dinput8.dll",
+        other => other,
+    }
+}
+
+/// 解析 "4.4.1" 形式的版本号
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect()
+}
+
+/// 按数字逐段比较版本号，缺少的段视为 0
+fn compare_versions(left: &str, right: &str) -> Ordering {
+    let (Some(left), Some(right)) = (parse_version(left), parse_version(right)) else {
+        return Ordering::Equal;
+    };
+    let len = left.len().max(right.len());
+    (0..len)
+        .map(|i| {
+            let l = left.get(i).copied().unwrap_or(0);
+            let r = right.get(i).copied().unwrap_or(0);
+            l.cmp(&r)
+        })
+        .find(|o| *o != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
@@ -0,0 +1,163 @@
+use crate::game::types::{G2MConfigValidationError, G2MModConfig};
+use crate::mod_core::config::mod_file_path_errors;
+use crate::mod_core::options::option_errors;
+use crate::mod_core::requirements::declaration_errors;
+use serde_json::Value;
+
+/// 当前 g2m.json 配置格式版本
+pub const G2M_CONFIG_SCHEMA_VERSION: u32 = 1;
+
+/// 从 Rust 类型生成 g2m.json 的 JSON Schema
+pub fn g2m_mod_config_schema() -> Value {
+    serde_json::to_value(schemars::schema_for!(G2MModConfig)).unwrap_or(Value::Null)
+}
+
+/// 解析后的 g2m.json
+pub struct ParsedModConfig {
+    pub config: G2MModConfig,
+    pub original_version: u32, // 文件中声明的版本，小于当前版本说明已迁移
+}
+
+/// 解析并校验 g2m.json 内容：语法检查 -> 版本迁移 -> 按类型反序列化 -> 路径检查
+/// 返回所有能收集到的错误及其 JSON 路径
+pub fn parse_g2m_mod_config(
+    content: &str,
+) -> Result<ParsedModConfig, (Option<u32>, Vec<G2MConfigValidationError>)> {
+    let mut value: Value = serde_json::from_str(content)
+        .map_err(|e| (None, vec![error_at("$", format!("JSON 语法错误: {}", e))]))?;
+
+    let original_version = match value.get("schema_version") {
+        None => 0,
+        Some(v) => match v.as_u64() {
+            Some(version) => version as u32,
+            None => {
+                return Err((
+                    None,
+                    vec![error_at(
+                        "$.schema_version",
+                        "版本号必须是非负整数".to_string(),
+                    )],
+                ))
+            }
+        },
+    };
+
+    migrate_g2m_mod_config(&mut value, original_version)
+        .map_err(|e| (Some(original_version), vec![e]))?;
+
+    let config: G2MModConfig = serde_path_to_error::deserialize(&value).map_err(|e| {
+        let path = e.path().to_string();
+        let path = if path == "." {
+            "$".to_string()
+        } else {
+            format!("$.{}", path)
+        };
+        (
+            Some(original_version),
+            vec![error_at(&path, e.into_inner().to_string())],
+        )
+    })?;
+
+    let mut errors = Vec::new();
+    if config.name.trim().is_empty() {
+        errors.push(error_at("$.name", "MOD 名称不能为空".to_string()));
+    }
+    if config.modfile.is_empty() && config.options.is_empty() {
+        errors.push(error_at(
+            "$.modfile",
+            "至少需要一个文件条目或可选组件".to_string(),
+        ));
+    }
+    errors.extend(mod_file_path_errors(&config));
+    errors.extend(declaration_errors(&config));
+    errors.extend(option_errors(&config));
+
+    if errors.is_empty() {
+        Ok(ParsedModConfig {
+            config,
+            original_version,
+        })
+    } else {
+        Err((Some(original_version), errors))
+    }
+}
+
+/// 将旧版本配置逐级升级到当前版本
+fn migrate_g2m_mod_config(
+    value: &mut Value,
+    from_version: u32,
+) -> Result<(), G2MConfigValidationError> {
+    if from_version > G2M_CONFIG_SCHEMA_VERSION {
+        return Err(error_at(
+            "$.schema_version",
+            format!(
+                "配置版本 {} 高于当前支持的版本 {}，请更新 G2M",
+                from_version, G2M_CONFIG_SCHEMA_VERSION
+            ),
+        ));
+    }
+
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| error_at("$", "配置必须是 JSON 对象".to_string()))?;
+
+    if from_version < 1 {
+        // 0 -> 1：早期配置的条目可能没有 is_directory，按 source 是否以分隔符结尾推断
+        if let Some(entries) = object.get_mut("modfile").and_then(Value::as_array_mut) {
+            for entry in entries.iter_mut().filter_map(Value::as_object_mut) {
+                if !entry.contains_key("is_directory") {
+                    let is_directory = entry
+                        .get("source")
+                        .and_then(Value::as_str)
+                        .is_some_and(|s| s.ends_with('/') || s.ends_with('\\'));
+                    entry.insert("is_directory".to_string(), Value::Bool(is_directory));
+                }
+            }
+        }
+    }
+
+    object.insert(
+        "schema_version".to_string(),
+        Value::from(G2M_CONFIG_SCHEMA_VERSION),
+    );
+    Ok(())
+}
+
+fn error_at(path: &str, message: String) -> G2MConfigValidationError {
+    G2MConfigValidationError {
+        path: path.to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    /// 文档站点提供的 g2m.json Schema（website/docs/public/g2m.schema.json）
+    fn published_schema_path() -> &'static Path {
+        Path::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../website/docs/public/g2m.schema.json"
+        ))
+    }
+
+    /// 发布的 Schema 必须与类型生成的一致；设置 G2M_UPDATE_SCHEMA=1 运行测试可重新生成该文件
+    #[test]
+    fn published_schema_matches_types() {
+        let generated = g2m_mod_config_schema();
+        let path = published_schema_path();
+        if std::env::var_os("G2M_UPDATE_SCHEMA").is_some() {
+            let content = serde_json::to_string_pretty(&generated).unwrap() + "\n";
+            std::fs::write(path, content).unwrap();
+        }
+        let published: Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert!(
+            published == generated,
+            "{} 与 G2MModConfig 不一致，请设置 G2M_UPDATE_SCHEMA=1 运行 cargo test 重新生成",
+            path.display()
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::formats::text::{diff_text_files, TextFile};
+use crate::game::types::{ApiResponse, G2MModInfo, ModTextChange, TextEntryDiff, TextMergeResult};
+use crate::game::utils::{read_g2m_json, save_mods_list};
+use crate::mod_core::utils::check_relative_path;
+use std::path::Path;
+
+fn same_entry(a: &ModTextChange, b: &ModTextChange) -> bool {
+    a.file.eq_ignore_ascii_case(&b.file) && a.table == b.table && a.key == b.key
+}
+
+/// 按文件分组修改文本表，每个文件只读写一次；edit 返回是否修改了该条目
+fn edit_text_files(
+    game_dir: &Path,
+    changes: &[ModTextChange],
+    mut edit: impl FnMut(&mut TextFile, &ModTextChange) -> Result<bool, String>,
+) -> Result<(), String> {
+    let mut files: Vec<String> = Vec::new();
+    for change in changes {
+        if !files.iter().any(|f| f.eq_ignore_ascii_case(&change.file)) {
+            files.push(change.file.clone());
+        }
+    }
+
+    for file in files {
+        let path = game_dir.join(&file);
+        if !path.is_file() {
+            continue;
+        }
+        let mut text = TextFile::load(&path)?;
+        let mut changed = false;
+        for change in changes
+            .iter()
+            .filter(|c| c.file.eq_ignore_ascii_case(&file))
+        {
+            changed |= edit(&mut text, change)?;
+        }
+        if changed {
+            text.save(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// 把条目恢复为 MOD 修改前的内容（修改前不存在的条目删除）
+fn restore_previous(text: &mut TextFile, change: &ModTextChange) -> Result<(), String> {
+    match &change.previous {
+        Some(previous) => text.set(&change.table, &change.key, previous),
+        None => {
+            text.remove(&change.table, &change.key);
+            Ok(())
+        }
+    }
+}
+
+/// 撤销 MOD 合并到文本表的条目，只恢复仍为该 MOD 所写内容的条目
+/// 条目在之后又被其他 MOD 修改时保持不变，并把修改前的内容转交给该 MOD（会修改 other_mods 中的记录）
+pub fn revert_mod_text_changes(
+    game_dir: &Path,
+    mod_info: &G2MModInfo,
+    other_mods: &mut [G2MModInfo],
+) -> Result<(), String> {
+    edit_text_files(game_dir, &mod_info.text_changes, |text, change| {
+        let upper = other_mods
+            .iter_mut()
+            .flat_map(|m| m.text_changes.iter_mut())
+            .find(|c| same_entry(c, change) && c.previous.as_deref() == Some(change.text.as_str()));
+        if let Some(upper) = upper {
+            upper.previous = change.previous.clone();
+            return Ok(false);
+        }
+        if text.get(&change.table, &change.key) != Some(change.text.as_str()) {
+            return Ok(false);
+        }
+        restore_previous(text, change)?;
+        Ok(true)
+    })
+}
+
+/// 重新写入 MOD 合并的条目（启用 MOD 时），只修改仍为修改前内容的条目
+pub fn reapply_mod_text_changes(game_dir: &Path, mod_info: &G2MModInfo) -> Result<(), String> {
+    edit_text_files(game_dir, &mod_info.text_changes, |text, change| {
+        if text.get(&change.table, &change.key) != change.previous.as_deref() {
+            return Ok(false);
+        }
+        text.set(&change.table, &change.key, &change.text)?;
+        Ok(true)
+    })
+}
+
+/// 把 MOD 修改的文本条目合并到游戏中已安装的 GXT/FXT 文件（Tauri 命令）
+/// MOD 修改的条目为 source 相对 base（MOD 制作时使用的原文件）新增和修改的条目；
+/// 未指定 base 时与已安装的文件比较。合并的条目记录到该 MOD，卸载或禁用时恢复
+#[tauri::command]
+pub async fn merge_mod_text(
+    game_dir: String,
+    mod_id: u32,
+    source: String,
+    target: String,
+    base: Option<String>,
+) -> Result<ApiResponse<TextMergeResult>, String> {
+    let game_path = Path::new(&game_dir);
+    let mut mods = read_g2m_json(&game_dir)
+        .map(|config| config.mods)
+        .unwrap_or_default();
+    let Some(index) = mods.iter().position(|m| m.id == mod_id) else {
+        return Ok(ApiResponse::error("未找到指定的 MOD".to_string()));
+    };
+    if !mods[index].enabled {
+        return Ok(ApiResponse::error(format!(
+            "MOD \"{}\" 已禁用，请先启用后再合并文本",
+            mods[index].name
+        )));
+    }
+    if let Err(reason) = check_relative_path(&target) {
+        return Ok(ApiResponse::error(format!(
+            "目标文件 \"{}\" {}",
+            target, reason
+        )));
+    }
+    let file = target.trim().replace('\\', "/");
+    let target_path = game_path.join(&file);
+    if !target_path.is_file() {
+        return Ok(ApiResponse::error(format!("游戏目录中没有 {}", file)));
+    }
+
+    let loaded = TextFile::load(&target_path).and_then(|installed| {
+        let modded = TextFile::load(Path::new(&source))?;
+        let diff = match base.as_deref().map(str::trim).filter(|b| !b.is_empty()) {
+            Some(base) => diff_text_files(&TextFile::load(Path::new(base))?, &modded)?,
+            None => diff_text_files(&installed, &modded)?,
+        };
+        if installed.format_name() != modded.format_name() {
+            return Err(format!(
+                "文本表格式不同（已安装的为 {}，MOD 的为 {}），无法合并",
+                installed.format_name(),
+                modded.format_name()
+            ));
+        }
+        Ok((installed, diff))
+    });
+    let (mut installed, diff) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => return Ok(ApiResponse::error(e)),
+    };
+
+    let mut result = TextMergeResult {
+        file: file.clone(),
+        changed: Vec::new(),
+        unchanged: 0,
+    };
+    for entry in diff.added.into_iter().chain(diff.changed) {
+        let new_text = entry.new_text.unwrap_or_default();
+        let current = installed.get(&entry.table, &entry.key).map(str::to_string);
+        if current.as_deref() == Some(new_text.as_str()) {
+            result.unchanged += 1;
+            continue;
+        }
+        if let Err(e) = installed.set(&entry.table, &entry.key, &new_text) {
+            return Ok(ApiResponse::error(e));
+        }
+
+        // 再次合并同一条目时保留最初的内容，卸载时恢复到合并前
+        let change = ModTextChange {
+            file: file.clone(),
+            table: entry.table.clone(),
+            key: entry.key.clone(),
+            text: new_text.clone(),
+            previous: current.clone(),
+        };
+        let changes = &mut mods[index].text_changes;
+        match changes.iter_mut().find(|c| same_entry(c, &change)) {
+            Some(existing) => existing.text = change.text,
+            None => changes.push(change),
+        }
+        result.changed.push(TextEntryDiff {
+            table: entry.table,
+            key: entry.key,
+            old_text: current,
+            new_text: Some(new_text),
+        });
+    }
+
+    if result.changed.is_empty() {
+        return Ok(ApiResponse::success(result));
+    }
+    if let Err(e) = installed.save(&target_path) {
+        return Ok(ApiResponse::error(e));
+    }
+    if let Err(e) = save_mods_list(&game_dir, mods) {
+        return Ok(ApiResponse::error(format!(
+            "文本已合并，但更新 mods.json 失败: {}",
+            e
+        )));
+    }
+    Ok(ApiResponse::success(result))
+}
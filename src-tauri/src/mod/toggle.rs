@@ -0,0 +1,123 @@
+use crate::formats::dat::{apply_mod_dat_entries, remove_mod_dat_entries};
+use crate::game::types::{FileOwnershipIndex, G2MModInfo};
+use crate::game::utils::get_g2m_dir_path;
+use crate::mod_core::img_target::{is_img_target, move_location, work_path, TargetLocation};
+use crate::mod_core::text_merge::{reapply_mod_text_changes, revert_mod_text_changes};
+use crate::mod_core::transaction::{get_mod_backup_dir, remove_mod_work_dir};
+use std::path::{Path, PathBuf};
+
+/// 获取 MOD 的禁用目录：.gtamodx/disabled/<mod-id>/
+pub fn get_mod_disabled_dir(game_dir: &Path, mod_id: u32) -> PathBuf {
+    get_g2m_dir_path(&game_dir.to_string_lossy())
+        .join("disabled")
+        .join(mod_id.to_string())
+}
+
+/// 计算已安装文件在禁用后的存放位置
+/// - modloader 目录下的文件整体移入 .gtamodx/disabled/<mod-id>/，ModLoader 不再加载
+/// - CLEO 脚本（.cs）原地重命名为 .cs.disabled，CLEO 会跳过该文件
+/// - 其他文件（ASI/DLL 插件、CLEO Redux 的 .js/.ts、IMG 档案条目等）移入 .gtamodx/disabled/<mod-id>/
+fn disabled_location(game_dir: &Path, mod_id: u32, relative: &str) -> TargetLocation {
+    let lower = relative.to_lowercase();
+    if !is_img_target(relative) && !lower.starts_with("modloader/") && lower.ends_with(".cs") {
+        TargetLocation::File(game_dir.join(format!("{}.disabled", relative)))
+    } else {
+        TargetLocation::File(get_mod_disabled_dir(game_dir, mod_id).join(work_path(relative)))
+    }
+}
+
+/// 禁用 MOD：删除写入 .dat 文件的行，恢复合并到文本表的条目，把已安装的文件移出生效位置，并把安装时被覆盖的原文件放回原处
+/// 已被其他 MOD 覆盖的文件不做处理
+pub fn disable_mod_files(
+    game_dir: &Path,
+    mod_info: &G2MModInfo,
+    ownership: &FileOwnershipIndex,
+) -> Result<(), String> {
+    ensure_has_manifest(mod_info)?;
+
+    let backup_dir = get_mod_backup_dir(game_dir, mod_info.id);
+    let mut moves = Vec::new();
+    for relative in &mod_info.installed_files {
+        let installed = TargetLocation::resolve(game_dir, relative);
+        if installed.is_file() && ownership.is_owned_by(relative, mod_info.id) {
+            moves.push((installed, disabled_location(game_dir, mod_info.id, relative)));
+        }
+    }
+    for relative in &mod_info.backed_up_files {
+        let backup = TargetLocation::File(backup_dir.join(work_path(relative)));
+        if backup.is_file() && ownership.is_owned_by(relative, mod_info.id) {
+            moves.push((backup, TargetLocation::resolve(game_dir, relative)));
+        }
+    }
+
+    // 先删除 .dat 行，避免游戏加载已移走的文件
+    remove_mod_dat_entries(game_dir, mod_info.id, &mod_info.dat_entries)?;
+    if let Err(e) = revert_mod_text_changes(game_dir, mod_info, &mut [])
+        .and_then(|_| apply_moves(&moves))
+    {
+        let _ = reapply_mod_text_changes(game_dir, mod_info);
+        let _ = apply_mod_dat_entries(game_dir, mod_info.id, &mod_info.dat_entries);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// 启用 MOD：重新写入 .dat 行和合并到文本表的条目，把原文件重新移回备份区，再把禁用的文件放回安装位置
+pub fn enable_mod_files(
+    game_dir: &Path,
+    mod_info: &G2MModInfo,
+    ownership: &FileOwnershipIndex,
+) -> Result<(), String> {
+    ensure_has_manifest(mod_info)?;
+
+    let backup_dir = get_mod_backup_dir(game_dir, mod_info.id);
+    let mut moves = Vec::new();
+    for relative in &mod_info.backed_up_files {
+        let original = TargetLocation::resolve(game_dir, relative);
+        if original.is_file() && ownership.is_owned_by(relative, mod_info.id) {
+            moves.push((original, TargetLocation::File(backup_dir.join(work_path(relative)))));
+        }
+    }
+    for relative in &mod_info.installed_files {
+        let disabled = disabled_location(game_dir, mod_info.id, relative);
+        if disabled.is_file() && ownership.is_owned_by(relative, mod_info.id) {
+            moves.push((disabled, TargetLocation::resolve(game_dir, relative)));
+        }
+    }
+
+    apply_mod_dat_entries(game_dir, mod_info.id, &mod_info.dat_entries)?;
+    if let Err(e) = reapply_mod_text_changes(game_dir, mod_info).and_then(|_| apply_moves(&moves)) {
+        let _ = revert_mod_text_changes(game_dir, mod_info, &mut []);
+        let _ = remove_mod_dat_entries(game_dir, mod_info.id, &mod_info.dat_entries);
+        return Err(e);
+    }
+    remove_mod_work_dir(&get_mod_disabled_dir(game_dir, mod_info.id));
+    Ok(())
+}
+
+/// 没有安装清单的旧记录无法确定要移动哪些文件
+fn ensure_has_manifest(mod_info: &G2MModInfo) -> Result<(), String> {
+    if mod_info.installed_files.is_empty() {
+        return Err(format!(
+            "MOD \"{}\" 没有安装清单，无法启用或禁用，请重新安装该 MOD",
+            mod_info.name
+        ));
+    }
+    Ok(())
+}
+
+/// 依次移动文件，任一失败时把已移动的文件移回原处
+fn apply_moves(moves: &[(TargetLocation, TargetLocation)]) -> Result<(), String> {
+    for (index, (from, to)) in moves.iter().enumerate() {
+        if let Err(e) = move_location(from, to) {
+            for (done_from, done_to) in moves[..index].iter().rev() {
+                let _ = move_location(done_to, done_from);
+            }
+            return Err(format!(
+                "移动文件失败: {}\n源路径: {}\n目标路径: {}",
+                e, from, to
+            ));
+        }
+    }
+    Ok(())
+}
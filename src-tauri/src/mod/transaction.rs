@@ -0,0 +1,603 @@
+use crate::game::types::{PlannedFileCopy, UserModInstallResult};
+use crate::game::utils::get_g2m_dir_path;
+use crate::mod_core::img_target::{move_location, work_path, TargetLocation};
+use crate::mod_core::utils::is_subpath_of;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 获取 MOD 的备份目录：.gtamodx/backup/<mod-id>/
+pub fn get_mod_backup_dir(game_dir: &Path, mod_id: u32) -> PathBuf {
+    get_g2m_dir_path(&game_dir.to_string_lossy())
+        .join("backup")
+        .join(mod_id.to_string())
+}
+
+/// 获取 MOD 的暂存目录：.gtamodx/staging/<mod-id>/
+fn get_mod_staging_dir(game_dir: &Path, mod_id: u32) -> PathBuf {
+    get_g2m_dir_path(&game_dir.to_string_lossy())
+        .join("staging")
+        .join(mod_id.to_string())
+}
+
+/// 获取 MOD 更新时旧版本文件的临时存放目录：.gtamodx/update/<mod-id>/
+fn get_mod_update_dir(game_dir: &Path, mod_id: u32) -> PathBuf {
+    get_g2m_dir_path(&game_dir.to_string_lossy())
+        .join("update")
+        .join(mod_id.to_string())
+}
+
+/// 删除 MOD 的工作目录（暂存、备份、禁用区），其上级目录为空时一并删除
+pub fn remove_mod_work_dir(dir: &Path) {
+    let _ = fs::remove_dir_all(dir);
+    if let Some(parent) = dir.parent() {
+        let _ = fs::remove_dir(parent);
+    }
+}
+
+/// 待复制的单个文件
+struct FileCopy {
+    source: PathBuf,
+    relative: String, // 相对游戏目录的目标路径，可以是 img:// 目标
+    target: TargetLocation,
+}
+
+/// 已执行的操作，用于回滚
+enum AppliedOp {
+    /// 写入文件，被覆盖的原文件移到了 backup
+    Copy {
+        target: TargetLocation,
+        relative: String,
+        backup: Option<PathBuf>,
+        is_previous: bool, // backup 中是该 MOD 的旧版本文件（更新时），而不是原文件备份
+    },
+    /// 移动文件（更新时删除旧文件、恢复原文件）
+    Move {
+        from: TargetLocation,
+        to: TargetLocation,
+    },
+}
+
+/// 更新已安装的 MOD 时的旧版本信息
+struct PreviousInstall {
+    files: Vec<String>,     // 旧版本写入且仍归该 MOD 所有的文件（小写）
+    stale: Vec<String>,     // 新版本不再包含、需要删除的文件
+    backed_up: Vec<String>, // 旧版本安装时备份的原文件
+}
+
+/// MOD 安装事务
+/// 安装函数只登记要创建的目录和要复制的文件，不直接修改游戏目录；
+/// commit 时先把所有源文件复制到暂存区，再把被覆盖的原文件移入 .gtamodx/backup/<mod-id>/，
+/// 最后将暂存文件移动到位。任一步骤失败都会恢复到安装前的状态。
+pub struct InstallTransaction {
+    game_dir: PathBuf,
+    mod_id: u32,
+    directories: Vec<PathBuf>,
+    copies: Vec<FileCopy>,
+    previous: Option<PreviousInstall>,
+}
+
+impl InstallTransaction {
+    pub fn new(game_dir: &Path, mod_id: u32) -> Self {
+        Self {
+            game_dir: game_dir.to_path_buf(),
+            mod_id,
+            directories: Vec::new(),
+            copies: Vec::new(),
+            previous: None,
+        }
+    }
+
+    /// 将事务设为更新已安装的 MOD
+    /// 旧版本的文件直接替换（不作为原文件备份），stale_files 会被删除并恢复其原文件，已有的备份保留
+    pub fn replace_previous_install(
+        &mut self,
+        previous_files: &[String],
+        stale_files: &[String],
+        backed_up_files: &[String],
+    ) {
+        self.previous = Some(PreviousInstall {
+            files: previous_files.iter().map(|f| f.to_lowercase()).collect(),
+            stale: stale_files.to_vec(),
+            backed_up: backed_up_files.to_vec(),
+        });
+    }
+
+    /// 移除计划中写入指定文件的复制（相对游戏目录，不区分大小写）
+    pub fn exclude_files(&mut self, relatives: &[String]) {
+        let excluded: Vec<String> = relatives.iter().map(|r| r.to_lowercase()).collect();
+        let keep: Vec<bool> = self
+            .copies
+            .iter()
+            .map(|c| !excluded.contains(&c.relative.to_lowercase()))
+            .collect();
+        let mut keep = keep.into_iter();
+        self.copies.retain(|_| keep.next().unwrap_or(true));
+    }
+
+    /// 将路径转换为相对游戏目录的路径（统一使用 / 分隔）
+    pub fn relative_path(&self, path: &Path) -> String {
+        path.strip_prefix(&self.game_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    /// 获取所有将要写入的文件（相对游戏目录）
+    pub fn planned_files(&self) -> Vec<String> {
+        self.copies.iter().map(|c| c.relative.clone()).collect()
+    }
+
+    /// 获取计划中的文件复制（源路径 -> 相对游戏目录的目标路径）
+    pub fn planned_copies(&self) -> Vec<PlannedFileCopy> {
+        self.copies
+            .iter()
+            .map(|c| PlannedFileCopy {
+                source: c.source.to_string_lossy().to_string(),
+                target: c.relative.clone(),
+            })
+            .collect()
+    }
+
+    /// 获取提交时将要新建的目录（相对游戏目录，从外到内）
+    pub fn planned_directories(&self) -> Vec<String> {
+        let required = self
+            .directories
+            .iter()
+            .map(PathBuf::as_path)
+            .chain(self.copies.iter().filter_map(|c| c.target.parent_dir()));
+
+        let mut missing: Vec<PathBuf> = Vec::new();
+        for dir in required {
+            for path in missing_dirs(dir, &self.game_dir) {
+                if !missing.contains(&path) {
+                    missing.push(path);
+                }
+            }
+        }
+        missing.iter().map(|d| self.relative_path(d)).collect()
+    }
+
+    /// 登记需要存在的目录（提交时创建）
+    pub fn create_dir_all(&mut self, dir: &Path) -> io::Result<()> {
+        if !self.directories.iter().any(|d| d == dir) {
+            self.directories.push(dir.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// 登记单个文件的复制；同一目标被登记多次时以最后一次为准
+    pub fn copy_file(&mut self, src: &Path, dst: &Path) -> io::Result<()> {
+        if !src.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("源文件不存在: {}", src.display()),
+            ));
+        }
+        self.push_copy(src, self.relative_path(dst), TargetLocation::File(dst.to_path_buf()));
+        Ok(())
+    }
+
+    /// 登记写入 IMG 档案条目的复制，target 为 img://<档案路径>/<条目名>
+    pub fn copy_into_img(&mut self, src: &Path, target: &str) -> io::Result<()> {
+        if !src.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("源文件不存在: {}", src.display()),
+            ));
+        }
+        let location = TargetLocation::resolve(&self.game_dir, target);
+        self.push_copy(src, target.to_string(), location);
+        Ok(())
+    }
+
+    fn push_copy(&mut self, src: &Path, relative: String, target: TargetLocation) {
+        let key = relative.to_lowercase();
+        self.copies.retain(|c| c.target != target && c.relative.to_lowercase() != key);
+        self.copies.push(FileCopy {
+            source: src.to_path_buf(),
+            relative,
+            target,
+        });
+    }
+
+    /// 递归登记目录的复制
+    pub fn copy_dir(&mut self, src: &Path, dst: &Path) -> io::Result<()> {
+        // 检查目标路径是否是源路径的子目录（会导致无限递归）
+        if is_subpath_of(dst, src) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "目标路径是源路径的子目录，会导致无限递归: {} -> {}",
+                    src.display(),
+                    dst.display()
+                ),
+            ));
+        }
+
+        self.create_dir_all(dst)?;
+
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            let entry_path = entry.path();
+            let target = dst.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                self.copy_dir(&entry_path, &target)?;
+            } else {
+                self.copy_file(&entry_path, &target)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 提交事务：暂存 -> 备份 -> 移动到位，失败时回滚
+    pub fn commit(self) -> Result<UserModInstallResult, String> {
+        self.commit_with(|_| Ok(()))
+    }
+
+    /// 提交事务，文件全部就位后执行 finish（如写入 MOD 记录）
+    /// finish 失败时与文件操作失败一样回滚，游戏目录恢复到安装前的状态
+    pub fn commit_with<F>(self, finish: F) -> Result<UserModInstallResult, String>
+    where
+        F: FnOnce(&UserModInstallResult) -> Result<(), String>,
+    {
+        let staging_dir = get_mod_staging_dir(&self.game_dir, self.mod_id);
+        let backup_dir = get_mod_backup_dir(&self.game_dir, self.mod_id);
+        let update_dir = get_mod_update_dir(&self.game_dir, self.mod_id);
+
+        // 清理上次残留的暂存区；新安装时该 ID 没有对应的 MOD 记录，残留的备份一并清理
+        remove_mod_work_dir(&staging_dir);
+        remove_mod_work_dir(&update_dir);
+        if self.previous.is_none() {
+            remove_mod_work_dir(&backup_dir);
+        }
+
+        // 第一阶段：复制所有源文件到暂存区，此时游戏目录不受影响
+        let mut staged = Vec::with_capacity(self.copies.len());
+        for copy in &self.copies {
+            let relative = copy.relative.clone();
+            let staged_path = staging_dir.join(work_path(&relative));
+            let result = staged_path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::copy(&copy.source, &staged_path).map(|_| ()));
+            if let Err(e) = result {
+                remove_mod_work_dir(&staging_dir);
+                return Err(format!(
+                    "暂存文件失败: {}\n源路径: {}",
+                    e,
+                    copy.source.display()
+                ));
+            }
+            staged.push((staged_path, relative));
+        }
+
+        // 第二阶段：创建目录、备份被覆盖的文件、移动暂存文件到位，更新时删除旧文件
+        let mut created = Vec::new();
+        let mut applied = Vec::new();
+        let result = self
+            .apply(
+                &staged,
+                &backup_dir,
+                &update_dir,
+                &mut created,
+                &mut applied,
+            )
+            .and_then(|_| {
+                let result = self.install_result(&staged, &created, &applied, &backup_dir);
+                finish(&result)?;
+                Ok(result)
+            });
+
+        remove_mod_work_dir(&staging_dir);
+
+        match result {
+            Ok(result) => {
+                remove_mod_work_dir(&update_dir);
+                // 没有覆盖任何文件时不保留空的备份目录
+                if result.backed_up_files.is_empty() {
+                    remove_mod_work_dir(&backup_dir);
+                }
+                Ok(result)
+            }
+            Err(e) => {
+                let rollback_errors = rollback(&applied, &created);
+                remove_mod_work_dir(&update_dir);
+                if self.previous.is_none() {
+                    remove_mod_work_dir(&backup_dir);
+                }
+                if rollback_errors.is_empty() {
+                    Err(format!("{}\n已回滚所有更改", e))
+                } else {
+                    Err(format!(
+                        "{}\n回滚时出现错误，请检查以下路径:\n{}",
+                        e,
+                        rollback_errors.join("\n")
+                    ))
+                }
+            }
+        }
+    }
+
+    /// 文件全部就位后的安装结果
+    fn install_result(
+        &self,
+        staged: &[(PathBuf, String)],
+        created: &[PathBuf],
+        applied: &[AppliedOp],
+        backup_dir: &Path,
+    ) -> UserModInstallResult {
+        let mut backed_up_files: Vec<String> = applied
+            .iter()
+            .filter_map(|op| match op {
+                AppliedOp::Copy {
+                    relative,
+                    backup: Some(_),
+                    is_previous: false,
+                    ..
+                } => Some(relative.clone()),
+                _ => None,
+            })
+            .collect();
+        // 更新时保留旧版本的备份（已恢复到游戏目录的除外）
+        if let Some(previous) = &self.previous {
+            for relative in &previous.backed_up {
+                if backup_dir.join(work_path(relative)).is_file()
+                    && !backed_up_files.contains(relative)
+                {
+                    backed_up_files.push(relative.clone());
+                }
+            }
+        }
+
+        UserModInstallResult {
+            installed_files: staged.iter().map(|(_, rel)| rel.clone()).collect(),
+            created_directories: created.iter().map(|d| self.relative_path(d)).collect(),
+            backed_up_files,
+            conflicts: Vec::new(),
+            requirement_issues: Vec::new(),
+            selected_options: BTreeMap::new(),
+            model_collisions: Vec::new(),
+            asset_mismatches: Vec::new(),
+            missing_cleo_plugins: Vec::new(),
+        }
+    }
+
+    fn apply(
+        &self,
+        staged: &[(PathBuf, String)],
+        backup_dir: &Path,
+        update_dir: &Path,
+        created: &mut Vec<PathBuf>,
+        applied: &mut Vec<AppliedOp>,
+    ) -> Result<(), String> {
+        for dir in &self.directories {
+            create_missing_dirs(dir, &self.game_dir, created)
+                .map_err(|e| format!("创建目录失败: {}\n目标路径: {}", e, dir.display()))?;
+        }
+
+        for (copy, (staged_path, relative)) in self.copies.iter().zip(staged) {
+            if let Some(parent) = copy.target.parent_dir() {
+                create_missing_dirs(parent, &self.game_dir, created)
+                    .map_err(|e| format!("创建目录失败: {}\n目标路径: {}", e, parent.display()))?;
+            }
+
+            // 备份将被覆盖的原文件；更新时旧版本文件移到临时目录，成功后删除
+            let is_previous = self
+                .previous
+                .as_ref()
+                .is_some_and(|p| p.files.contains(&relative.to_lowercase()));
+            let backup = if copy.target.exists() {
+                if copy.target.is_dir() {
+                    return Err(format!(
+                        "目标路径是已存在的目录，无法覆盖为文件: {}",
+                        copy.target
+                    ));
+                }
+                let backup_path = if is_previous {
+                    update_dir.join(work_path(relative))
+                } else {
+                    backup_dir.join(work_path(relative))
+                };
+                move_location(&copy.target, &TargetLocation::File(backup_path.clone()))
+                    .map_err(|e| format!("备份原文件失败: {}\n路径: {}", e, copy.target))?;
+                Some(backup_path)
+            } else {
+                None
+            };
+
+            let move_result =
+                move_location(&TargetLocation::File(staged_path.clone()), &copy.target);
+            applied.push(AppliedOp::Copy {
+                target: copy.target.clone(),
+                relative: relative.clone(),
+                backup,
+                is_previous,
+            });
+            move_result.map_err(|e| format!("复制文件失败: {}\n目标路径: {}", e, copy.target))?;
+        }
+
+        // 更新时删除新版本不再包含的文件，并恢复其原文件
+        if let Some(previous) = &self.previous {
+            for relative in &previous.stale {
+                let target = TargetLocation::resolve(&self.game_dir, relative);
+                if target.is_file() {
+                    let moved = TargetLocation::File(update_dir.join(work_path(relative)));
+                    move_location(&target, &moved)
+                        .map_err(|e| format!("删除旧文件失败: {}\n路径: {}", e, target))?;
+                    applied.push(AppliedOp::Move {
+                        from: target.clone(),
+                        to: moved,
+                    });
+                }
+
+                let backup = TargetLocation::File(backup_dir.join(work_path(relative)));
+                if previous.backed_up.contains(relative) && backup.is_file() {
+                    move_location(&backup, &target)
+                        .map_err(|e| format!("恢复原文件失败: {}\n路径: {}", e, target))?;
+                    applied.push(AppliedOp::Move {
+                        from: backup,
+                        to: target,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// 获取目录及其上级目录中尚不存在的部分（从外到内，不超出游戏目录）
+fn missing_dirs(dir: &Path, game_dir: &Path) -> Vec<PathBuf> {
+    let mut missing = Vec::new();
+    let mut current = Some(dir);
+    while let Some(path) = current {
+        if path.exists() || path == game_dir {
+            break;
+        }
+        missing.push(path.to_path_buf());
+        current = path.parent();
+    }
+    missing.reverse();
+    missing
+}
+
+/// 创建目录及其缺失的上级目录，按从外到内的顺序记录新建的目录
+fn create_missing_dirs(dir: &Path, game_dir: &Path, created: &mut Vec<PathBuf>) -> io::Result<()> {
+    if dir.exists() {
+        return Ok(());
+    }
+
+    let missing = missing_dirs(dir, game_dir);
+    fs::create_dir_all(dir)?;
+    created.extend(missing);
+    Ok(())
+}
+
+/// 移动文件，跨分区时退回到复制后删除
+pub fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to)?;
+    fs::remove_file(from)
+}
+
+/// 回滚已执行的操作：删除新写入的文件、恢复备份、撤销文件移动、删除新建的目录
+fn rollback(applied: &[AppliedOp], created: &[PathBuf]) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for op in applied.iter().rev() {
+        match op {
+            AppliedOp::Copy { target, backup, .. } => {
+                if target.is_file() {
+                    if let Err(e) = target.remove() {
+                        errors.push(format!("{} ({})", target, e));
+                        continue;
+                    }
+                }
+                if let Some(backup) = backup {
+                    if let Err(e) = move_location(&TargetLocation::File(backup.clone()), target) {
+                        errors.push(format!("{} ({})", target, e));
+                    }
+                }
+            }
+            AppliedOp::Move { from, to } => {
+                if let Err(e) = move_location(to, from) {
+                    errors.push(format!("{} ({})", from, e));
+                }
+            }
+        }
+    }
+
+    for dir in created.iter().rev() {
+        let _ = fs::remove_dir(dir);
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 每个测试使用独立的临时目录，返回（游戏目录, MOD 源目录）
+    fn temp_dirs(name: &str) -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!("g2m-tx-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let game_dir = root.join("game");
+        let source = root.join("source");
+        fs::create_dir_all(game_dir.join("data")).unwrap();
+        fs::create_dir_all(&source).unwrap();
+        (game_dir, source)
+    }
+
+    #[test]
+    fn failed_finish_rolls_back_files() {
+        let (game_dir, source) = temp_dirs("finish");
+        fs::write(game_dir.join("data/a.txt"), "original").unwrap();
+        fs::write(source.join("a.txt"), "modded").unwrap();
+        fs::write(source.join("b.txt"), "new").unwrap();
+
+        let mut transaction = InstallTransaction::new(&game_dir, 7);
+        transaction
+            .copy_file(&source.join("a.txt"), &game_dir.join("data/a.txt"))
+            .unwrap();
+        transaction
+            .copy_file(&source.join("b.txt"), &game_dir.join("extra/b.txt"))
+            .unwrap();
+        let error = transaction
+            .commit_with(|result| {
+                assert_eq!(result.backed_up_files, vec!["data/a.txt"]);
+                assert_eq!(result.created_directories, vec!["extra"]);
+                Err("记录失败".to_string())
+            })
+            .unwrap_err();
+
+        assert!(error.starts_with("记录失败"), "{}", error);
+        assert_eq!(
+            fs::read_to_string(game_dir.join("data/a.txt")).unwrap(),
+            "original"
+        );
+        assert!(!game_dir.join("extra").exists());
+        assert!(!get_mod_backup_dir(&game_dir, 7).exists());
+        fs::remove_dir_all(game_dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn failed_move_rolls_back_earlier_files() {
+        let (game_dir, source) = temp_dirs("move");
+        fs::write(game_dir.join("data/a.txt"), "original").unwrap();
+        // 第三个文件的目标是已存在的目录，前两个文件已移到位后才失败
+        fs::create_dir_all(game_dir.join("data/c.txt")).unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(source.join(name), "modded").unwrap();
+        }
+
+        let mut transaction = InstallTransaction::new(&game_dir, 8);
+        transaction
+            .copy_file(&source.join("a.txt"), &game_dir.join("data/a.txt"))
+            .unwrap();
+        transaction
+            .copy_file(&source.join("b.txt"), &game_dir.join("new/b.txt"))
+            .unwrap();
+        transaction
+            .copy_file(&source.join("c.txt"), &game_dir.join("data/c.txt"))
+            .unwrap();
+        let error = transaction.commit().unwrap_err();
+
+        assert!(error.contains("已回滚所有更改"), "{}", error);
+        assert_eq!(
+            fs::read_to_string(game_dir.join("data/a.txt")).unwrap(),
+            "original"
+        );
+        assert!(!game_dir.join("new").exists());
+        assert!(game_dir.join("data/c.txt").is_dir());
+        assert!(!get_mod_backup_dir(&game_dir, 8).exists());
+        assert!(!get_mod_staging_dir(&game_dir, 8).exists());
+        // 源文件不受影响
+        assert!(source.join("a.txt").is_file());
+        fs::remove_dir_all(game_dir.parent().unwrap()).unwrap();
+    }
+}
@@ -1,5 +1,5 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// 检查文件是否为贴图或模型文件
 pub fn is_texture_or_model_file(file_path: &Path) -> bool {
@@ -57,7 +57,7 @@ pub fn is_texture_or_model_directory(dir_path: &Path) -> bool {
 }
 
 /// 检查路径是否是另一个路径的子路径（规范化比较）
-fn is_subpath_of(path: &Path, parent: &Path) -> bool {
+pub(crate) fn is_subpath_of(path: &Path, parent: &Path) -> bool {
     // 尝试规范化路径
     let path_canonical = path.canonicalize().ok();
     let parent_canonical = parent.canonicalize().ok();
@@ -72,42 +72,120 @@ fn is_subpath_of(path: &Path, parent: &Path) -> bool {
     path_str.starts_with(&parent_str)
 }
 
-/// 递归复制目录
-pub fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
-    // 检查源路径和目标路径是否相同
-    if is_subpath_of(dst, src) && is_subpath_of(src, dst) {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            format!("源路径和目标路径相同或会导致循环复制: {} -> {}", src.display(), dst.display())
-        ));
+/// 检查配置中的相对路径：不能为空、不能是绝对路径或带盘符，也不能包含 ..
+pub(crate) fn check_relative_path(path: &str) -> Result<(), &'static str> {
+    let normalized = path.trim().replace('\\', "/");
+    if normalized.is_empty() {
+        return Err("路径为空");
     }
-    
-    // 检查目标路径是否是源路径的子目录（会导致无限递归）
-    if is_subpath_of(dst, src) {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            format!("目标路径是源路径的子目录，会导致无限递归: {} -> {}", src.display(), dst.display())
-        ));
+    if normalized.starts_with('/') {
+        return Err("不能使用绝对路径");
     }
-    
-    if !dst.exists() {
-        fs::create_dir_all(dst)?;
+    if normalized.contains(':') {
+        return Err("不能包含盘符或冒号");
+    }
+    if normalized.split('/').any(|part| part == "..") {
+        return Err("不能包含 ..");
+    }
+    Ok(())
+}
+
+/// 检查路径解析符号链接后是否仍位于根目录内
+/// 路径不存在时检查其最深的已存在上级目录
+pub(crate) fn resolves_within(path: &Path, root: &Path) -> bool {
+    let Ok(root) = root.canonicalize() else {
+        return false;
+    };
+
+    let mut existing = Some(path);
+    while let Some(current) = existing {
+        if let Ok(canonical) = current.canonicalize() {
+            return canonical.starts_with(&root);
+        }
+        existing = current.parent();
     }
+    false
+}
 
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let entry_path = entry.path();
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            let dst_entry = dst.join(entry.file_name());
-            // 再次检查，防止递归复制到自身
-            if !is_subpath_of(&dst_entry, &entry_path) {
-                copy_dir_all(&entry_path, &dst_entry)?;
+/// 查找目录中解析后位于根目录之外的条目（通过符号链接逃逸）
+pub(crate) fn find_escaping_entry(dir: &Path, root: &Path) -> Option<PathBuf> {
+    let entries = fs::read_dir(dir).ok()?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !resolves_within(&path, root) {
+            return Some(path);
+        }
+        let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+        if is_dir {
+            if let Some(escaping) = find_escaping_entry(&path, root) {
+                return Some(escaping);
             }
-        } else {
-            fs::copy(&entry_path, dst.join(entry.file_name()))?;
         }
     }
-    Ok(())
+    None
 }
 
+/// 比较两个文件内容是否相同（先比较大小）
+pub(crate) fn files_identical(a: &Path, b: &Path) -> bool {
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(ma), Ok(mb)) if ma.is_file() && mb.is_file() && ma.len() == mb.len() => {}
+        _ => return false,
+    }
+    match (fs::read(a), fs::read(b)) {
+        (Ok(da), Ok(db)) => da == db,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relative_path_rejects_escapes() {
+        assert!(check_relative_path("data/handling.cfg").is_ok());
+        assert!(check_relative_path("models\\gta3.img").is_ok());
+        assert!(check_relative_path("").is_err());
+        assert!(check_relative_path("  ").is_err());
+        assert!(check_relative_path("../gta_sa.exe").is_err());
+        assert!(check_relative_path("data\\..\\..\\gta_sa.exe").is_err());
+        assert!(check_relative_path("/etc/passwd").is_err());
+        assert!(check_relative_path("\\Windows\\system32").is_err());
+        assert!(check_relative_path("C:\\Windows").is_err());
+        assert!(check_relative_path("C:gta_sa.exe").is_err());
+        // 文件名中的 .. 不是上级目录
+        assert!(check_relative_path("data/a..b.txt").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_out_of_root_is_found() {
+        use std::os::unix::fs::symlink;
+
+        let base = std::env::temp_dir().join(format!("g2m-utils-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let root = base.join("mod");
+        let outside = base.join("outside");
+        fs::create_dir_all(root.join("data")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(root.join("data/handling.cfg"), b"handling").unwrap();
+
+        assert!(resolves_within(&root.join("data/handling.cfg"), &root));
+        // 尚不存在的路径按已存在的上级目录判断
+        assert!(resolves_within(&root.join("data/new/file.txt"), &root));
+        assert!(!resolves_within(&outside, &root));
+        assert_eq!(find_escaping_entry(&root, &root), None);
+
+        // 指向根目录内的链接不算逃逸
+        symlink(root.join("data"), root.join("inner")).unwrap();
+        assert_eq!(find_escaping_entry(&root, &root), None);
+
+        symlink(&outside, root.join("data/escape")).unwrap();
+        assert!(!resolves_within(&root.join("data/escape/file.txt"), &root));
+        assert_eq!(
+            find_escaping_entry(&root, &root),
+            Some(root.join("data/escape"))
+        );
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,100 @@
+use crate::game::descriptors::find_game_descriptor;
+use crate::game::utils::{detect_game_type_from_exe, read_g2m_json};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 获取游戏类型：优先读取 .gtamodx 配置，否则从游戏目录中的 exe 文件名识别
+pub fn detect_game_type(game_dir: &Path) -> Option<String> {
+    read_g2m_json(game_dir.to_str().unwrap_or(""))
+        .and_then(|c| c.r#type)
+        .or_else(|| {
+            fs::read_dir(game_dir).ok()?.flatten().find_map(|entry| {
+                let path = entry.path();
+                if !path.is_file() {
+                    return None;
+                }
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .and_then(detect_game_type_from_exe)
+            })
+        })
+}
+
+/// 确定 CLEO 目录位置（优先使用已存在的目录）
+/// 都不存在时使用游戏描述中的目录名（GTA SA 为小写 cleo，III/VC 为 CLEO）
+pub fn resolve_cleo_dir(game_dir: &Path) -> PathBuf {
+    let cleo_dir = game_dir.join("CLEO");
+    let cleo_lower_dir = game_dir.join("cleo");
+
+    if cleo_dir.exists() {
+        cleo_dir
+    } else if cleo_lower_dir.exists() {
+        cleo_lower_dir
+    } else {
+        let descriptor = detect_game_type(game_dir)
+            .as_deref()
+            .and_then(find_game_descriptor);
+        game_dir.join(descriptor.map_or("CLEO", |d| d.cleo_dir.as_str()))
+    }
+}
+
+/// 确定 ASI/DLL 插件目录（优先级：plugins > scripts，都不存在时使用 plugins，安装时创建）
+/// 自动识别安装与 ${plugins}、${scripts} 变量使用同一规则
+pub fn resolve_plugin_dir(game_dir: &Path) -> PathBuf {
+    let plugins_dir = game_dir.join("plugins");
+    let scripts_dir = game_dir.join("scripts");
+
+    if !plugins_dir.exists() && scripts_dir.exists() {
+        scripts_dir
+    } else {
+        plugins_dir
+    }
+}
+
+/// 解析单个路径变量，返回对应目录（相对游戏目录，根目录为空字符串）
+fn resolve_variable(game_dir: &Path, name: &str) -> Option<String> {
+    let dir = match name {
+        "cleo" => resolve_cleo_dir(game_dir),
+        "cleo_redux" => game_dir.join("plugins").join("CLEO"),
+        "modloader" => game_dir.join("modloader"),
+        "plugins" | "scripts" => resolve_plugin_dir(game_dir),
+        _ => return None,
+    };
+
+    Some(
+        dir.strip_prefix(game_dir)
+            .unwrap_or(&dir)
+            .to_string_lossy()
+            .replace('\\', "/"),
+    )
+}
+
+/// 将 g2m.json 中的目标路径里的 ${cleo}、${cleo_redux}、${modloader}、${plugins}、${scripts}
+/// 变量替换为当前游戏目录下的实际位置，返回相对游戏目录的路径
+pub fn resolve_path_variables(game_dir: &Path, target: &str) -> Result<String, String> {
+    let mut resolved = String::new();
+    let mut rest = target;
+
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..]
+            .find('}')
+            .map(|offset| start + offset)
+            .ok_or_else(|| format!("路径变量缺少结束符 }}: {}", target))?;
+        let name = &rest[start + 2..end];
+        let value = resolve_variable(game_dir, &name.to_lowercase())
+            .ok_or_else(|| format!("未知的路径变量: ${{{}}}\n目标路径: {}", name, target))?;
+
+        resolved.push_str(&rest[..start]);
+        resolved.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    resolved.push_str(rest);
+
+    // 变量解析为根目录时会留下多余的分隔符，如 "/x.asi"
+    let normalized = resolved.replace('\\', "/");
+    Ok(normalized
+        .split('/')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/"))
+}
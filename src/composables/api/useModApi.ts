@@ -41,7 +41,7 @@ export function useModApi() {
       const response: any = await tauriInvoke('get_game_mods', { gameDir });
 
       if (response?.success && response?.data) {
-        return response.data;
+        return response.data.mods;
       } else {
         const errorMsg = response?.error || '获取MOD列表失败';
         const detailMsg = `游戏目录: ${gameDir}\n错误: ${errorMsg}`;